@@ -0,0 +1,196 @@
+use fastnoise_lite::{FastNoiseLite, NoiseType};
+use serde::{Deserialize, Serialize};
+
+/// Broad terrain regions selected by low-frequency biome noise.
+///
+/// Variants are ordered along the biome noise axis, so each biome only ever
+/// borders its neighbours in this list.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Biome {
+    Desert,
+    Plains,
+    Forest,
+    Mountains,
+}
+
+/// Surface block family used by a biome.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Ground {
+    Grass,
+    Sand,
+}
+
+/// Generation parameters a biome overrides.
+#[derive(Clone, Copy, Debug)]
+pub struct BiomeParams {
+    /// Multiplier applied to the summed noise layer height.
+    pub amplitude: f32,
+    /// Weight of the ridged cliff noise.
+    pub ridge: f32,
+    /// Surface block family.
+    pub ground: Ground,
+    /// World height above which the surface turns to snow.
+    pub snow_line: f32,
+    /// Tree scatter density in `0.0..=1.0`.
+    pub tree_density: f32,
+    /// Boulder scatter density in `0.0..=1.0`.
+    pub boulder_density: f32,
+}
+
+const BIOMES: [Biome; 4] = [
+    Biome::Desert,
+    Biome::Plains,
+    Biome::Forest,
+    Biome::Mountains,
+];
+
+/// Biome noise values separating neighbouring entries of [`BIOMES`].
+const THRESHOLDS: [f32; 3] = [-0.25, 0.0, 0.25];
+
+impl Biome {
+    pub fn params(self) -> BiomeParams {
+        match self {
+            Biome::Desert => BiomeParams {
+                amplitude: 0.4,
+                ridge: 0.0,
+                ground: Ground::Sand,
+                snow_line: NO_SNOW_LINE,
+                tree_density: 0.0,
+                boulder_density: 0.15,
+            },
+            Biome::Plains => BiomeParams {
+                amplitude: 0.6,
+                ridge: 0.0,
+                ground: Ground::Grass,
+                snow_line: 200.0,
+                tree_density: 0.1,
+                boulder_density: 0.05,
+            },
+            Biome::Forest => BiomeParams {
+                amplitude: 0.8,
+                ridge: 0.2,
+                ground: Ground::Grass,
+                snow_line: 200.0,
+                tree_density: 0.3,
+                boulder_density: 0.08,
+            },
+            Biome::Mountains => BiomeParams {
+                amplitude: 1.0,
+                ridge: 1.0,
+                ground: Ground::Grass,
+                snow_line: 120.0,
+                tree_density: 0.08,
+                boulder_density: 0.2,
+            },
+        }
+    }
+}
+
+/// Persisted biome layout settings.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct BiomeSettings {
+    pub seed: i32,
+    /// Frequency of the biome noise; lower values produce larger regions.
+    pub frequency: f32,
+    /// Width of the blend band between neighbouring biomes in noise units.
+    pub blend_width: f32,
+}
+
+impl Default for BiomeSettings {
+    fn default() -> Self {
+        Self {
+            seed: 7777,
+            frequency: 0.0015,
+            blend_width: 0.1,
+        }
+    }
+}
+
+/// Biome parameters resolved for a single world column.
+#[derive(Clone, Copy, Debug)]
+pub struct BiomeSample {
+    /// Biome with the largest blend weight.
+    pub dominant: Biome,
+    /// Amplitude multiplier blended across neighbouring biomes.
+    pub amplitude: f32,
+    /// Ridge weight blended across neighbouring biomes.
+    pub ridge: f32,
+    /// Snow line blended across neighbouring biomes.
+    pub snow_line: f32,
+    /// Tree density blended across neighbouring biomes.
+    pub tree_density: f32,
+    /// Boulder density blended across neighbouring biomes.
+    pub boulder_density: f32,
+}
+
+/// Samples biome regions from a low-frequency noise field.
+pub struct BiomeSampler {
+    noise: FastNoiseLite,
+    blend_width: f32,
+}
+
+impl BiomeSampler {
+    pub fn new(settings: &BiomeSettings) -> Self {
+        let mut noise = FastNoiseLite::with_seed(settings.seed);
+        noise.set_noise_type(Some(NoiseType::Perlin));
+        noise.set_frequency(Some(settings.frequency));
+        Self {
+            noise,
+            blend_width: settings.blend_width.max(0.0),
+        }
+    }
+
+    pub fn sample(&self, wx: i32, wz: i32) -> BiomeSample {
+        let v = self.noise.get_noise_2d(wx as f32, wz as f32);
+        let weights = self.weights(v);
+
+        let mut dominant = 0;
+        let mut amplitude = 0.0;
+        let mut ridge = 0.0;
+        let mut snow_line = 0.0;
+        let mut tree_density = 0.0;
+        let mut boulder_density = 0.0;
+        for (i, (biome, weight)) in BIOMES.iter().zip(weights).enumerate() {
+            if weight > weights[dominant] {
+                dominant = i;
+            }
+            let params = biome.params();
+            amplitude += params.amplitude * weight;
+            ridge += params.ridge * weight;
+            snow_line += params.snow_line * weight;
+            tree_density += params.tree_density * weight;
+            boulder_density += params.boulder_density * weight;
+        }
+
+        BiomeSample {
+            dominant: BIOMES[dominant],
+            amplitude,
+            ridge,
+            snow_line,
+            tree_density,
+            boulder_density,
+        }
+    }
+
+    /// Blend weights of every biome for a noise value. Weights sum to one.
+    fn weights(&self, v: f32) -> [f32; 4] {
+        let half = self.blend_width * 0.5;
+        let step = |t: f32| {
+            if half <= 0.0 {
+                return if v >= t { 1.0 } else { 0.0 };
+            }
+            smoothstep(t - half, t + half, v)
+        };
+        let s = THRESHOLDS.map(step);
+        [1.0 - s[0], s[0] - s[1], s[1] - s[2], s[2]]
+    }
+}
+
+/// Snow line for biomes that never get snow, kept finite so it blends.
+const NO_SNOW_LINE: f32 = 1000.0;
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
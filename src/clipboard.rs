@@ -0,0 +1,79 @@
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+use bevy::log::{info, warn};
+use bevy::tasks::IoTaskPool;
+
+/// Clipboard tools tried in order, with their arguments. No clipboard crate
+/// is bundled, so copying relies on whichever the system provides.
+const CLIPBOARD_TOOLS: &[(&str, &[&str])] = &[
+    ("pbcopy", &[]),
+    ("clip", &[]),
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+];
+
+/// Puts `text` on the system clipboard through the first clipboard tool
+/// found, failing with [`io::ErrorKind::NotFound`] when there is none.
+///
+/// Returns once the text is written to the tool; the tool itself is waited
+/// for on the [`IoTaskPool`], since some keep running until another program
+/// takes the clipboard. A tool failing only after that is logged.
+pub fn copy_to_clipboard(text: &str) -> io::Result<()> {
+    for (tool, args) in CLIPBOARD_TOOLS {
+        let mut child = match Command::new(tool)
+            .args(*args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            match stdin.write_all(text.as_bytes()) {
+                Ok(()) => {}
+                // the tool exited before reading, see below
+                Err(err) if err.kind() == io::ErrorKind::BrokenPipe => {}
+                Err(err) => return Err(err),
+            }
+            // dropped here, so the tool sees the end of its input
+        }
+        // e.g. xclip without an X display; another tool may still work
+        if child.try_wait()?.is_some_and(|status| !status.success()) {
+            continue;
+        }
+        IoTaskPool::get()
+            .spawn(async move {
+                match child.wait() {
+                    Ok(status) if !status.success() => warn!("{tool} failed with {status}"),
+                    Ok(_) => {}
+                    Err(err) => warn!("could not wait for {tool}: {err}"),
+                }
+            })
+            .detach();
+        return Ok(());
+    }
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        "no clipboard tool available",
+    ))
+}
+
+/// Copies the world `seed` to the clipboard, returning a status line that
+/// still shows the seed when copying failed.
+pub fn copy_seed(seed: i32) -> String {
+    match copy_to_clipboard(&seed.to_string()) {
+        Ok(()) => {
+            info!("copied seed {seed} to the clipboard");
+            format!("Copied seed {seed}")
+        }
+        Err(err) => {
+            warn!("could not copy seed {seed}: {err}");
+            format!("Seed {seed} (copy failed: {err})")
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use bevy::pbr::{NotShadowCaster, NotShadowReceiver};
+use bevy::prelude::*;
+
+use crate::player::PlayerCam;
+use crate::settings::NoiseSettings;
+use crate::state::{AppState, GameState};
+use crate::wind::Wind;
+use crate::world::WorldParams;
+
+/// Half the edge of the square cloud field around the camera, in blocks.
+const CLOUD_FIELD_RADIUS: f32 = 768.0;
+/// Edge of the field cell that holds one cloud at a density of one.
+const CLOUD_CELL: f32 = 128.0;
+/// Blocks the cloud layer floats above the top of the world.
+const CLOUD_CLEARANCE: f32 = 16.0;
+/// Smallest and largest cloud edge in blocks.
+const CLOUD_SIZE: (f32, f32) = (40.0, 120.0);
+
+/// Highest accepted [`CloudSettings::density`], which keeps the field to a
+/// few hundred clouds.
+pub const MAX_CLOUD_DENSITY: f32 = 4.0;
+
+/// Cloud layer look and motion.
+#[derive(Resource, Clone)]
+pub struct CloudSettings {
+    /// Clouds per [`CLOUD_CELL`] square of sky; zero clears the sky.
+    pub density: f32,
+    /// Drift speed in blocks per second at a wind strength of one.
+    pub speed: f32,
+    /// Horizontal direction the clouds drift in, along x and z.
+    pub direction: Vec2,
+}
+
+impl Default for CloudSettings {
+    fn default() -> Self {
+        Self {
+            density: 0.3,
+            speed: 4.0,
+            direction: Vec2::new(1.0, 0.3),
+        }
+    }
+}
+
+/// Translucent sheet of cloud drifting over the terrain.
+#[derive(Component)]
+pub struct Cloud {
+    /// Drifted world position along x and z, wrapped into the field around
+    /// the camera when drawn.
+    anchor: Vec2,
+}
+
+/// Plugin drawing a layer of drifting clouds high above the terrain while
+/// playing.
+pub struct CloudsPlugin;
+
+impl Plugin for CloudsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CloudSettings>()
+            .add_systems(OnEnter(AppState::Playing), spawn_clouds)
+            .add_systems(
+                Update,
+                (
+                    respawn_clouds,
+                    drift_clouds.run_if(in_state(GameState::Running)),
+                    wrap_clouds,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(OnExit(AppState::Playing), despawn_clouds);
+    }
+}
+
+/// Cheap hash of `i` to `0.0..1.0`, so the same world seed brings back the
+/// same clouds.
+fn scatter(i: u32, salt: u32) -> f32 {
+    let mut h = i.wrapping_mul(0x9e37_79b9) ^ salt.wrapping_mul(0x85eb_ca6b);
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x7feb_352d);
+    h ^= h >> 15;
+    (h & 0x00ff_ffff) as f32 / 0x0100_0000 as f32
+}
+
+fn spawn_clouds(
+    mut commands: Commands,
+    settings: Res<CloudSettings>,
+    noise: Res<NoiseSettings>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let cells = (2.0 * CLOUD_FIELD_RADIUS / CLOUD_CELL).powi(2);
+    let count = (settings.density.clamp(0.0, MAX_CLOUD_DENSITY) * cells).round() as u32;
+    if count == 0 {
+        return;
+    }
+    // one quad and one material shared by every cloud
+    let mesh = meshes.add(Plane3d::default().mesh().size(1.0, 1.0));
+    let material = materials.add(StandardMaterial {
+        base_color: Color::srgba(1.0, 1.0, 1.0, 0.7),
+        alpha_mode: AlphaMode::Blend,
+        unlit: true,
+        // seen from below
+        cull_mode: None,
+        ..default()
+    });
+    // salted with the world seed like the terrain noises
+    let salt = |k: u32| (noise.seed as u32).wrapping_mul(4).wrapping_add(k);
+    for i in 0..count {
+        let anchor = Vec2::new(scatter(i, salt(1)), scatter(i, salt(2))) * 2.0 * CLOUD_FIELD_RADIUS
+            - CLOUD_FIELD_RADIUS;
+        let size = Vec2::new(
+            CLOUD_SIZE.0.lerp(CLOUD_SIZE.1, scatter(i, salt(3))),
+            CLOUD_SIZE.0.lerp(CLOUD_SIZE.1, scatter(i, salt(4))),
+        );
+        commands.spawn((
+            Mesh3d(mesh.clone()),
+            MeshMaterial3d(material.clone()),
+            Transform::from_scale(Vec3::new(size.x, 1.0, size.y)),
+            NotShadowCaster,
+            NotShadowReceiver,
+            Cloud { anchor },
+        ));
+    }
+}
+
+/// Rebuilds the clouds when their settings or the world seed change, e.g.
+/// from the console.
+fn respawn_clouds(
+    mut commands: Commands,
+    settings: Res<CloudSettings>,
+    noise: Res<NoiseSettings>,
+    meshes: ResMut<Assets<Mesh>>,
+    materials: ResMut<Assets<StandardMaterial>>,
+    clouds: Query<Entity, With<Cloud>>,
+    mut seed: Local<Option<i32>>,
+) {
+    let reseeded = seed
+        .replace(noise.seed)
+        .is_some_and(|old| old != noise.seed);
+    if !reseeded && (!settings.is_changed() || settings.is_added()) {
+        return;
+    }
+    for entity in &clouds {
+        commands.entity(entity).despawn();
+    }
+    spawn_clouds(commands, settings, noise, meshes, materials);
+}
+
+/// Moves the clouds along [`CloudSettings::direction`], faster in stronger
+/// wind.
+fn drift_clouds(
+    time: Res<Time>,
+    settings: Res<CloudSettings>,
+    wind: Res<Wind>,
+    mut clouds: Query<&mut Cloud>,
+) {
+    let velocity = settings.direction.normalize_or_zero() * settings.speed * wind.strength;
+    let step = velocity * time.delta_secs();
+    for mut cloud in &mut clouds {
+        cloud.anchor += step;
+    }
+}
+
+/// Places every cloud inside the field around the camera, so clouds that
+/// drift or are left behind come back in on the other side.
+fn wrap_clouds(
+    params: Res<WorldParams>,
+    cams: Query<&Transform, With<PlayerCam>>,
+    mut clouds: Query<(&Cloud, &mut Transform), Without<PlayerCam>>,
+) {
+    let Ok(cam) = cams.single() else {
+        return;
+    };
+    let center = cam.translation.xz();
+    let altitude = params.max_height() as f32 + CLOUD_CLEARANCE;
+    let field = 2.0 * CLOUD_FIELD_RADIUS;
+    for (cloud, mut transform) in &mut clouds {
+        let offset = (cloud.anchor - center + CLOUD_FIELD_RADIUS).rem_euclid(Vec2::splat(field))
+            - CLOUD_FIELD_RADIUS;
+        let position = (center + offset).extend(altitude).xzy();
+        if transform.translation != position {
+            transform.translation = position;
+        }
+    }
+}
+
+fn despawn_clouds(mut commands: Commands, clouds: Query<Entity, With<Cloud>>) {
+    for entity in &clouds {
+        commands.entity(entity).despawn();
+    }
+}
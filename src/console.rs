@@ -0,0 +1,558 @@
+use bevy::input::ButtonState;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::prelude::*;
+
+use crate::clouds::{CloudSettings, MAX_CLOUD_DENSITY};
+use crate::display::Headlamp;
+use crate::drone::{DRONE_DISTANCES, DroneSettings};
+use crate::game::toggle_pause;
+use crate::player::{MoveSpeed, PlayerCam};
+use crate::settings::{NoiseSettings, PhysicsSettings};
+use crate::state::AppState;
+use crate::wind::Wind;
+use crate::world::{BlockCounts, Chunk, ChunkBounds, MAX_OFFSET, RegenerateWorld, WorldParams};
+
+/// Number of output lines kept on screen.
+const LOG_LINES: usize = 8;
+
+/// Runtime state of the developer console.
+#[derive(Resource, Default)]
+pub struct Console {
+    pub open: bool,
+    input: String,
+    log: Vec<String>,
+}
+
+impl Console {
+    fn print(&mut self, line: String) {
+        self.log.push(line);
+        if self.log.len() > LOG_LINES {
+            self.log.remove(0);
+        }
+    }
+}
+
+/// Run condition for gameplay input that must be ignored while typing.
+pub fn console_closed(console: Res<Console>) -> bool {
+    !console.open
+}
+
+#[derive(Component)]
+pub struct ConsoleRoot;
+
+#[derive(Component)]
+pub struct ConsoleText;
+
+/// Commands understood by the console.
+#[derive(Debug, PartialEq)]
+enum Command {
+    Teleport(Vec3),
+    Seed(i32),
+    Offset(IVec2),
+    View(i32),
+    /// Full-detail radius in chunks, see `WorldParams::lod1_radius`.
+    Lod(i32),
+    Regen,
+    Speed(f32),
+    Accel(f32),
+    Gravity(f32),
+    /// Horizontal and vertical flying speed multipliers.
+    FlySpeed(f32, f32),
+    Wind(f32),
+    Near(f32),
+    /// Camera floor height, `None` to fly anywhere.
+    Floor(Option<f32>),
+    /// Chunks from the origin the player is kept within, `None` for an
+    /// unbounded world.
+    Bounds(Option<i32>),
+    /// Whether chunks merge faces into larger quads, see
+    /// `NoiseSettings::greedy_meshing`.
+    Mesher(bool),
+    /// Whether chunk meshes share coincident vertices, see
+    /// `NoiseSettings::weld_vertices`.
+    Weld(bool),
+    /// Whether the sun shines along the camera, see [`Headlamp`].
+    Headlamp(bool),
+    Drone {
+        distance: f32,
+        height: f32,
+    },
+    Clouds {
+        density: f32,
+        speed: f32,
+    },
+    Blocks,
+}
+
+/// Plugin providing the in-game console, toggled with `/` or backtick.
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Console>()
+            .add_systems(OnEnter(AppState::Playing), console_setup)
+            .add_systems(
+                Update,
+                (console_input.after(toggle_pause), update_console)
+                    .chain()
+                    .run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(OnExit(AppState::Playing), console_cleanup);
+    }
+}
+
+fn console_setup(mut commands: Commands, mut console: ResMut<Console>) {
+    console.open = false;
+    console.input.clear();
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(5.0),
+                left: Val::Px(5.0),
+                padding: UiRect::all(Val::Px(5.0)),
+                display: Display::None,
+                ..Default::default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            ConsoleRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 16.0,
+                    ..Default::default()
+                },
+                ConsoleText,
+            ));
+        });
+}
+
+/// Collects typed text and runs the entered command on Enter.
+#[allow(clippy::too_many_arguments)]
+fn console_input(
+    mut keys: EventReader<KeyboardInput>,
+    mut console: ResMut<Console>,
+    mut settings: ResMut<NoiseSettings>,
+    mut params: ResMut<WorldParams>,
+    mut speed: ResMut<MoveSpeed>,
+    mut wind: ResMut<Wind>,
+    mut clouds: ResMut<CloudSettings>,
+    mut drone: ResMut<DroneSettings>,
+    mut physics: ResMut<PhysicsSettings>,
+    mut headlamp: ResMut<Headlamp>,
+    mut player: Query<(&mut Transform, &mut PlayerCam)>,
+    mut regen: EventWriter<RegenerateWorld>,
+    chunks: Query<&BlockCounts, With<Chunk>>,
+) {
+    for ev in keys.read() {
+        if ev.state != ButtonState::Pressed {
+            continue;
+        }
+        let toggle = matches!(ev.key_code, KeyCode::Slash | KeyCode::Backquote);
+        if !console.open {
+            if toggle {
+                console.open = true;
+                console.input.clear();
+                // the toggle key itself must not end up in the input
+                break;
+            }
+            continue;
+        }
+
+        match &ev.logical_key {
+            _ if toggle || ev.key_code == KeyCode::Escape => {
+                console.open = false;
+                break;
+            }
+            Key::Enter => {
+                let line = std::mem::take(&mut console.input);
+                if line.trim().is_empty() {
+                    continue;
+                }
+                console.print(format!("> {line}"));
+                match parse_command(&line) {
+                    Ok(Command::Teleport(pos)) => {
+                        for (mut transform, mut cam) in &mut player {
+                            transform.translation = pos;
+                            cam.velocity = Vec3::ZERO;
+                        }
+                    }
+                    Ok(Command::Seed(seed)) => {
+                        settings.seed = seed;
+                        regen.write(RegenerateWorld);
+                    }
+                    Ok(Command::Offset(offset)) => {
+                        settings.offset = offset.to_array();
+                        regen.write(RegenerateWorld);
+                    }
+                    Ok(Command::View(view)) => params.view_width = view,
+                    Ok(Command::Lod(radius)) => params.lod1_radius = radius,
+                    Ok(Command::Regen) => {
+                        regen.write(RegenerateWorld);
+                    }
+                    Ok(Command::Speed(value)) => speed.max = value,
+                    Ok(Command::Accel(value)) => speed.acceleration = value,
+                    Ok(Command::Gravity(value)) => physics.gravity = value,
+                    Ok(Command::FlySpeed(horizontal, vertical)) => {
+                        physics.horizontal_speed = horizontal;
+                        physics.vertical_speed = vertical;
+                    }
+                    Ok(Command::Wind(strength)) => wind.strength = strength,
+                    Ok(Command::Near(near)) => params.near_plane = near,
+                    Ok(Command::Floor(floor)) => params.camera_floor = floor,
+                    Ok(Command::Bounds(radius)) => {
+                        params.bounds = radius.map(ChunkBounds::around_origin);
+                    }
+                    Ok(Command::Mesher(greedy)) => {
+                        settings.greedy_meshing = greedy;
+                        regen.write(RegenerateWorld);
+                    }
+                    Ok(Command::Weld(weld)) => {
+                        settings.weld_vertices = weld;
+                        regen.write(RegenerateWorld);
+                    }
+                    Ok(Command::Headlamp(on)) => headlamp.0 = on,
+                    Ok(Command::Drone { distance, height }) => {
+                        drone.distance = distance;
+                        drone.height = height;
+                    }
+                    Ok(Command::Clouds { density, speed }) => {
+                        clouds.density = density;
+                        clouds.speed = speed;
+                    }
+                    Ok(Command::Blocks) => {
+                        let mut total = BlockCounts::default();
+                        for counts in &chunks {
+                            total.merge(counts);
+                        }
+                        info!("block composition of {} chunks:", chunks.iter().len());
+                        for line in total.histogram() {
+                            info!("  {line}");
+                            console.print(line);
+                        }
+                    }
+                    Err(err) => console.print(format!("error: {err}")),
+                }
+            }
+            Key::Backspace => {
+                console.input.pop();
+            }
+            Key::Space => console.input.push(' '),
+            Key::Character(text) => {
+                console
+                    .input
+                    .extend(text.chars().filter(|c| !c.is_control()));
+            }
+            _ => {}
+        }
+    }
+}
+
+fn update_console(
+    console: Res<Console>,
+    mut roots: Query<&mut Node, With<ConsoleRoot>>,
+    mut texts: Query<&mut Text, With<ConsoleText>>,
+) {
+    if !console.is_changed() {
+        return;
+    }
+    for mut node in &mut roots {
+        node.display = if console.open {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+    for mut text in &mut texts {
+        let mut lines = console.log.clone();
+        lines.push(format!("> {}_", console.input));
+        text.0 = lines.join("\n");
+    }
+}
+
+fn parse_command(line: &str) -> Result<Command, String> {
+    let mut words = line.split_whitespace();
+    let name = words.next().unwrap_or_default();
+    let args: Vec<&str> = words.collect();
+
+    fn arg<T: std::str::FromStr>(args: &[&str], i: usize) -> Result<T, String> {
+        let word = args.get(i).ok_or("missing argument")?;
+        word.parse().map_err(|_| format!("invalid number `{word}`"))
+    }
+    let expect = |count: usize| {
+        if args.len() == count {
+            Ok(())
+        } else {
+            Err(format!("`{name}` takes {count} argument(s)"))
+        }
+    };
+
+    match name {
+        "tp" => {
+            expect(3)?;
+            let pos = Vec3::new(arg(&args, 0)?, arg(&args, 1)?, arg(&args, 2)?);
+            if !pos.is_finite() {
+                return Err("teleport coordinates must be finite".into());
+            }
+            Ok(Command::Teleport(pos))
+        }
+        "seed" => {
+            expect(1)?;
+            Ok(Command::Seed(arg(&args, 0)?))
+        }
+        "offset" => {
+            expect(2)?;
+            let offset = IVec2::new(arg(&args, 0)?, arg(&args, 1)?);
+            let range = -MAX_OFFSET..=MAX_OFFSET;
+            if !range.contains(&offset.x) || !range.contains(&offset.y) {
+                return Err(format!(
+                    "offset must be between -{MAX_OFFSET} and {MAX_OFFSET}"
+                ));
+            }
+            Ok(Command::Offset(offset))
+        }
+        "view" => {
+            expect(1)?;
+            let view: i32 = arg(&args, 0)?;
+            if view < 1 {
+                return Err("view must be at least 1".into());
+            }
+            Ok(Command::View(view))
+        }
+        "lod" => {
+            expect(1)?;
+            let radius: i32 = arg(&args, 0)?;
+            if radius < 0 {
+                return Err("lod radius must not be negative".into());
+            }
+            Ok(Command::Lod(radius))
+        }
+        "regen" => {
+            expect(0)?;
+            Ok(Command::Regen)
+        }
+        "speed" => {
+            expect(1)?;
+            let speed: f32 = arg(&args, 0)?;
+            if !(speed.is_finite() && speed > 0.0) {
+                return Err("speed must be positive".into());
+            }
+            Ok(Command::Speed(speed))
+        }
+        "accel" => {
+            expect(1)?;
+            let accel: f32 = arg(&args, 0)?;
+            if !(accel.is_finite() && accel > 0.0) {
+                return Err("acceleration must be positive".into());
+            }
+            Ok(Command::Accel(accel))
+        }
+        "gravity" => {
+            expect(1)?;
+            let gravity: f32 = arg(&args, 0)?;
+            if !(gravity.is_finite() && gravity >= 0.0) {
+                return Err("gravity must not be negative".into());
+            }
+            Ok(Command::Gravity(gravity))
+        }
+        "flyspeed" => {
+            expect(2)?;
+            let (horizontal, vertical): (f32, f32) = (arg(&args, 0)?, arg(&args, 1)?);
+            if !(horizontal.is_finite()
+                && horizontal > 0.0
+                && vertical.is_finite()
+                && vertical > 0.0)
+            {
+                return Err("flying speed multipliers must be positive".into());
+            }
+            Ok(Command::FlySpeed(horizontal, vertical))
+        }
+        "wind" => {
+            expect(1)?;
+            let strength: f32 = arg(&args, 0)?;
+            if !(strength.is_finite() && strength >= 0.0) {
+                return Err("wind must not be negative".into());
+            }
+            Ok(Command::Wind(strength))
+        }
+        "blocks" => {
+            expect(0)?;
+            Ok(Command::Blocks)
+        }
+        "near" => {
+            expect(1)?;
+            let near: f32 = arg(&args, 0)?;
+            if !(near.is_finite() && near > 0.0) {
+                return Err("near plane must be positive".into());
+            }
+            Ok(Command::Near(near))
+        }
+        "floor" => {
+            expect(1)?;
+            if args[0] == "off" {
+                return Ok(Command::Floor(None));
+            }
+            let floor: f32 = arg(&args, 0)?;
+            if !floor.is_finite() {
+                return Err("floor must be a height or off".into());
+            }
+            Ok(Command::Floor(Some(floor)))
+        }
+        "bounds" => {
+            expect(1)?;
+            if args[0] == "off" {
+                return Ok(Command::Bounds(None));
+            }
+            let radius: i32 = arg(&args, 0)?;
+            if radius < 0 {
+                return Err("bounds must not be negative".into());
+            }
+            Ok(Command::Bounds(Some(radius)))
+        }
+        "mesher" => {
+            expect(1)?;
+            match args[0] {
+                "greedy" => Ok(Command::Mesher(true)),
+                "naive" => Ok(Command::Mesher(false)),
+                other => Err(format!("unknown mesher `{other}`, use greedy or naive")),
+            }
+        }
+        "weld" => {
+            expect(1)?;
+            match args[0] {
+                "on" => Ok(Command::Weld(true)),
+                "off" => Ok(Command::Weld(false)),
+                other => Err(format!("unknown weld mode `{other}`, use on or off")),
+            }
+        }
+        "headlamp" => {
+            expect(1)?;
+            match args[0] {
+                "on" => Ok(Command::Headlamp(true)),
+                "off" => Ok(Command::Headlamp(false)),
+                other => Err(format!("unknown headlamp mode `{other}`, use on or off")),
+            }
+        }
+        "drone" => {
+            expect(2)?;
+            let (distance, height): (f32, f32) = (arg(&args, 0)?, arg(&args, 1)?);
+            if !(DRONE_DISTANCES.contains(&distance) && height.is_finite()) {
+                return Err(format!(
+                    "drone distance must be {} to {}",
+                    DRONE_DISTANCES.start(),
+                    DRONE_DISTANCES.end()
+                ));
+            }
+            Ok(Command::Drone { distance, height })
+        }
+        "clouds" => {
+            expect(2)?;
+            let (density, speed): (f32, f32) = (arg(&args, 0)?, arg(&args, 1)?);
+            if !((0.0..=MAX_CLOUD_DENSITY).contains(&density) && speed.is_finite() && speed >= 0.0)
+            {
+                return Err(format!(
+                    "cloud density must be 0 to {MAX_CLOUD_DENSITY} and speed not negative"
+                ));
+            }
+            Ok(Command::Clouds { density, speed })
+        }
+        _ => Err(format!("unknown command `{name}`")),
+    }
+}
+
+fn console_cleanup(
+    mut commands: Commands,
+    mut console: ResMut<Console>,
+    roots: Query<Entity, With<ConsoleRoot>>,
+) {
+    console.open = false;
+    for e in &roots {
+        commands.entity(e).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_known_commands() {
+        assert_eq!(
+            parse_command("tp 1 -2.5 3"),
+            Ok(Command::Teleport(Vec3::new(1.0, -2.5, 3.0)))
+        );
+        assert_eq!(parse_command("seed -42"), Ok(Command::Seed(-42)));
+        assert_eq!(
+            parse_command("offset 5000 -12"),
+            Ok(Command::Offset(IVec2::new(5000, -12)))
+        );
+        assert_eq!(parse_command("  view   12 "), Ok(Command::View(12)));
+        assert_eq!(parse_command("lod 6"), Ok(Command::Lod(6)));
+        assert_eq!(parse_command("regen"), Ok(Command::Regen));
+        assert_eq!(parse_command("speed 60"), Ok(Command::Speed(60.0)));
+        assert_eq!(parse_command("accel 80"), Ok(Command::Accel(80.0)));
+        assert_eq!(parse_command("gravity 4.5"), Ok(Command::Gravity(4.5)));
+        assert_eq!(
+            parse_command("flyspeed 1 0.25"),
+            Ok(Command::FlySpeed(1.0, 0.25))
+        );
+        assert_eq!(parse_command("wind 0"), Ok(Command::Wind(0.0)));
+        assert_eq!(parse_command("near 0.5"), Ok(Command::Near(0.5)));
+        assert_eq!(parse_command("floor -20"), Ok(Command::Floor(Some(-20.0))));
+        assert_eq!(parse_command("floor off"), Ok(Command::Floor(None)));
+        assert_eq!(parse_command("bounds 8"), Ok(Command::Bounds(Some(8))));
+        assert_eq!(parse_command("bounds off"), Ok(Command::Bounds(None)));
+        assert_eq!(parse_command("mesher naive"), Ok(Command::Mesher(false)));
+        assert_eq!(parse_command("mesher greedy"), Ok(Command::Mesher(true)));
+        assert_eq!(parse_command("weld on"), Ok(Command::Weld(true)));
+        assert_eq!(parse_command("weld off"), Ok(Command::Weld(false)));
+        assert_eq!(parse_command("headlamp on"), Ok(Command::Headlamp(true)));
+        assert_eq!(
+            parse_command("drone 20 -2"),
+            Ok(Command::Drone {
+                distance: 20.0,
+                height: -2.0
+            })
+        );
+        assert_eq!(
+            parse_command("clouds 0.5 10"),
+            Ok(Command::Clouds {
+                density: 0.5,
+                speed: 10.0
+            })
+        );
+        assert_eq!(parse_command("blocks"), Ok(Command::Blocks));
+    }
+
+    #[test]
+    fn rejects_bad_input() {
+        assert!(parse_command("fly").is_err());
+        assert!(parse_command("tp 1 2").is_err());
+        assert!(parse_command("seed abc").is_err());
+        assert!(parse_command("offset 1.5 2").is_err());
+        assert!(parse_command("offset 16777217 0").is_err());
+        assert!(parse_command("offset 0 -2147483648").is_err());
+        assert!(parse_command("view 0").is_err());
+        assert!(parse_command("lod -1").is_err());
+        assert!(parse_command("speed -1").is_err());
+        assert!(parse_command("accel 0").is_err());
+        assert!(parse_command("gravity -9.8").is_err());
+        assert!(parse_command("flyspeed 1 0").is_err());
+        assert!(parse_command("wind -0.5").is_err());
+        assert!(parse_command("near 0").is_err());
+        assert!(parse_command("floor low").is_err());
+        assert!(parse_command("bounds -2").is_err());
+        assert!(parse_command("mesher fast").is_err());
+        assert!(parse_command("weld yes").is_err());
+        assert!(parse_command("headlamp").is_err());
+        assert!(parse_command("drone 0 4").is_err());
+        assert!(parse_command("clouds 1").is_err());
+        assert!(parse_command("clouds -1 4").is_err());
+        assert!(parse_command("clouds 1e7 4").is_err());
+        assert!(parse_command("tp nan 0 0").is_err());
+        assert!(parse_command("tp 0 inf 0").is_err());
+        assert!(parse_command("regen now").is_err());
+    }
+}
@@ -0,0 +1,254 @@
+use std::time::{Duration, Instant};
+
+use bevy::pbr::CascadeShadowConfigBuilder;
+use bevy::prelude::*;
+use bevy::window::{PresentMode, PrimaryWindow};
+
+use crate::player::PlayerCam;
+use crate::settings::{Action, DisplaySettings, KeyBindings};
+use crate::sky::SkyColors;
+use crate::state::AppState;
+use crate::world::{ChunkBrightness, WorldParams};
+
+/// Time the previous frame finished, used by the frame rate cap.
+#[derive(Resource)]
+struct FrameLimiter {
+    last: Instant,
+}
+
+/// Debug lighting mode pointing the sun along the player camera every frame,
+/// like a headlamp, so caves and shaded slopes are lit while looked at. Off
+/// by default and not saved; [`Action::ToggleHeadlamp`] and the `headlamp`
+/// console command switch it. Sun shadows are off while it is on, since they
+/// would fall straight away from the camera.
+#[derive(Resource, Default)]
+pub struct Headlamp(pub bool);
+
+/// Plugin applying [`DisplaySettings`] to the primary window and capping
+/// the frame rate.
+pub struct DisplayPlugin;
+
+impl Plugin for DisplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DisplaySettings>()
+            .init_resource::<Headlamp>()
+            .insert_resource(FrameLimiter {
+                last: Instant::now(),
+            })
+            .add_systems(
+                Update,
+                (
+                    apply_display_settings,
+                    apply_msaa,
+                    apply_shadows,
+                    apply_sun,
+                    apply_brightness,
+                    apply_sky_color,
+                    apply_clip_planes,
+                ),
+            )
+            .add_systems(
+                Update,
+                toggle_headlamp
+                    .before(apply_shadows)
+                    .run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(Startup, apply_saved_quality)
+            .add_systems(Last, limit_frame_rate);
+    }
+}
+
+/// Reapplies the saved quality preset, whose view distances are not
+/// persisted on their own.
+fn apply_saved_quality(mut settings: ResMut<DisplaySettings>, mut params: ResMut<WorldParams>) {
+    if let Some(preset) = settings.quality {
+        preset.apply(&mut settings);
+        params.apply_quality(preset);
+    }
+}
+
+/// Switches the present mode whenever the settings change, including the
+/// first frame so saved settings apply at startup.
+fn apply_display_settings(
+    settings: Res<DisplaySettings>,
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    if let Ok(mut window) = windows.single_mut() {
+        window.present_mode = if settings.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        };
+    }
+}
+
+/// Applies the MSAA sample count to every camera, including ones spawned
+/// after the setting last changed.
+fn apply_msaa(
+    mut commands: Commands,
+    settings: Res<DisplaySettings>,
+    cameras: Query<(Entity, Ref<Camera>)>,
+) {
+    let msaa = match settings.msaa {
+        1 => Msaa::Off,
+        2 => Msaa::Sample2,
+        _ => Msaa::Sample4,
+    };
+    for (entity, camera) in &cameras {
+        if settings.is_changed() || camera.is_added() {
+            commands.entity(entity).insert(msaa);
+        }
+    }
+}
+
+/// Shortest shadow range, used when the view distance is tiny.
+const MIN_SHADOW_DISTANCE: f32 = 32.0;
+
+fn toggle_headlamp(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut headlamp: ResMut<Headlamp>,
+) {
+    if keys.just_pressed(bindings.key(Action::ToggleHeadlamp)) {
+        headlamp.0 = !headlamp.0;
+    }
+}
+
+/// Toggles sun shadows, which the [`Headlamp`] keeps off, and stretches the
+/// shadow cascades over the view distance so far terrain keeps its shadows.
+fn apply_shadows(
+    mut commands: Commands,
+    settings: Res<DisplaySettings>,
+    params: Res<WorldParams>,
+    headlamp: Res<Headlamp>,
+    mut lights: Query<(Entity, &mut DirectionalLight)>,
+) {
+    let changed = settings.is_changed() || params.is_changed() || headlamp.is_changed();
+    let shadows = settings.shadows && !headlamp.0;
+    let distance = params.view_distance().max(MIN_SHADOW_DISTANCE);
+    for (entity, mut light) in &mut lights {
+        if !changed && !light.is_added() {
+            continue;
+        }
+        let cascades = CascadeShadowConfigBuilder {
+            num_cascades: 4,
+            // most of the resolution goes to the terrain right around the player
+            first_cascade_far_bound: distance / 16.0,
+            maximum_distance: distance,
+            ..default()
+        }
+        .build();
+        commands.entity(entity).insert(cascades);
+        if light.shadows_enabled != shadows {
+            light.shadows_enabled = shadows;
+        }
+    }
+}
+
+/// Pose of the sun light for the sun angles in `settings`, shining from
+/// [`DisplaySettings::sun_direction`] onto the origin.
+pub fn sun_transform(settings: &DisplaySettings) -> Transform {
+    Transform::from_translation(settings.sun_direction()).looking_at(Vec3::ZERO, Vec3::Y)
+}
+
+/// Turns the sun and sets its illuminance when the settings change, or
+/// keeps it pointing along the player camera while the [`Headlamp`] is on.
+fn apply_sun(
+    settings: Res<DisplaySettings>,
+    headlamp: Res<Headlamp>,
+    cams: Query<&Transform, With<PlayerCam>>,
+    mut lights: Query<(&mut DirectionalLight, &mut Transform), Without<PlayerCam>>,
+) {
+    let pose = match cams.single() {
+        Ok(cam) if headlamp.0 => Transform::from_rotation(cam.rotation),
+        _ if settings.is_changed() || headlamp.is_changed() => sun_transform(&settings),
+        _ => return,
+    };
+    for (mut light, mut transform) in &mut lights {
+        if light.illuminance != settings.sun_illuminance {
+            light.illuminance = settings.sun_illuminance;
+        }
+        if transform.rotation != pose.rotation {
+            *transform = pose;
+        }
+    }
+}
+
+/// Perspective projection of the player camera for the current view distance.
+pub fn camera_projection(params: &WorldParams) -> Projection {
+    let (near, far) = params.clip_planes();
+    Projection::Perspective(PerspectiveProjection {
+        near,
+        far,
+        ..default()
+    })
+}
+
+/// Moves the camera clip planes along when the view distance or world
+/// height changes.
+fn apply_clip_planes(params: Res<WorldParams>, mut cams: Query<&mut Projection, With<PlayerCam>>) {
+    if !params.is_changed() {
+        return;
+    }
+    let (near, far) = params.clip_planes();
+    for mut projection in &mut cams {
+        if let Projection::Perspective(perspective) = projection.as_mut()
+            && (perspective.near, perspective.far) != (near, far)
+        {
+            perspective.near = near;
+            perspective.far = far;
+        }
+    }
+}
+
+/// Ambient light level at a brightness of one, Bevy's default.
+const BASE_AMBIENT: f32 = 80.0;
+
+/// Scales the terrain color and the ambient light by the brightness setting.
+fn apply_brightness(
+    settings: Res<DisplaySettings>,
+    mut chunks: ResMut<ChunkBrightness>,
+    mut ambient: ResMut<AmbientLight>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    let brightness = settings.brightness.max(0.0);
+    if chunks.0 != brightness {
+        chunks.0 = brightness;
+    }
+    ambient.brightness = BASE_AMBIENT * brightness;
+}
+
+/// Clears the window to the sky color setting and moves the horizon of the
+/// sky dome along; the dome picks it up when the next game starts.
+fn apply_sky_color(
+    settings: Res<DisplaySettings>,
+    mut clear: ResMut<ClearColor>,
+    mut sky: ResMut<SkyColors>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    let [r, g, b] = settings.sky_color;
+    let color = Color::srgb(r, g, b);
+    if clear.0 != color {
+        clear.0 = color;
+        sky.horizon = color;
+    }
+}
+
+/// Sleeps off the rest of the frame budget when a frame rate cap is set.
+fn limit_frame_rate(settings: Res<DisplaySettings>, mut limiter: ResMut<FrameLimiter>) {
+    if let Some(cap) = settings.fps_cap.filter(|cap| *cap > 0) {
+        let budget = Duration::from_secs_f64(1.0 / cap as f64);
+        let elapsed = limiter.last.elapsed();
+        if elapsed < budget {
+            std::thread::sleep(budget - elapsed);
+        }
+    }
+    limiter.last = Instant::now();
+}
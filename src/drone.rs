@@ -0,0 +1,208 @@
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+
+use crate::player::{EYE_HEIGHT, PlayerCam};
+use crate::settings::{Action, KeyBindings};
+use crate::state::{AppState, GameState};
+
+/// Range of [`DroneSettings::distance`] the mouse wheel zooms within.
+pub const DRONE_DISTANCES: std::ops::RangeInclusive<f32> = 2.0..=96.0;
+/// Fraction of the distance one wheel notch zooms by.
+const ZOOM_STEP: f32 = 0.1;
+/// Angular speed in radians per second of orbiting around the player.
+const DRONE_ORBIT_SPEED: f32 = 1.5;
+const PLAYER_MARKER_COLOR: Color = Color::srgb(1.0, 0.35, 0.2);
+
+/// Where the observer drone flies relative to the player.
+#[derive(Resource, Clone)]
+pub struct DroneSettings {
+    /// Horizontal distance in blocks from the player.
+    pub distance: f32,
+    /// Height in blocks above the player camera.
+    pub height: f32,
+}
+
+impl Default for DroneSettings {
+    fn default() -> Self {
+        Self {
+            distance: 12.0,
+            height: 4.0,
+        }
+    }
+}
+
+/// Third-person camera following the player for recordings.
+///
+/// The player camera keeps moving, loading chunks and picking blocks as
+/// usual; only rendering switches over to the drone while it exists.
+#[derive(Component, Default)]
+pub struct DroneCam {
+    /// Angle in radians around the player, added to its yaw; zero looks
+    /// over the player's shoulder from behind.
+    pub orbit: f32,
+}
+
+/// Plugin toggling the observer drone (`V` by default) while playing.
+pub struct DronePlugin;
+
+impl Plugin for DronePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DroneSettings>()
+            .add_systems(
+                Update,
+                (
+                    (toggle_drone, steer_drone).run_if(in_state(GameState::Running)),
+                    follow_player,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(OnExit(AppState::Playing), despawn_drone);
+    }
+}
+
+/// Spawns the drone and hands rendering over to it, or back to the player
+/// camera.
+fn toggle_drone(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    drones: Query<Entity, With<DroneCam>>,
+    mut players: Query<(&mut Camera, &Projection), With<PlayerCam>>,
+) {
+    if !keys.just_pressed(bindings.key(Action::ToggleDrone)) {
+        return;
+    }
+    let Ok((mut camera, projection)) = players.single_mut() else {
+        return;
+    };
+    if drones.is_empty() {
+        camera.is_active = false;
+        commands.spawn((
+            Camera3d::default(),
+            // above the player camera so the HUD draws onto the drone view
+            Camera {
+                order: 1,
+                ..default()
+            },
+            projection.clone(),
+            Transform::default(),
+            DroneCam::default(),
+        ));
+    } else {
+        camera.is_active = true;
+        for e in &drones {
+            commands.entity(e).despawn();
+        }
+    }
+}
+
+/// Orbits the drone with the orbit keys and zooms it with the mouse wheel.
+fn steer_drone(
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut wheel: EventReader<MouseWheel>,
+    mut settings: ResMut<DroneSettings>,
+    mut drones: Query<&mut DroneCam>,
+) {
+    let notches: f32 = wheel
+        .read()
+        .map(|ev| match ev.unit {
+            MouseScrollUnit::Line => ev.y,
+            MouseScrollUnit::Pixel => ev.y / 100.0,
+        })
+        .sum();
+    let Ok(mut drone) = drones.single_mut() else {
+        return;
+    };
+    let mut turn = 0.0;
+    if keys.pressed(bindings.key(Action::DroneOrbitLeft)) {
+        turn -= 1.0;
+    }
+    if keys.pressed(bindings.key(Action::DroneOrbitRight)) {
+        turn += 1.0;
+    }
+    if turn != 0.0 {
+        let step = turn * DRONE_ORBIT_SPEED * time.delta_secs();
+        drone.orbit = (drone.orbit + step).rem_euclid(std::f32::consts::TAU);
+    }
+    if notches != 0.0 {
+        // scrolling up brings the drone closer
+        let distance = settings.distance * (1.0 - notches * ZOOM_STEP);
+        settings.distance = distance.clamp(*DRONE_DISTANCES.start(), *DRONE_DISTANCES.end());
+    }
+}
+
+/// Places the drone around the player, keeps its clip planes in step with
+/// the player camera and marks where the player is.
+fn follow_player(
+    settings: Res<DroneSettings>,
+    players: Query<(&Transform, &Projection, &PlayerCam)>,
+    mut drones: Query<(&mut Transform, &mut Projection, &DroneCam), Without<PlayerCam>>,
+    mut gizmos: Gizmos,
+) {
+    let Ok((player, player_projection, cam)) = players.single() else {
+        return;
+    };
+    for (mut transform, mut projection, drone) in &mut drones {
+        *transform = drone_pose(player.translation, cam.yaw + drone.orbit, &settings);
+        if let (Projection::Perspective(ours), Projection::Perspective(theirs)) =
+            (projection.as_ref(), player_projection)
+            && (ours.near, ours.far) != (theirs.near, theirs.far)
+        {
+            *projection = player_projection.clone();
+        }
+        // the player has no body, so outline one and point where it looks
+        let feet = player.translation - Vec3::Y * EYE_HEIGHT;
+        gizmos.cuboid(
+            Transform::from_translation(feet + Vec3::Y * EYE_HEIGHT / 2.0)
+                .with_scale(Vec3::new(0.6, EYE_HEIGHT, 0.6)),
+            PLAYER_MARKER_COLOR,
+        );
+        gizmos.arrow(
+            player.translation,
+            player.translation + player.forward() * 1.5,
+            PLAYER_MARKER_COLOR,
+        );
+    }
+}
+
+/// Drone transform `settings.distance` behind the player at `target` for a
+/// heading of `yaw`, raised by `settings.height` and looking at the player.
+fn drone_pose(target: Vec3, yaw: f32, settings: &DroneSettings) -> Transform {
+    // the camera faces -Z at a yaw of zero, so behind is +Z
+    let offset = Quat::from_rotation_y(yaw) * Vec3::new(0.0, settings.height, settings.distance);
+    Transform::from_translation(target + offset).looking_at(target, Vec3::Y)
+}
+
+fn despawn_drone(mut commands: Commands, drones: Query<Entity, With<DroneCam>>) {
+    for e in &drones {
+        commands.entity(e).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::look_rotation;
+    use std::f32::consts::FRAC_PI_2;
+
+    #[test]
+    fn drone_hangs_behind_and_above_the_player() {
+        let settings = DroneSettings::default();
+        let target = Vec3::new(10.0, 40.0, -5.0);
+        for yaw in [0.0, FRAC_PI_2, 2.5] {
+            let pose = drone_pose(target, yaw, &settings);
+            let facing = look_rotation(yaw, 0.0) * Vec3::NEG_Z;
+            let offset = pose.translation - target;
+            // behind the way the player faces, at the set distance and height
+            assert!(offset.xz().dot(facing.xz()) < 0.0);
+            assert!((offset.xz().length() - settings.distance).abs() < 1e-4);
+            assert!((offset.y - settings.height).abs() < 1e-4);
+            // and looking back at the player
+            let towards = (target - pose.translation).normalize();
+            assert!(pose.forward().dot(towards) > 0.9999, "yaw {yaw}");
+        }
+    }
+}
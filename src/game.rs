@@ -1,48 +1,139 @@
 use bevy::prelude::*;
+use bevy::window::{CursorGrabMode, PrimaryWindow};
 
-use crate::player::PlayerCam;
-use crate::state::AppState;
+use crate::display::{camera_projection, sun_transform};
+use crate::pause::ConfirmReturn;
+use crate::player::{PlayerCam, SpawnPoint, look_rotation};
+use crate::saves::ActiveWorld;
+use crate::settings::{Action, DisplaySettings, KeyBindings, NoiseSettings, SavedNoise};
+use crate::sky::{SkyColors, SkyDome, spawn_sky};
+use crate::state::{AppState, GameState};
+use crate::world::{TerrainNoise, WorldParams};
 
 /// Sets up the camera and lighting for the gameplay scene.
 ///
-/// World and chunk generation are handled by the `WorldPlugin`.
-pub fn setup_game(mut commands: Commands) {
+/// The camera starts standing on the terrain at the spawn point inside the
+/// sky dome, or where the player left a resumed world. World and chunk
+/// generation are handled by the `WorldPlugin`.
+#[allow(clippy::too_many_arguments)]
+pub fn setup_game(
+    mut commands: Commands,
+    spawn: Res<SpawnPoint>,
+    noise: Res<TerrainNoise>,
+    params: Res<WorldParams>,
+    display: Res<DisplaySettings>,
+    sky: Res<SkyColors>,
+    mut active: ResMut<ActiveWorld>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let (start, cam) = match active.resume.take() {
+        Some(pose) => (
+            Vec3::from_array(pose.position),
+            PlayerCam {
+                yaw: pose.yaw,
+                pitch: pose.pitch,
+                ..default()
+            },
+        ),
+        None => (spawn.position(&noise.0), PlayerCam::default()),
+    };
+    let rotation = look_rotation(cam.yaw, cam.pitch);
+
     // camera
     commands.spawn((
         Camera3d::default(),
-        Transform::from_xyz(0.0, 2.0, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
-        PlayerCam {
-            yaw: 0.0,
-            pitch: 0.0,
-        },
+        camera_projection(&params),
+        Transform::from_translation(start).with_rotation(rotation),
+        cam,
         Visibility::default(),
     ));
 
     // light
     commands.spawn((
-        DirectionalLight::default(),
-        Transform::from_xyz(4.0, 8.0, 4.0).looking_at(Vec3::ZERO, Vec3::Y),
+        DirectionalLight {
+            illuminance: display.sun_illuminance,
+            ..default()
+        },
+        sun_transform(&display),
     ));
+
+    spawn_sky(&mut commands, &mut meshes, &mut materials, &sky, start);
 }
 
+/// Leaves gameplay when `P` (by default) is pressed, or pauses to ask first
+/// when the noise settings have unsaved changes, see [`ConfirmReturn`].
+///
+/// All teardown (chunks, pending tasks, caches, camera and cursor) happens in
+/// the `OnExit(AppState::Playing)` systems so every exit path is equally clean.
 pub fn return_to_menu(
     keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    settings: Res<NoiseSettings>,
+    saved: Res<SavedNoise>,
+    mut confirm: ResMut<ConfirmReturn>,
+    mut next_game_state: ResMut<NextState<GameState>>,
     mut next_state: ResMut<NextState<AppState>>,
 ) {
-    if keys.just_pressed(KeyCode::KeyP) {
+    if !keys.just_pressed(bindings.key(Action::ReturnToMenu)) {
+        return;
+    }
+    if saved.differs(&settings) {
+        confirm.0 = true;
+        next_game_state.set(GameState::Paused);
+    } else {
         next_state.set(AppState::Menu);
     }
 }
 
+/// Toggles between running and paused gameplay when Escape (by default) is
+/// pressed.
+pub fn toggle_pause(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keys.just_pressed(bindings.key(Action::Pause)) {
+        next_state.set(match state.get() {
+            GameState::Running => GameState::Paused,
+            GameState::Paused => GameState::Running,
+        });
+    }
+}
+
+/// Locks and hides the cursor so mouse motion drives the camera.
+pub fn grab_cursor(mut windows: Query<&mut Window, With<PrimaryWindow>>) {
+    if let Ok(mut window) = windows.single_mut() {
+        window.cursor_options.grab_mode = CursorGrabMode::Locked;
+        window.cursor_options.visible = false;
+    }
+}
+
+/// Releases the cursor so UI can be used with the mouse.
+pub fn release_cursor(mut windows: Query<&mut Window, With<PrimaryWindow>>) {
+    if let Ok(mut window) = windows.single_mut() {
+        window.cursor_options.grab_mode = CursorGrabMode::None;
+        window.cursor_options.visible = true;
+    }
+}
+
 pub fn game_cleanup(
     mut commands: Commands,
+    mut spawn: ResMut<SpawnPoint>,
     cams: Query<Entity, With<PlayerCam>>,
     lights: Query<Entity, With<DirectionalLight>>,
+    skies: Query<Entity, With<SkyDome>>,
 ) {
+    // the next world may use different settings, so a recorded spot is meaningless there
+    spawn.custom = None;
     for e in &cams {
         commands.entity(e).despawn();
     }
     for e in &lights {
         commands.entity(e).despawn();
     }
+    for e in &skies {
+        commands.entity(e).despawn();
+    }
 }
@@ -0,0 +1,538 @@
+use std::collections::VecDeque;
+
+use bevy::diagnostic::{DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use bevy::prelude::*;
+
+use crate::player::{MoveSpeed, PlayerCam};
+use crate::settings::{Action, KeyBindings};
+use crate::state::AppState;
+use crate::world::{CHUNK_SIZE, Chunk, CullStats, FreezeChunks, GenerationStats, WorldParams};
+
+/// Whether the gameplay readout is shown. Kept across games.
+#[derive(Resource)]
+pub struct ShowHud(pub bool);
+
+impl Default for ShowHud {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+#[derive(Component)]
+pub struct HudText;
+
+/// Heading readout at the top of the screen, toggled with the HUD.
+#[derive(Component)]
+pub struct CompassText;
+
+/// Compass points clockwise from north, which is the -Z direction.
+const COMPASS_POINTS: [&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+
+/// Whether the debug overlay (`F3` by default) is shown. Kept across games.
+#[derive(Resource, Default)]
+pub struct ShowDebug(pub bool);
+
+#[derive(Component)]
+pub struct DebugText;
+
+/// Whether loaded chunks are outlined (`F6` by default). Kept across games.
+#[derive(Resource, Default)]
+pub struct ShowChunkBorders(pub bool);
+
+/// Outline colors of chunks at lod 1, lod 2 and anything coarser.
+const BORDER_COLORS: [Color; 3] = [
+    Color::srgb(0.2, 1.0, 0.3),
+    Color::srgb(1.0, 0.85, 0.2),
+    Color::srgb(1.0, 0.3, 0.2),
+];
+
+/// Whether the frame-time graph (`F7` by default) is shown. Kept across
+/// games.
+#[derive(Resource, Default)]
+pub struct ShowFrameGraph(pub bool);
+
+/// Frames shown by the frame-time graph, one bar each.
+const GRAPH_FRAMES: usize = 120;
+/// Frame time in milliseconds drawn at the full graph height.
+const GRAPH_MAX_MS: f32 = 50.0;
+const GRAPH_HEIGHT: f32 = 80.0;
+const GRAPH_BAR_WIDTH: f32 = 2.0;
+/// Height of the marks under frames that took in finished chunks.
+const GRAPH_MARK_HEIGHT: f32 = 3.0;
+/// Frame times up to which bars are green and yellow, in milliseconds:
+/// 60 and 30 frames per second.
+const GRAPH_GOOD_MS: f32 = 1000.0 / 60.0;
+const GRAPH_SLOW_MS: f32 = 1000.0 / 30.0;
+
+/// Recent frame times in milliseconds, oldest first, each with the number
+/// of chunk results taken in that frame.
+#[derive(Resource, Default)]
+struct FrameHistory {
+    frames: VecDeque<(f32, u64)>,
+    /// [`GenerationStats::finished`] at the last recorded frame.
+    finished: u64,
+}
+
+#[derive(Component)]
+pub struct FrameGraph;
+
+#[derive(Component)]
+struct GraphText;
+
+/// Bar of the frame `GRAPH_FRAMES - 1 - n` frames ago, newest on the right.
+#[derive(Component)]
+struct GraphBar(usize);
+
+/// Mark under a [`GraphBar`], lit when chunks arrived in that frame.
+#[derive(Component)]
+struct GraphMark(usize);
+
+/// Full-screen tint shown while the camera is under water.
+#[derive(Component)]
+pub struct UnderwaterTint;
+
+/// Plugin providing the lightweight speed, view distance and position
+/// readout shown during gameplay (toggled with `F1` by default), the debug
+/// overlay with frame rate and chunk culling statistics and the frame-time
+/// graph.
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(FrameTimeDiagnosticsPlugin::default())
+            .init_resource::<ShowHud>()
+            .init_resource::<ShowDebug>()
+            .init_resource::<ShowChunkBorders>()
+            .init_resource::<ShowFrameGraph>()
+            .init_resource::<FrameHistory>()
+            .add_systems(OnEnter(AppState::Playing), hud_setup)
+            .add_systems(
+                Update,
+                (
+                    toggle_hud,
+                    update_hud,
+                    update_compass,
+                    toggle_debug,
+                    toggle_freeze,
+                    update_debug,
+                    toggle_chunk_borders,
+                    draw_chunk_borders,
+                    record_frame_time,
+                    toggle_frame_graph,
+                    update_frame_graph,
+                    update_underwater_tint,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(OnExit(AppState::Playing), hud_cleanup);
+    }
+}
+
+fn hud_setup(
+    mut commands: Commands,
+    show: Res<ShowHud>,
+    debug: Res<ShowDebug>,
+    graph: Res<ShowFrameGraph>,
+) {
+    // spawned first so the text stays on top of it
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..Default::default()
+        },
+        BackgroundColor(Color::srgba(0.05, 0.2, 0.45, 0.45)),
+        Visibility::Hidden,
+        UnderwaterTint,
+    ));
+
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size: 16.0,
+            ..Default::default()
+        },
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(5.0),
+            left: Val::Px(5.0),
+            ..Default::default()
+        },
+        hud_visibility(show.0),
+        HudText,
+    ));
+
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size: 18.0,
+            ..Default::default()
+        },
+        TextLayout::new_with_justify(JustifyText::Center),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(5.0),
+            width: Val::Percent(100.0),
+            ..Default::default()
+        },
+        hud_visibility(show.0),
+        CompassText,
+    ));
+
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size: 16.0,
+            ..Default::default()
+        },
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(5.0),
+            right: Val::Px(5.0),
+            ..Default::default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.4)),
+        hud_visibility(debug.0),
+        DebugText,
+    ));
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(5.0),
+                left: Val::Px(5.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(3.0)),
+                ..Default::default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.4)),
+            hud_visibility(graph.0),
+            FrameGraph,
+        ))
+        .with_children(|graph| {
+            graph.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 14.0,
+                    ..Default::default()
+                },
+                GraphText,
+            ));
+            graph
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    align_items: AlignItems::FlexEnd,
+                    height: Val::Px(GRAPH_HEIGHT),
+                    ..Default::default()
+                })
+                .with_children(|bars| {
+                    for i in 0..GRAPH_FRAMES {
+                        bars.spawn((
+                            Node {
+                                width: Val::Px(GRAPH_BAR_WIDTH),
+                                height: Val::Px(0.0),
+                                ..Default::default()
+                            },
+                            BackgroundColor(Color::NONE),
+                            GraphBar(i),
+                        ));
+                    }
+                });
+            graph
+                .spawn(Node {
+                    flex_direction: FlexDirection::Row,
+                    margin: UiRect::top(Val::Px(1.0)),
+                    ..Default::default()
+                })
+                .with_children(|marks| {
+                    for i in 0..GRAPH_FRAMES {
+                        marks.spawn((
+                            Node {
+                                width: Val::Px(GRAPH_BAR_WIDTH),
+                                height: Val::Px(GRAPH_MARK_HEIGHT),
+                                ..Default::default()
+                            },
+                            BackgroundColor(Color::NONE),
+                            GraphMark(i),
+                        ));
+                    }
+                });
+        });
+}
+
+#[allow(clippy::type_complexity)]
+fn toggle_hud(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut show: ResMut<ShowHud>,
+    mut q: Query<&mut Visibility, Or<(With<HudText>, With<CompassText>)>>,
+) {
+    if !keys.just_pressed(bindings.key(Action::ToggleHud)) {
+        return;
+    }
+    show.0 = !show.0;
+    for mut vis in &mut q {
+        *vis = hud_visibility(show.0);
+    }
+}
+
+fn update_hud(
+    show: Res<ShowHud>,
+    speed: Res<MoveSpeed>,
+    params: Res<WorldParams>,
+    player: Query<&Transform, With<PlayerCam>>,
+    mut q: Query<&mut Text, With<HudText>>,
+) {
+    if !show.0 {
+        return;
+    }
+    let Ok(transform) = player.single() else {
+        return;
+    };
+    let pos = transform.translation;
+    for mut text in &mut q {
+        text.0 = format!(
+            "Speed: {:.1} | View: {} chunks | Pos: {:.1}, {:.1}, {:.1}",
+            speed.max, params.view_width, pos.x, pos.y, pos.z
+        );
+    }
+}
+
+fn update_compass(
+    show: Res<ShowHud>,
+    player: Query<&PlayerCam>,
+    mut q: Query<&mut Text, With<CompassText>>,
+) {
+    if !show.0 {
+        return;
+    }
+    let Ok(cam) = player.single() else {
+        return;
+    };
+    // yaw turns counter-clockwise seen from above, headings clockwise
+    let heading = (-cam.yaw).to_degrees().round().rem_euclid(360.0);
+    let point = COMPASS_POINTS[((heading / 45.0).round() as usize) % COMPASS_POINTS.len()];
+    for mut text in &mut q {
+        text.0 = format!("{point} {heading:.0}°");
+    }
+}
+
+fn toggle_debug(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut show: ResMut<ShowDebug>,
+    mut q: Query<&mut Visibility, With<DebugText>>,
+) {
+    if !keys.just_pressed(bindings.key(Action::ToggleDebug)) {
+        return;
+    }
+    show.0 = !show.0;
+    for mut vis in &mut q {
+        *vis = hud_visibility(show.0);
+    }
+}
+
+/// Freezes or resumes chunk loading when `F4` (by default) is pressed.
+fn toggle_freeze(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut freeze: ResMut<FreezeChunks>,
+) {
+    if keys.just_pressed(bindings.key(Action::FreezeChunks)) {
+        freeze.0 = !freeze.0;
+    }
+}
+
+fn toggle_chunk_borders(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut show: ResMut<ShowChunkBorders>,
+) {
+    if keys.just_pressed(bindings.key(Action::ToggleChunkBorders)) {
+        show.0 = !show.0;
+    }
+}
+
+/// Draws the box each loaded chunk's mesh fills, colored by its lod.
+fn draw_chunk_borders(show: Res<ShowChunkBorders>, chunks: Query<&Chunk>, mut gizmos: Gizmos) {
+    if !show.0 {
+        return;
+    }
+    let size = CHUNK_SIZE as f32;
+    for chunk in &chunks {
+        // chunk `c` meshes the voxels `c * CHUNK_SIZE + 1..=(c + 1) * CHUNK_SIZE`
+        let min = (chunk.coord * CHUNK_SIZE + IVec3::ONE).as_vec3();
+        let color = BORDER_COLORS[(chunk.lod as usize).clamp(1, BORDER_COLORS.len()) - 1];
+        gizmos.cuboid(
+            Transform::from_translation(min + Vec3::splat(size / 2.0))
+                .with_scale(Vec3::splat(size)),
+            color,
+        );
+    }
+}
+
+fn update_debug(
+    show: Res<ShowDebug>,
+    diagnostics: Res<DiagnosticsStore>,
+    stats: Res<CullStats>,
+    freeze: Res<FreezeChunks>,
+    generation: Res<GenerationStats>,
+    mut q: Query<&mut Text, With<DebugText>>,
+) {
+    if !show.0 {
+        return;
+    }
+    let fps = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+        .unwrap_or(0.0);
+    let timing: Vec<String> = [1, 2]
+        .into_iter()
+        .filter_map(|lod| {
+            let (mean, slowest) = generation.timing(lod)?;
+            Some(format!(
+                "lod {lod} {:.1} ms avg, {:.1} ms max",
+                mean.as_secs_f32() * 1000.0,
+                slowest.as_secs_f32() * 1000.0
+            ))
+        })
+        .collect();
+    for mut text in &mut q {
+        text.0 = format!(
+            "FPS: {fps:.1}\nChunks: {} loaded{}, {} visible, {} occluded, {} distant\nDraw calls: {} (occlusion saved {})\nGeneration: {}",
+            stats.chunks,
+            if freeze.0 { " (frozen)" } else { "" },
+            stats.visible,
+            stats.occluded,
+            stats.distant,
+            stats.draw_calls,
+            stats.saved_draw_calls,
+            if timing.is_empty() {
+                "none yet".to_string()
+            } else {
+                timing.join(" | ")
+            }
+        );
+    }
+}
+
+/// Appends this frame's time and the chunk results taken in during it to
+/// the [`FrameHistory`].
+fn record_frame_time(
+    diagnostics: Res<DiagnosticsStore>,
+    generation: Res<GenerationStats>,
+    mut history: ResMut<FrameHistory>,
+) {
+    let Some(ms) = diagnostics
+        .get(&FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        .and_then(|frame| frame.value())
+    else {
+        return;
+    };
+    let chunks = generation.finished() - history.finished;
+    history.finished = generation.finished();
+    if history.frames.len() == GRAPH_FRAMES {
+        history.frames.pop_front();
+    }
+    history.frames.push_back((ms as f32, chunks));
+}
+
+fn toggle_frame_graph(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut show: ResMut<ShowFrameGraph>,
+    mut q: Query<&mut Visibility, With<FrameGraph>>,
+) {
+    if !keys.just_pressed(bindings.key(Action::ToggleFrameGraph)) {
+        return;
+    }
+    show.0 = !show.0;
+    for mut vis in &mut q {
+        *vis = hud_visibility(show.0);
+    }
+}
+
+/// Sizes and colors the graph bars from the [`FrameHistory`], and marks
+/// the frames that took in finished chunks so spikes can be told apart
+/// from mesh uploads.
+fn update_frame_graph(
+    show: Res<ShowFrameGraph>,
+    history: Res<FrameHistory>,
+    mut bars: Query<(&GraphBar, &mut Node, &mut BackgroundColor), Without<GraphMark>>,
+    mut marks: Query<(&GraphMark, &mut BackgroundColor), Without<GraphBar>>,
+    mut texts: Query<&mut Text, With<GraphText>>,
+) {
+    if !show.0 {
+        return;
+    }
+    // the newest frame sits in the rightmost slot
+    let skipped = GRAPH_FRAMES - history.frames.len();
+    let frame = |slot: usize| slot.checked_sub(skipped).map(|i| history.frames[i]);
+    for (bar, mut node, mut color) in &mut bars {
+        let ms = frame(bar.0).map_or(0.0, |(ms, _)| ms);
+        node.height = Val::Px((ms / GRAPH_MAX_MS).min(1.0) * GRAPH_HEIGHT);
+        color.0 = if ms <= GRAPH_GOOD_MS {
+            Color::srgb(0.3, 0.9, 0.3)
+        } else if ms <= GRAPH_SLOW_MS {
+            Color::srgb(0.95, 0.8, 0.2)
+        } else {
+            Color::srgb(1.0, 0.3, 0.2)
+        };
+    }
+    for (mark, mut color) in &mut marks {
+        let chunks = frame(mark.0).map_or(0, |(_, chunks)| chunks);
+        color.0 = if chunks > 0 {
+            Color::srgb(0.4, 0.7, 1.0)
+        } else {
+            Color::NONE
+        };
+    }
+    let times = history.frames.iter().map(|(ms, _)| *ms);
+    let slowest = times.clone().fold(0.0, f32::max);
+    let mean = times.sum::<f32>() / history.frames.len().max(1) as f32;
+    for mut text in &mut texts {
+        text.0 = format!("Frame time: {mean:.1} ms avg, {slowest:.1} ms max");
+    }
+}
+
+fn hud_visibility(show: bool) -> Visibility {
+    if show {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    }
+}
+
+fn update_underwater_tint(
+    player: Query<&PlayerCam, Changed<PlayerCam>>,
+    mut q: Query<&mut Visibility, With<UnderwaterTint>>,
+) {
+    let Ok(cam) = player.single() else {
+        return;
+    };
+    for mut vis in &mut q {
+        *vis = hud_visibility(cam.submerged);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn hud_cleanup(
+    mut commands: Commands,
+    q: Query<
+        Entity,
+        Or<(
+            With<HudText>,
+            With<CompassText>,
+            With<DebugText>,
+            With<UnderwaterTint>,
+            With<FrameGraph>,
+        )>,
+    >,
+) {
+    for e in &q {
+        commands.entity(e).despawn();
+    }
+}
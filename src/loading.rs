@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+
+use crate::state::AppState;
+use crate::world::LoadingProgress;
+
+#[derive(Component)]
+pub struct LoadingRoot;
+
+#[derive(Component)]
+pub struct LoadingText;
+
+/// Plugin covering the screen with generation progress until the chunks
+/// around the player exist, see [`LoadingProgress`].
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Playing), loading_setup)
+            .add_systems(
+                Update,
+                update_loading_screen.run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(OnExit(AppState::Playing), loading_cleanup);
+    }
+}
+
+fn loading_setup(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..Default::default()
+            },
+            BackgroundColor(Color::srgb(0.05, 0.07, 0.1)),
+            // above the HUD and the console
+            GlobalZIndex(10),
+            LoadingRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Generating terrain..."),
+                TextFont {
+                    font_size: 32.0,
+                    ..Default::default()
+                },
+                LoadingText,
+            ));
+        });
+}
+
+/// Shows the chunk count while loading and hides the overlay once done.
+///
+/// The overlay is hidden rather than despawned, so rebuilding the world
+/// brings it back.
+fn update_loading_screen(
+    progress: Res<LoadingProgress>,
+    mut roots: Query<&mut Node, With<LoadingRoot>>,
+    mut texts: Query<&mut Text, With<LoadingText>>,
+) {
+    if !progress.is_changed() {
+        return;
+    }
+    for mut node in &mut roots {
+        node.display = if progress.finished {
+            Display::None
+        } else {
+            Display::Flex
+        };
+    }
+    for mut text in &mut texts {
+        text.0 = format!(
+            "Generating terrain... {}/{} chunks",
+            progress.done, progress.needed
+        );
+    }
+}
+
+fn loading_cleanup(mut commands: Commands, roots: Query<Entity, With<LoadingRoot>>) {
+    for e in &roots {
+        commands.entity(e).despawn();
+    }
+}
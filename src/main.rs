@@ -1,24 +1,64 @@
+mod biome;
+mod clipboard;
+mod clouds;
+mod console;
+mod display;
+mod drone;
 mod game;
+mod hud;
+mod loading;
 mod menu;
+mod minimap;
+mod pause;
 mod player;
+mod saves;
+mod selection;
 mod settings;
+mod sky;
 mod state;
+mod wind;
 mod world;
 
+use bevy::app::{TaskPoolOptions, TaskPoolThreadAssignmentPolicy};
 use bevy::prelude::*;
 use bevy::render::RenderPlugin;
 use bevy::render::renderer::RenderAdapterInfo;
 use bevy::render::settings::{Backends, RenderCreation, WgpuSettings};
 
-use game::{game_cleanup, return_to_menu, setup_game};
+use clouds::CloudsPlugin;
+use console::{ConsolePlugin, console_closed};
+use display::DisplayPlugin;
+use drone::DronePlugin;
+use game::{game_cleanup, release_cursor, return_to_menu, setup_game, toggle_pause};
+use hud::HudPlugin;
+use loading::LoadingPlugin;
 use menu::{
-    menu_actions, menu_cleanup, menu_setup, noise_actions, save_settings_on_l, update_noise_text,
-    update_view_text,
+    ActivePage, ExitRequested, MenuFocus, MenuPress, SeedInput, WorldName, close_requested,
+    copy_seed_on_c, display_actions, exit_dialog_actions, keyboard_navigation, menu_actions,
+    menu_cleanup, menu_setup, mouse_presses, noise_actions, open_requested_exit_dialog,
+    page_actions, repeat_held_steps, save_settings_on_l, scroll_steppers, seed_field_actions,
+    seed_input, tick_rate_actions, update_display_text, update_focus_highlight, update_height_text,
+    update_noise_text, update_orbit_text, update_pages, update_preview, update_render_text,
+    update_seed_input_text, update_seed_text, update_status_message, update_switch_text,
+    update_terrain_text, update_tick_rate_text, update_view_text, update_world_name_text,
+    world_actions, world_name_input,
 };
-use player::{keyboard_move, mouse_look};
-use settings::NoiseSettings;
-use state::AppState;
-use world::{WorldParams, WorldPlugin};
+use minimap::MinimapPlugin;
+use pause::PausePlugin;
+use player::{
+    MoveSpeed, SpawnPoint, apply_tick_rate, end_tick, interpolate_camera, keyboard_move,
+    mouse_look, orbit_camera, orbit_mode, resume_tick_position, set_spawn_point, start_tick,
+    teleport_to_spawn, toggle_walk, update_submerged,
+};
+use saves::{ActiveWorld, save_active_world};
+use selection::SelectionPlugin;
+use settings::{
+    KeyBindings, LookSettings, NoiseSettings, PhysicsSettings, SavedNoise, compute_threads,
+};
+use sky::{SkyColors, follow_camera};
+use state::{AppState, GameState};
+use wind::WindPlugin;
+use world::{WorldParams, WorldPlugin, world_loaded};
 
 fn main() {
     let forced = WgpuSettings {
@@ -26,39 +66,184 @@ fn main() {
         ..Default::default()
     };
 
+    let mut task_pool_options = TaskPoolOptions::default();
+    if let Some(threads) = compute_threads() {
+        // chunk generation runs on the async compute pool
+        task_pool_options.async_compute = TaskPoolThreadAssignmentPolicy {
+            min_threads: threads,
+            max_threads: threads,
+            percent: 1.0,
+            on_thread_spawn: None,
+            on_thread_destroy: None,
+        };
+    }
+
     App::new()
+        // without the `audio` feature Bevy leaves `AudioPlugin` out of the group
         .add_plugins(
             DefaultPlugins
                 .set(RenderPlugin {
                     render_creation: RenderCreation::Automatic(forced),
                     ..Default::default()
                 })
+                .set(TaskPoolPlugin { task_pool_options })
                 .set(WindowPlugin {
                     primary_window: Some(Window {
                         title: "Voxel World".into(),
                         resolution: (800., 600.).into(),
                         ..Default::default()
                     }),
+                    // `close_requested` asks about unsaved settings first
+                    close_when_requested: false,
                     ..Default::default()
                 }),
         )
         .init_resource::<WorldParams>()
         .init_resource::<NoiseSettings>()
-        .add_plugins(WorldPlugin)
+        .init_resource::<SavedNoise>()
+        .init_resource::<KeyBindings>()
+        .init_resource::<PhysicsSettings>()
+        .init_resource::<LookSettings>()
+        .init_resource::<ActivePage>()
+        .init_resource::<MenuFocus>()
+        .init_resource::<WorldName>()
+        .init_resource::<SeedInput>()
+        .init_resource::<ExitRequested>()
+        .add_event::<MenuPress>()
+        .init_resource::<MoveSpeed>()
+        .init_resource::<SpawnPoint>()
+        .init_resource::<SkyColors>()
+        .init_resource::<ActiveWorld>()
+        .add_plugins((WindPlugin, WorldPlugin, DisplayPlugin))
         .init_state::<AppState>()
+        .add_sub_state::<GameState>()
+        .add_plugins((
+            PausePlugin,
+            HudPlugin,
+            ConsolePlugin,
+            SelectionPlugin,
+            LoadingPlugin,
+            CloudsPlugin,
+            MinimapPlugin,
+            DronePlugin,
+        ))
         .add_systems(OnEnter(AppState::Menu), menu_setup)
+        .add_systems(Update, mouse_presses.run_if(in_state(AppState::Menu)))
+        .add_systems(
+            Update,
+            (repeat_held_steps, scroll_steppers).run_if(in_state(AppState::Menu)),
+        )
+        .add_systems(Update, keyboard_navigation.run_if(in_state(AppState::Menu)))
+        .add_systems(
+            Update,
+            update_focus_highlight.run_if(in_state(AppState::Menu)),
+        )
         .add_systems(Update, menu_actions.run_if(in_state(AppState::Menu)))
         .add_systems(Update, noise_actions.run_if(in_state(AppState::Menu)))
+        .add_systems(Update, display_actions.run_if(in_state(AppState::Menu)))
+        .add_systems(Update, update_display_text.run_if(in_state(AppState::Menu)))
+        .add_systems(
+            Update,
+            (tick_rate_actions, update_tick_rate_text)
+                .chain()
+                .run_if(in_state(AppState::Menu)),
+        )
         .add_systems(Update, update_view_text.run_if(in_state(AppState::Menu)))
+        .add_systems(Update, update_render_text.run_if(in_state(AppState::Menu)))
+        .add_systems(Update, update_height_text.run_if(in_state(AppState::Menu)))
+        .add_systems(Update, update_orbit_text.run_if(in_state(AppState::Menu)))
+        .add_systems(Update, page_actions.run_if(in_state(AppState::Menu)))
+        .add_systems(Update, update_pages.run_if(in_state(AppState::Menu)))
         .add_systems(Update, update_noise_text.run_if(in_state(AppState::Menu)))
+        .add_systems(Update, update_switch_text.run_if(in_state(AppState::Menu)))
+        .add_systems(Update, update_terrain_text.run_if(in_state(AppState::Menu)))
+        .add_systems(Update, exit_dialog_actions.run_if(in_state(AppState::Menu)))
+        .add_systems(Update, close_requested)
+        .add_systems(
+            Update,
+            open_requested_exit_dialog.run_if(in_state(AppState::Menu)),
+        )
+        .add_systems(Update, update_seed_text.run_if(in_state(AppState::Menu)))
+        .add_systems(
+            Update,
+            (seed_input, seed_field_actions, update_seed_input_text)
+                .chain()
+                .run_if(in_state(AppState::Menu)),
+        )
+        .add_systems(Update, copy_seed_on_c.run_if(in_state(AppState::Menu)))
+        .add_systems(Update, update_preview.run_if(in_state(AppState::Menu)))
         .add_systems(Update, save_settings_on_l.run_if(in_state(AppState::Menu)))
+        .add_systems(
+            Update,
+            update_status_message.run_if(in_state(AppState::Menu)),
+        )
+        .add_systems(Update, world_name_input.run_if(in_state(AppState::Menu)))
+        .add_systems(
+            Update,
+            update_world_name_text.run_if(in_state(AppState::Menu)),
+        )
+        .add_systems(Update, world_actions.run_if(in_state(AppState::Menu)))
         .add_systems(OnExit(AppState::Menu), menu_cleanup)
         .add_systems(OnEnter(AppState::Playing), setup_game)
         .add_systems(
             Update,
-            (mouse_look, keyboard_move, return_to_menu).run_if(in_state(AppState::Playing)),
+            (mouse_look, teleport_to_spawn, set_spawn_point, toggle_walk)
+                .run_if(in_state(GameState::Running))
+                .run_if(console_closed)
+                .run_if(not(orbit_mode)),
+        )
+        // movement and gravity step at a fixed rate so jumps do not depend on the frame rate
+        .add_systems(
+            FixedUpdate,
+            (
+                update_submerged.run_if(in_state(GameState::Running)),
+                // nothing to stand on until the nearby chunks exist
+                keyboard_move
+                    .run_if(in_state(GameState::Running))
+                    .run_if(world_loaded)
+                    .run_if(console_closed)
+                    .run_if(not(orbit_mode)),
+            )
+                .chain(),
+        )
+        // the camera is drawn between the last two steps, see `TickBlend`
+        .add_systems(
+            RunFixedMainLoop,
+            (
+                resume_tick_position.in_set(RunFixedMainLoopSystem::BeforeFixedMainLoop),
+                interpolate_camera.in_set(RunFixedMainLoopSystem::AfterFixedMainLoop),
+            ),
+        )
+        .add_systems(FixedFirst, start_tick)
+        .add_systems(FixedLast, end_tick)
+        .add_systems(Update, apply_tick_rate)
+        .add_systems(
+            Update,
+            return_to_menu
+                .run_if(in_state(GameState::Running))
+                .run_if(console_closed),
+        )
+        .add_systems(
+            Update,
+            orbit_camera
+                .run_if(in_state(GameState::Running))
+                .run_if(orbit_mode),
+        )
+        .add_systems(Update, follow_camera.run_if(in_state(AppState::Playing)))
+        .add_systems(
+            Update,
+            toggle_pause
+                .run_if(in_state(AppState::Playing))
+                .run_if(console_closed),
+        )
+        .add_systems(
+            OnExit(AppState::Playing),
+            (
+                save_active_world.before(game_cleanup),
+                game_cleanup,
+                release_cursor,
+            ),
         )
-        .add_systems(OnExit(AppState::Playing), game_cleanup)
         .add_systems(Startup, print_backend)
         .run();
 }
@@ -1,10 +1,44 @@
 use bevy::app::AppExit;
 use bevy::ecs::hierarchy::ChildSpawnerCommands;
+use bevy::input::ButtonState;
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
 use bevy::prelude::*;
+use bevy::window::{PrimaryWindow, WindowCloseRequested};
 
-use crate::settings::NoiseSettings;
+use crate::clipboard::copy_seed;
+use crate::player::SpawnPoint;
+use crate::saves::{
+    ActiveWorld, MAX_NAME_LEN, WorldManifest, delete_world, list_worlds, load_world, save_world,
+    valid_name, worlds_dir,
+};
+use crate::settings::{
+    Action, BRIGHTNESS_LEVELS, DisplaySettings, FPS_CAPS, KeyBindings, LookSettings, MAX_OCTAVES,
+    MSAA_SAMPLES, NoiseSettings, PhysicsSettings, QualityPreset, RidgeMode, SKY_COLORS,
+    SUN_ANGLE_STEP, SUN_ELEVATIONS, SUN_ILLUMINANCES, SavedNoise, TICK_RATE_STEPS, key_label,
+    save_settings, settings_path,
+};
 use crate::state::AppState;
-use crate::world::WorldParams;
+use crate::world::{
+    MAX_FEATURE_DENSITY, MAX_HEIGHT_CHUNKS, MAX_RIDGE_HEIGHT, PREVIEW_SIZE, WorldParams,
+    heightmap_preview,
+};
+
+const BUTTON_COLOR: Color = Color::srgb(0.15, 0.15, 0.15);
+const ACTIVE_TAB_COLOR: Color = Color::srgb(0.3, 0.3, 0.3);
+const FOCUS_COLOR: Color = Color::srgb(0.25, 0.35, 0.55);
+/// Seconds a status message such as the result of a save stays on screen.
+const STATUS_MESSAGE_SECS: f32 = 2.0;
+/// Seconds a `-` or `+` button is held before it starts repeating.
+const REPEAT_DELAY: f32 = 0.4;
+/// Seconds between the first repeats of a held button.
+const REPEAT_INTERVAL: f32 = 0.15;
+/// Factor the repeat interval shrinks by after every repeat.
+const REPEAT_ACCELERATION: f32 = 0.85;
+/// Shortest repeat interval a held button reaches.
+const MIN_REPEAT_INTERVAL: f32 = 0.02;
+/// Pixels of touchpad scrolling that nudge a stepper by one step.
+const SCROLL_PIXELS_PER_STEP: f32 = 40.0;
 
 #[derive(Component)]
 pub struct MenuRoot;
@@ -12,6 +46,84 @@ pub struct MenuRoot;
 #[derive(Component)]
 pub struct MenuCamera;
 
+/// Settings pages of the title screen, shown one at a time.
+#[derive(Component, Clone, Copy, Default, Debug, Eq, PartialEq)]
+pub enum MenuPage {
+    #[default]
+    World,
+    Terrain,
+    Display,
+    Controls,
+    Worlds,
+}
+
+impl MenuPage {
+    const ALL: [MenuPage; 5] = [
+        MenuPage::World,
+        MenuPage::Terrain,
+        MenuPage::Display,
+        MenuPage::Controls,
+        MenuPage::Worlds,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            MenuPage::World => "World",
+            MenuPage::Terrain => "Terrain",
+            MenuPage::Display => "Display",
+            MenuPage::Controls => "Controls",
+            MenuPage::Worlds => "Worlds",
+        }
+    }
+}
+
+/// Currently visible settings page.
+#[derive(Resource, Default)]
+pub struct ActivePage(pub MenuPage);
+
+#[derive(Component)]
+pub struct PageButton(pub MenuPage);
+
+/// A menu button was activated, either clicked or pressed from the keyboard.
+#[derive(Event)]
+pub struct MenuPress(pub Entity);
+
+/// Menu control reachable with keyboard navigation.
+#[derive(Component)]
+pub struct Focusable {
+    /// Background shown while the control is not focused.
+    base: Color,
+    kind: FocusKind,
+}
+
+enum FocusKind {
+    /// Enter presses the control itself.
+    Button,
+    /// Left and Right press the `-` and `+` buttons.
+    Stepper { minus: Entity, plus: Entity },
+    /// Typed keys edit the field and Enter presses it, see [`seed_input`].
+    TextField,
+}
+
+/// `-` or `+` button of a stepper, repeated while held.
+#[derive(Component)]
+pub struct StepButton;
+
+/// Step button being held down, see [`repeat_held_steps`].
+#[derive(Default)]
+pub struct HeldStep {
+    button: Option<Entity>,
+    /// Seconds since the button was pressed.
+    held: f32,
+    /// Hold time at which the next repeat fires.
+    next: f32,
+    interval: f32,
+}
+
+/// Index of the focused control among the visible [`Focusable`]s in screen order.
+#[derive(Resource, Default)]
+pub struct MenuFocus(pub usize);
+
 #[derive(Component)]
 pub struct ViewText;
 
@@ -20,18 +132,328 @@ pub struct ViewButton {
     pub delta: i32,
 }
 
+#[derive(Component)]
+pub struct RenderText;
+
+#[derive(Component)]
+pub struct RenderButton {
+    pub delta: i32,
+}
+
+#[derive(Component)]
+pub struct HeightText;
+
+#[derive(Component)]
+pub struct HeightButton {
+    pub delta: i32,
+}
+
+/// World seed readout on the World page.
+#[derive(Component)]
+pub struct SeedText;
+
+/// Most characters of a typed seed, enough for `i32::MIN`.
+const MAX_SEED_LEN: usize = 11;
+
+/// Seed typed into the [`SeedField`], applied when Enter is pressed on it.
+#[derive(Resource, Default)]
+pub struct SeedInput {
+    text: String,
+    /// Byte offset of the text cursor; the text only holds ASCII.
+    cursor: usize,
+}
+
+impl SeedInput {
+    /// Inserts `c` at the cursor if the text stays a possible seed: digits,
+    /// with an optional leading minus.
+    fn insert(&mut self, c: char) {
+        let fits = c.is_ascii_digit() || (c == '-' && self.cursor == 0);
+        if fits && self.text.len() < MAX_SEED_LEN && !self.text[self.cursor..].starts_with('-') {
+            self.text.insert(self.cursor, c);
+            self.cursor += 1;
+        }
+    }
+
+    fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            self.text.remove(self.cursor);
+        }
+    }
+
+    fn delete(&mut self) {
+        if self.cursor < self.text.len() {
+            self.text.remove(self.cursor);
+        }
+    }
+
+    /// Applies a key pressed while the field has focus.
+    fn edit(&mut self, key: &Key) {
+        match key {
+            Key::Backspace => self.backspace(),
+            Key::Delete => self.delete(),
+            Key::ArrowLeft => self.cursor = self.cursor.saturating_sub(1),
+            Key::ArrowRight => self.cursor = (self.cursor + 1).min(self.text.len()),
+            Key::Home => self.cursor = 0,
+            Key::End => self.cursor = self.text.len(),
+            // anything but digits and a leading minus is ignored
+            Key::Character(text) => {
+                for c in text.chars() {
+                    self.insert(c);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Text field on the World page for typing in a shared seed.
+#[derive(Component)]
+pub struct SeedField;
+
+#[derive(Component)]
+pub struct SeedInputText;
+
+/// Rolls a new random world seed; saved only with the other settings.
+#[derive(Component)]
+pub struct RandomizeButton;
+
+#[derive(Component)]
+pub struct OrbitText;
+
+/// Switches the orbit camera off (`delta < 0`) or on.
+#[derive(Component)]
+pub struct OrbitButton {
+    pub delta: i32,
+}
+
+/// Image node showing the terrain preview for the current noise settings.
+#[derive(Component)]
+pub struct PreviewImage;
+
 #[derive(Component)]
 pub struct StartButton;
 
 #[derive(Component)]
 pub struct ExitButton;
 
+/// Overlay asking what to do with unsaved noise settings on exit, hidden
+/// until Exit is pressed or the window is closed with changes pending.
+#[derive(Component)]
+pub struct ExitDialog;
+
+/// Set when the window was asked to close away from the menu or with unsaved
+/// noise settings, so the menu exits or opens its [`ExitDialog`] once shown.
+#[derive(Resource, Default)]
+pub struct ExitRequested(pub bool);
+
+#[derive(Component, Clone, Copy)]
+pub enum ExitChoice {
+    Save,
+    Discard,
+    Cancel,
+}
+
+#[derive(Component, Clone, Copy)]
+pub enum DisplayField {
+    VSync,
+    FpsCap,
+    Msaa,
+    Shadows,
+    Brightness,
+    /// Background color, one of [`SKY_COLORS`].
+    SkyColor,
+    SunAzimuth,
+    SunElevation,
+    /// Sunlight strength, one of [`SUN_ILLUMINANCES`].
+    SunIlluminance,
+}
+
+impl DisplayField {
+    const ALL: [DisplayField; 9] = [
+        DisplayField::VSync,
+        DisplayField::FpsCap,
+        DisplayField::Msaa,
+        DisplayField::Shadows,
+        DisplayField::Brightness,
+        DisplayField::SkyColor,
+        DisplayField::SunAzimuth,
+        DisplayField::SunElevation,
+        DisplayField::SunIlluminance,
+    ];
+}
+
+#[derive(Component)]
+pub struct DisplayText(pub DisplayField);
+
+#[derive(Component)]
+pub struct QualityText;
+
+#[derive(Component)]
+pub struct TickRateText;
+
+/// Steps [`PhysicsSettings::tick_rate`] through [`TICK_RATE_STEPS`].
+#[derive(Component)]
+pub struct TickRateButton(pub i32);
+
+/// Applies a [`QualityPreset`] to the display and view settings.
+#[derive(Component)]
+pub struct QualityButton(pub QualityPreset);
+
+#[derive(Component)]
+pub struct DisplayButton {
+    pub field: DisplayField,
+    pub delta: i32,
+}
+
 #[derive(Component, Clone, Copy)]
 pub enum NoiseField {
     Amplitude,
     Frequency,
+    /// Fractal octaves of the layer, see [`crate::settings::LayerFractal`].
+    Octaves,
+}
+
+/// Two-way generation setting switched on the World and Terrain pages.
+#[derive(Component, Clone, Copy)]
+pub enum SwitchField {
+    Superflat,
+    NormalizeHeight,
+    /// 3D density terrain.
+    Overhangs,
+    /// Multiplicative rather than additive [`NoiseSettings::ridge_mode`].
+    ScaledRidges,
+    Caves,
+    Bedrock,
+    Trees,
+    Boulders,
+}
+
+impl SwitchField {
+    /// Switches on the Terrain page, in order.
+    const TERRAIN: [SwitchField; 7] = [
+        SwitchField::ScaledRidges,
+        SwitchField::NormalizeHeight,
+        SwitchField::Overhangs,
+        SwitchField::Caves,
+        SwitchField::Bedrock,
+        SwitchField::Trees,
+        SwitchField::Boulders,
+    ];
+
+    fn get(self, settings: &NoiseSettings) -> bool {
+        match self {
+            SwitchField::Superflat => settings.superflat,
+            SwitchField::NormalizeHeight => settings.normalize_height,
+            SwitchField::Overhangs => settings.overhangs,
+            SwitchField::ScaledRidges => settings.ridge_mode == RidgeMode::Multiplicative,
+            SwitchField::Caves => settings.caves,
+            SwitchField::Bedrock => settings.bedrock,
+            SwitchField::Trees => settings.trees,
+            SwitchField::Boulders => settings.boulders,
+        }
+    }
+
+    fn set(self, settings: &mut NoiseSettings, on: bool) {
+        let flag = match self {
+            SwitchField::Superflat => &mut settings.superflat,
+            SwitchField::NormalizeHeight => &mut settings.normalize_height,
+            SwitchField::Overhangs => &mut settings.overhangs,
+            SwitchField::ScaledRidges => {
+                settings.ridge_mode = if on {
+                    RidgeMode::Multiplicative
+                } else {
+                    RidgeMode::Additive
+                };
+                return;
+            }
+            SwitchField::Caves => &mut settings.caves,
+            SwitchField::Bedrock => &mut settings.bedrock,
+            SwitchField::Trees => &mut settings.trees,
+            SwitchField::Boulders => &mut settings.boulders,
+        };
+        *flag = on;
+    }
+}
+
+#[derive(Component)]
+pub struct SwitchText(pub SwitchField);
+
+/// Switches a generation setting off (`delta < 0`) or on.
+#[derive(Component)]
+pub struct SwitchButton {
+    pub field: SwitchField,
+    pub delta: i32,
+}
+
+/// Terrain height or feature density stepped on the Terrain page.
+#[derive(Component, Clone, Copy)]
+pub enum TerrainField {
+    BaseHeight,
+    RidgeHeight,
+    TreeDensity,
+    BoulderDensity,
+}
+
+impl TerrainField {
+    /// Amount one press of a stepper changes the field by.
+    fn step(self) -> f32 {
+        match self {
+            TerrainField::BaseHeight => BASE_HEIGHT_STEP as f32,
+            TerrainField::RidgeHeight => RIDGE_HEIGHT_STEP,
+            TerrainField::TreeDensity | TerrainField::BoulderDensity => FEATURE_DENSITY_STEP,
+        }
+    }
+
+    /// Moves the field by `delta`, kept within its range and below
+    /// `max_height`.
+    fn apply(self, settings: &mut NoiseSettings, delta: f32, max_height: i32) {
+        let density = match self {
+            TerrainField::BaseHeight => {
+                settings.base_height =
+                    (settings.base_height + delta as i32).clamp(1, max_height - 1);
+                return;
+            }
+            TerrainField::RidgeHeight => {
+                settings.ridge_height =
+                    (settings.ridge_height + delta).clamp(0.0, MAX_RIDGE_HEIGHT);
+                return;
+            }
+            TerrainField::TreeDensity => &mut settings.tree_density,
+            TerrainField::BoulderDensity => &mut settings.boulder_density,
+        };
+        // snapped to the step so repeated presses land back on zero exactly
+        let steps = ((*density + delta) / FEATURE_DENSITY_STEP).round();
+        *density = (steps * FEATURE_DENSITY_STEP).clamp(0.0, MAX_FEATURE_DENSITY);
+    }
+}
+
+#[derive(Component)]
+pub struct TerrainText(pub TerrainField);
+
+/// Moves a [`TerrainField`] by `delta`.
+#[derive(Component)]
+pub struct TerrainButton {
+    pub field: TerrainField,
+    pub delta: f32,
+}
+
+/// Multiplies one field of every noise layer by `factor`.
+#[derive(Component)]
+pub struct ScaleButton {
+    pub field: NoiseField,
+    pub factor: f32,
 }
 
+/// Step factor of the global scale buttons.
+const SCALE_STEP: f32 = 1.1;
+/// Amount the tree and boulder density buttons change the scale by.
+const FEATURE_DENSITY_STEP: f32 = 0.1;
+/// Blocks the base height buttons move the ground by.
+const BASE_HEIGHT_STEP: i32 = 4;
+/// Blocks one press of a ridge height button moves it by.
+const RIDGE_HEIGHT_STEP: f32 = 5.0;
+
 #[derive(Component)]
 pub struct NoiseText {
     pub layer: usize,
@@ -45,7 +467,49 @@ pub struct NoiseButton {
     pub delta: f32,
 }
 
-pub fn menu_setup(mut commands: Commands, params: Res<WorldParams>, settings: Res<NoiseSettings>) {
+/// Name typed on the Worlds page for the next created world.
+#[derive(Resource, Default)]
+pub struct WorldName(pub String);
+
+#[derive(Component)]
+pub struct WorldNameText;
+
+/// Saves a new world named after [`WorldName`] and starts it.
+#[derive(Component)]
+pub struct CreateWorldButton;
+
+/// Loads the named world and resumes it.
+#[derive(Component)]
+pub struct LoadWorldButton(pub String);
+
+/// Deletes the named world and removes its `row` from the list.
+#[derive(Component)]
+pub struct DeleteWorldButton {
+    pub name: String,
+    pub row: Entity,
+}
+
+/// Briefly shows the outcome of the last save or world operation.
+#[derive(Component)]
+pub struct StatusMessage {
+    timer: Timer,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn menu_setup(
+    mut commands: Commands,
+    params: Res<WorldParams>,
+    settings: Res<NoiseSettings>,
+    display: Res<DisplaySettings>,
+    physics: Res<PhysicsSettings>,
+    bindings: Res<KeyBindings>,
+    active: Res<ActivePage>,
+    world_name: Res<WorldName>,
+    seed_input: Res<SeedInput>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let preview = images.add(heightmap_preview(&settings, params.max_height()));
+
     let root = commands
         .spawn((
             Node {
@@ -71,309 +535,1008 @@ pub fn menu_setup(mut commands: Commands, params: Res<WorldParams>, settings: Re
             },
         ));
 
-        spawn_view_row(parent, params.view_width);
-        spawn_noise_rows(parent, &settings);
+        parent
+            .spawn((Node {
+                flex_direction: FlexDirection::Row,
+                margin: UiRect::all(Val::Px(5.0)),
+                ..Default::default()
+            },))
+            .with_children(|tabs| {
+                for page in MenuPage::ALL {
+                    let color = if page == active.0 {
+                        ACTIVE_TAB_COLOR
+                    } else {
+                        BUTTON_COLOR
+                    };
+                    spawn_button(tabs, page.label(), color, PageButton(page));
+                }
+            });
 
         parent
-            .spawn((
-                Button,
-                Node {
-                    padding: UiRect::axes(Val::Px(10.0), Val::Px(5.0)),
-                    margin: UiRect::all(Val::Px(5.0)),
-                    ..Default::default()
-                },
-                BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
-                StartButton,
-            ))
-            .with_children(|p| {
-                p.spawn((
-                    Text::new("Start Game"),
-                    TextFont {
-                        font_size: 24.0,
+            .spawn((Node {
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },))
+            .with_children(|columns| {
+                spawn_page(columns, MenuPage::World, active.0, |page| {
+                    page.spawn((
+                        Text::new(seed_label(&settings, &bindings)),
+                        TextFont {
+                            font_size: 18.0,
+                            ..Default::default()
+                        },
+                        SeedText,
+                    ));
+                    page.spawn((
+                        Node {
+                            padding: UiRect::all(Val::Px(2.0)),
+                            ..Default::default()
+                        },
+                        BackgroundColor(Color::NONE),
+                        // clicked to focus, without sending a press like a button
+                        Interaction::default(),
+                        Focusable {
+                            base: Color::NONE,
+                            kind: FocusKind::TextField,
+                        },
+                        SeedField,
+                    ))
+                    .with_children(|field| {
+                        field.spawn((
+                            Text::new(seed_input_label(&seed_input)),
+                            TextFont {
+                                font_size: 24.0,
+                                ..Default::default()
+                            },
+                            SeedInputText,
+                        ));
+                    });
+                    spawn_button(page, "Randomize", BUTTON_COLOR, RandomizeButton);
+                    spawn_stepper_row(
+                        page,
+                        format!("View Width: {}", params.view_width),
+                        ViewText,
+                        ViewButton { delta: -1 },
+                        ViewButton { delta: 1 },
+                    );
+                    spawn_stepper_row(
+                        page,
+                        render_label(&params),
+                        RenderText,
+                        RenderButton { delta: -1 },
+                        RenderButton { delta: 1 },
+                    );
+                    spawn_stepper_row(
+                        page,
+                        format!("World Height: {} chunks", params.height_chunks),
+                        HeightText,
+                        HeightButton { delta: -1 },
+                        HeightButton { delta: 1 },
+                    );
+                    spawn_switch_row(page, SwitchField::Superflat, &settings);
+                    spawn_stepper_row(
+                        page,
+                        orbit_label(&params),
+                        OrbitText,
+                        OrbitButton { delta: -1 },
+                        OrbitButton { delta: 1 },
+                    );
+                });
+                spawn_page(columns, MenuPage::Terrain, active.0, |page| {
+                    for field in [TerrainField::BaseHeight, TerrainField::RidgeHeight] {
+                        spawn_terrain_row(page, field, &settings);
+                    }
+                    for field in SwitchField::TERRAIN {
+                        spawn_switch_row(page, field, &settings);
+                    }
+                    for field in [TerrainField::TreeDensity, TerrainField::BoulderDensity] {
+                        spawn_terrain_row(page, field, &settings);
+                    }
+                    spawn_scale_row(page);
+                    spawn_noise_rows(page, &settings);
+                });
+                spawn_page(columns, MenuPage::Display, active.0, |page| {
+                    page.spawn((
+                        Text::new(quality_label(&display)),
+                        TextFont {
+                            font_size: 24.0,
+                            ..Default::default()
+                        },
+                        QualityText,
+                    ));
+                    page.spawn(Node {
+                        flex_direction: FlexDirection::Row,
+                        ..Default::default()
+                    })
+                    .with_children(|row| {
+                        for preset in QualityPreset::ALL {
+                            spawn_button(row, preset.label(), BUTTON_COLOR, QualityButton(preset));
+                        }
+                    });
+                    for field in DisplayField::ALL {
+                        spawn_stepper_row(
+                            page,
+                            display_label(field, &display),
+                            DisplayText(field),
+                            DisplayButton { field, delta: -1 },
+                            DisplayButton { field, delta: 1 },
+                        );
+                    }
+                });
+                // bindings are read-only; they are changed under `keys` in settings.json
+                spawn_page(columns, MenuPage::Controls, active.0, |page| {
+                    spawn_stepper_row(
+                        page,
+                        tick_rate_label(&physics),
+                        TickRateText,
+                        TickRateButton(-1),
+                        TickRateButton(1),
+                    );
+                    for action in Action::ALL {
+                        page.spawn((
+                            Text::new(format!(
+                                "{}: {}",
+                                action.label(),
+                                key_label(bindings.key(action))
+                            )),
+                            TextFont {
+                                font_size: 18.0,
+                                ..Default::default()
+                            },
+                        ));
+                    }
+                });
+
+                spawn_page(columns, MenuPage::Worlds, active.0, |page| {
+                    page.spawn((Node {
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::Center,
+                        margin: UiRect::all(Val::Px(5.0)),
+                        ..Default::default()
+                    },))
+                        .with_children(|row| {
+                            row.spawn((
+                                Text::new(world_name_label(&world_name)),
+                                TextFont {
+                                    font_size: 24.0,
+                                    ..Default::default()
+                                },
+                                WorldNameText,
+                            ));
+                            spawn_button(row, "Create", BUTTON_COLOR, CreateWorldButton);
+                        });
+
+                    let worlds = list_worlds(&worlds_dir());
+                    if worlds.is_empty() {
+                        page.spawn((
+                            Text::new("No saved worlds"),
+                            TextFont {
+                                font_size: 18.0,
+                                ..Default::default()
+                            },
+                        ));
+                    }
+                    for world in worlds {
+                        page.spawn((Node {
+                            flex_direction: FlexDirection::Row,
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },))
+                            .with_children(|row| {
+                                let entity = row.target_entity();
+                                spawn_button(
+                                    row,
+                                    &world,
+                                    BUTTON_COLOR,
+                                    LoadWorldButton(world.clone()),
+                                );
+                                spawn_button(
+                                    row,
+                                    "Delete",
+                                    BUTTON_COLOR,
+                                    DeleteWorldButton {
+                                        name: world,
+                                        row: entity,
+                                    },
+                                );
+                            });
+                    }
+                });
+
+                columns.spawn((
+                    ImageNode::new(preview),
+                    Node {
+                        width: Val::Px(PREVIEW_SIZE as f32 * 2.0),
+                        height: Val::Px(PREVIEW_SIZE as f32 * 2.0),
+                        margin: UiRect::all(Val::Px(10.0)),
                         ..Default::default()
                     },
-                    TextColor::default(),
+                    PreviewImage,
                 ));
             });
 
+        parent
+            .spawn((Node {
+                flex_direction: FlexDirection::Row,
+                ..Default::default()
+            },))
+            .with_children(|buttons| {
+                spawn_button(buttons, "Start Game", BUTTON_COLOR, StartButton);
+                spawn_button(buttons, "Exit", BUTTON_COLOR, ExitButton);
+            });
+
+        parent.spawn((
+            Text::new(""),
+            StatusMessage {
+                timer: Timer::from_seconds(STATUS_MESSAGE_SECS, TimerMode::Once),
+            },
+        ));
+
         parent
             .spawn((
-                Button,
                 Node {
-                    padding: UiRect::axes(Val::Px(10.0), Val::Px(5.0)),
-                    margin: UiRect::all(Val::Px(5.0)),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    display: Display::None,
                     ..Default::default()
                 },
-                BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
-                ExitButton,
+                BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+                ExitDialog,
             ))
-            .with_children(|p| {
-                p.spawn((
-                    Text::new("Exit"),
+            .with_children(|dialog| {
+                dialog.spawn((
+                    Text::new("The noise settings have unsaved changes."),
                     TextFont {
-                        font_size: 24.0,
+                        font_size: 28.0,
                         ..Default::default()
                     },
-                    TextColor::default(),
                 ));
+                dialog
+                    .spawn(Node {
+                        flex_direction: FlexDirection::Row,
+                        ..Default::default()
+                    })
+                    .with_children(|buttons| {
+                        spawn_button(buttons, "Save", BUTTON_COLOR, ExitChoice::Save);
+                        spawn_button(buttons, "Discard", BUTTON_COLOR, ExitChoice::Discard);
+                        spawn_button(buttons, "Cancel", BUTTON_COLOR, ExitChoice::Cancel);
+                    });
             });
     });
 }
 
-fn spawn_view_row(parent: &mut ChildSpawnerCommands, value: i32) {
+/// Spawns a settings page container that is only displayed while it is active.
+fn spawn_page(
+    parent: &mut ChildSpawnerCommands,
+    page: MenuPage,
+    active: MenuPage,
+    build: impl FnOnce(&mut ChildSpawnerCommands),
+) {
     parent
-        .spawn((Node {
-            flex_direction: FlexDirection::Row,
-            align_items: AlignItems::Center,
-            margin: UiRect::all(Val::Px(5.0)),
-            ..Default::default()
-        },))
-        .with_children(|row| {
-            row.spawn((
-                Text::new(format!("View Width: {}", value)),
+        .spawn((
+            Node {
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                display: if page == active {
+                    Display::Flex
+                } else {
+                    Display::None
+                },
+                ..Default::default()
+            },
+            page,
+        ))
+        .with_children(build);
+}
+
+/// Spawns a text button carrying `marker`.
+fn spawn_button(parent: &mut ChildSpawnerCommands, label: &str, color: Color, marker: impl Bundle) {
+    parent
+        .spawn((
+            Button,
+            Node {
+                padding: UiRect::axes(Val::Px(10.0), Val::Px(5.0)),
+                margin: UiRect::all(Val::Px(5.0)),
+                ..Default::default()
+            },
+            BackgroundColor(color),
+            Focusable {
+                base: color,
+                kind: FocusKind::Button,
+            },
+            marker,
+        ))
+        .with_children(|p| {
+            p.spawn((
+                Text::new(label),
                 TextFont {
                     font_size: 24.0,
                     ..Default::default()
                 },
                 TextColor::default(),
-                ViewText,
             ));
-
-            row.spawn((
-                Button,
-                Node {
-                    padding: UiRect::axes(Val::Px(5.0), Val::Px(2.0)),
-                    margin: UiRect::left(Val::Px(5.0)),
-                    ..Default::default()
-                },
-                BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
-                ViewButton { delta: -1 },
-            ))
-            .with_children(|p| {
-                p.spawn((
-                    Text::new("-"),
-                    TextFont {
-                        font_size: 24.0,
-                        ..Default::default()
-                    },
-                    TextColor::default(),
-                ));
-            });
-
-            row.spawn((
-                Button,
-                Node {
-                    padding: UiRect::axes(Val::Px(5.0), Val::Px(2.0)),
-                    margin: UiRect::left(Val::Px(5.0)),
-                    ..Default::default()
-                },
-                BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
-                ViewButton { delta: 1 },
-            ))
-            .with_children(|p| {
-                p.spawn((
-                    Text::new("+"),
-                    TextFont {
-                        font_size: 24.0,
-                        ..Default::default()
-                    },
-                    TextColor::default(),
-                ));
-            });
         });
 }
 
-fn spawn_noise_rows(parent: &mut ChildSpawnerCommands, settings: &NoiseSettings) {
-    for (i, layer) in settings.layers.iter().enumerate() {
-        // amplitude row
-        parent
-            .spawn((Node {
-                flex_direction: FlexDirection::Row,
-                align_items: AlignItems::Center,
-                margin: UiRect::all(Val::Px(5.0)),
-                ..Default::default()
-            },))
-            .with_children(|row| {
-                row.spawn((
-                    Text::new(format!("Layer {} Amp: {:.2}", i + 1, layer.amplitude)),
-                    TextFont {
-                        font_size: 24.0,
-                        ..Default::default()
+/// Spawns a focusable value label followed by its `-` and `+` buttons into `row`.
+fn spawn_stepper(
+    row: &mut ChildSpawnerCommands,
+    label: String,
+    text_marker: impl Bundle,
+    minus: impl Bundle,
+    plus: impl Bundle,
+) {
+    let mut stepper = row.spawn((
+        Node {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            padding: UiRect::all(Val::Px(2.0)),
+            ..Default::default()
+        },
+        BackgroundColor(Color::NONE),
+    ));
+    let mut buttons = (Entity::PLACEHOLDER, Entity::PLACEHOLDER);
+    stepper.with_children(|stepper| {
+        stepper.spawn((
+            Text::new(label),
+            TextFont {
+                font_size: 24.0,
+                ..Default::default()
+            },
+            TextColor::default(),
+            text_marker,
+        ));
+
+        buttons = (
+            spawn_step_button(stepper, "-", minus),
+            spawn_step_button(stepper, "+", plus),
+        );
+    });
+    stepper.insert(Focusable {
+        base: Color::NONE,
+        kind: FocusKind::Stepper {
+            minus: buttons.0,
+            plus: buttons.1,
+        },
+    });
+}
+
+fn spawn_step_button(row: &mut ChildSpawnerCommands, sign: &str, marker: impl Bundle) -> Entity {
+    row.spawn((
+        Button,
+        Node {
+            padding: UiRect::axes(Val::Px(5.0), Val::Px(2.0)),
+            margin: UiRect::left(Val::Px(5.0)),
+            ..Default::default()
+        },
+        BackgroundColor(BUTTON_COLOR),
+        StepButton,
+        marker,
+    ))
+    .with_children(|p| {
+        p.spawn((
+            Text::new(sign),
+            TextFont {
+                font_size: 24.0,
+                ..Default::default()
+            },
+            TextColor::default(),
+        ));
+    })
+    .id()
+}
+
+/// Spawns a row holding a single stepper.
+fn spawn_stepper_row(
+    parent: &mut ChildSpawnerCommands,
+    label: String,
+    text_marker: impl Bundle,
+    minus: impl Bundle,
+    plus: impl Bundle,
+) {
+    parent
+        .spawn((Node {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            margin: UiRect::all(Val::Px(5.0)),
+            ..Default::default()
+        },))
+        .with_children(|row| spawn_stepper(row, label, text_marker, minus, plus));
+}
+
+fn spawn_switch_row(
+    parent: &mut ChildSpawnerCommands,
+    field: SwitchField,
+    settings: &NoiseSettings,
+) {
+    spawn_stepper_row(
+        parent,
+        switch_label(field, settings),
+        SwitchText(field),
+        SwitchButton { field, delta: -1 },
+        SwitchButton { field, delta: 1 },
+    );
+}
+
+fn spawn_terrain_row(
+    parent: &mut ChildSpawnerCommands,
+    field: TerrainField,
+    settings: &NoiseSettings,
+) {
+    spawn_stepper_row(
+        parent,
+        terrain_label(field, settings),
+        TerrainText(field),
+        TerrainButton {
+            field,
+            delta: -field.step(),
+        },
+        TerrainButton {
+            field,
+            delta: field.step(),
+        },
+    );
+}
+
+/// Spawns the steppers scaling all layer amplitudes or frequencies at once.
+fn spawn_scale_row(parent: &mut ChildSpawnerCommands) {
+    parent
+        .spawn((Node {
+            flex_direction: FlexDirection::Row,
+            align_items: AlignItems::Center,
+            margin: UiRect::all(Val::Px(5.0)),
+            ..Default::default()
+        },))
+        .with_children(|row| {
+            for (label, field) in [
+                ("Scale All Amp", NoiseField::Amplitude),
+                ("Scale All Freq", NoiseField::Frequency),
+            ] {
+                spawn_stepper(
+                    row,
+                    label.to_string(),
+                    (),
+                    ScaleButton {
+                        field,
+                        factor: 1.0 / SCALE_STEP,
+                    },
+                    ScaleButton {
+                        field,
+                        factor: SCALE_STEP,
                     },
-                    TextColor::default(),
+                );
+                row.spawn(Node {
+                    width: Val::Px(15.0),
+                    ..Default::default()
+                });
+            }
+        });
+}
+
+fn spawn_noise_rows(parent: &mut ChildSpawnerCommands, settings: &NoiseSettings) {
+    for i in 0..settings.layers.len() {
+        parent
+            .spawn((Node {
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::Center,
+                margin: UiRect::all(Val::Px(5.0)),
+                ..Default::default()
+            },))
+            .with_children(|row| {
+                spawn_stepper(
+                    row,
+                    noise_label(i, NoiseField::Amplitude, settings),
                     NoiseText {
                         layer: i,
                         field: NoiseField::Amplitude,
                     },
-                ));
-
-                row.spawn((
-                    Button,
-                    Node {
-                        padding: UiRect::axes(Val::Px(5.0), Val::Px(2.0)),
-                        margin: UiRect::left(Val::Px(5.0)),
-                        ..Default::default()
-                    },
-                    BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
                     NoiseButton {
                         layer: i,
                         field: NoiseField::Amplitude,
                         delta: -1.0,
                     },
-                ))
-                .with_children(|p| {
-                    p.spawn((
-                        Text::new("-"),
-                        TextFont {
-                            font_size: 24.0,
-                            ..Default::default()
-                        },
-                        TextColor::default(),
-                    ));
-                });
-
-                row.spawn((
-                    Button,
-                    Node {
-                        padding: UiRect::axes(Val::Px(5.0), Val::Px(2.0)),
-                        margin: UiRect::left(Val::Px(5.0)),
-                        ..Default::default()
-                    },
-                    BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
                     NoiseButton {
                         layer: i,
                         field: NoiseField::Amplitude,
                         delta: 1.0,
                     },
-                ))
-                .with_children(|p| {
-                    p.spawn((
-                        Text::new("+"),
-                        TextFont {
-                            font_size: 24.0,
-                            ..Default::default()
-                        },
-                        TextColor::default(),
-                    ));
+                );
+                row.spawn(Node {
+                    width: Val::Px(15.0),
+                    ..Default::default()
                 });
-            });
-
-        // frequency row
-        parent
-            .spawn((Node {
-                flex_direction: FlexDirection::Row,
-                align_items: AlignItems::Center,
-                margin: UiRect::all(Val::Px(5.0)),
-                ..Default::default()
-            },))
-            .with_children(|row| {
-                row.spawn((
-                    Text::new(format!("Layer {} Freq: {:.2}", i + 1, layer.frequency)),
-                    TextFont {
-                        font_size: 24.0,
-                        ..Default::default()
-                    },
-                    TextColor::default(),
+                spawn_stepper(
+                    row,
+                    noise_label(i, NoiseField::Frequency, settings),
                     NoiseText {
                         layer: i,
                         field: NoiseField::Frequency,
                     },
-                ));
-
-                row.spawn((
-                    Button,
-                    Node {
-                        padding: UiRect::axes(Val::Px(5.0), Val::Px(2.0)),
-                        margin: UiRect::left(Val::Px(5.0)),
-                        ..Default::default()
-                    },
-                    BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
                     NoiseButton {
                         layer: i,
                         field: NoiseField::Frequency,
                         delta: -0.01,
                     },
-                ))
-                .with_children(|p| {
-                    p.spawn((
-                        Text::new("-"),
-                        TextFont {
-                            font_size: 24.0,
-                            ..Default::default()
-                        },
-                        TextColor::default(),
-                    ));
-                });
-
-                row.spawn((
-                    Button,
-                    Node {
-                        padding: UiRect::axes(Val::Px(5.0), Val::Px(2.0)),
-                        margin: UiRect::left(Val::Px(5.0)),
-                        ..Default::default()
-                    },
-                    BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
                     NoiseButton {
                         layer: i,
                         field: NoiseField::Frequency,
                         delta: 0.01,
                     },
-                ))
-                .with_children(|p| {
-                    p.spawn((
-                        Text::new("+"),
-                        TextFont {
-                            font_size: 24.0,
-                            ..Default::default()
-                        },
-                        TextColor::default(),
-                    ));
+                );
+                row.spawn(Node {
+                    width: Val::Px(15.0),
+                    ..Default::default()
                 });
+                spawn_stepper(
+                    row,
+                    noise_label(i, NoiseField::Octaves, settings),
+                    NoiseText {
+                        layer: i,
+                        field: NoiseField::Octaves,
+                    },
+                    NoiseButton {
+                        layer: i,
+                        field: NoiseField::Octaves,
+                        delta: -1.0,
+                    },
+                    NoiseButton {
+                        layer: i,
+                        field: NoiseField::Octaves,
+                        delta: 1.0,
+                    },
+                );
             });
     }
 }
 
+fn noise_label(layer: usize, field: NoiseField, settings: &NoiseSettings) -> String {
+    let values = &settings.layers[layer];
+    match field {
+        NoiseField::Amplitude => format!("Layer {} Amp: {:.2}", layer + 1, values.amplitude),
+        NoiseField::Frequency => format!("Freq: {:.3}", values.frequency),
+        NoiseField::Octaves => format!("Oct: {}", values.fractal.octaves),
+    }
+}
+
+/// Forwards mouse clicks on menu buttons as [`MenuPress`] events.
+#[allow(clippy::type_complexity)]
+pub fn mouse_presses(
+    interaction_q: Query<(Entity, &Interaction), (Changed<Interaction>, With<Button>)>,
+    mut presses: EventWriter<MenuPress>,
+) {
+    for (entity, interaction) in &interaction_q {
+        if *interaction == Interaction::Pressed {
+            presses.write(MenuPress(entity));
+        }
+    }
+}
+
+/// Presses a held `-` or `+` button again, after [`REPEAT_DELAY`] and then
+/// ever faster down to [`MIN_REPEAT_INTERVAL`].
+pub fn repeat_held_steps(
+    time: Res<Time>,
+    buttons: Query<(Entity, &Interaction), With<StepButton>>,
+    mut held: Local<HeldStep>,
+    mut presses: EventWriter<MenuPress>,
+) {
+    let pressed = buttons
+        .iter()
+        .find(|(_, interaction)| **interaction == Interaction::Pressed)
+        .map(|(entity, _)| entity);
+    if pressed != held.button {
+        // the first press is sent by `mouse_presses`
+        *held = HeldStep {
+            button: pressed,
+            held: 0.0,
+            next: REPEAT_DELAY,
+            interval: REPEAT_INTERVAL,
+        };
+        return;
+    }
+    let Some(button) = held.button else {
+        return;
+    };
+    held.held += time.delta_secs();
+    while held.held >= held.next {
+        presses.write(MenuPress(button));
+        held.next += held.interval;
+        held.interval = (held.interval * REPEAT_ACCELERATION).max(MIN_REPEAT_INTERVAL);
+    }
+}
+
+/// Nudges the stepper under the cursor with the mouse wheel, up pressing
+/// `+` and down pressing `-`.
+pub fn scroll_steppers(
+    mut wheel: EventReader<MouseWheel>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    controls: Query<(&Focusable, &ComputedNode, &GlobalTransform)>,
+    mut pixels: Local<f32>,
+    mut presses: EventWriter<MenuPress>,
+) {
+    let mut steps = 0;
+    for event in wheel.read() {
+        match event.unit {
+            MouseScrollUnit::Line => steps += event.y.signum() as i32,
+            MouseScrollUnit::Pixel => {
+                *pixels += event.y;
+                let whole = (*pixels / SCROLL_PIXELS_PER_STEP).trunc();
+                *pixels -= whole * SCROLL_PIXELS_PER_STEP;
+                steps += whole as i32;
+            }
+        }
+    }
+    if steps == 0 {
+        return;
+    }
+    let Some(cursor) = windows
+        .single()
+        .ok()
+        .and_then(|window| window.physical_cursor_position())
+    else {
+        return;
+    };
+    // UI layout is in physical pixels, centred on the node
+    let hovered = controls.iter().find_map(|(focusable, node, transform)| {
+        let FocusKind::Stepper { minus, plus } = focusable.kind else {
+            return None;
+        };
+        let half = node.size() / 2.0;
+        let offset = cursor - transform.translation().truncate();
+        (node.size() != Vec2::ZERO && offset.abs().cmple(half).all()).then_some((minus, plus))
+    });
+    if let Some((minus, plus)) = hovered {
+        let button = if steps > 0 { plus } else { minus };
+        for _ in 0..steps.unsigned_abs() {
+            presses.write(MenuPress(button));
+        }
+    }
+}
+
+/// Moves the focus with Up/Down/Tab and presses the focused control with
+/// Enter (buttons) or Left/Right (steppers).
+pub fn keyboard_navigation(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut focus: ResMut<MenuFocus>,
+    controls: Query<(Entity, &Focusable, &ComputedNode, &GlobalTransform)>,
+    mut presses: EventWriter<MenuPress>,
+) {
+    let order = focus_order(&controls);
+    if order.is_empty() {
+        return;
+    }
+    let index = focus.0.min(order.len() - 1);
+    let (entity, focusable) = order[index];
+
+    let shift = keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    let mut step = 0;
+    if keys.just_pressed(KeyCode::ArrowDown) || (keys.just_pressed(KeyCode::Tab) && !shift) {
+        step = 1;
+    }
+    if keys.just_pressed(KeyCode::ArrowUp) || (keys.just_pressed(KeyCode::Tab) && shift) {
+        step = -1;
+    }
+
+    match focusable.kind {
+        FocusKind::Button => {
+            if keys.just_pressed(KeyCode::Enter) {
+                presses.write(MenuPress(entity));
+            }
+            if keys.just_pressed(KeyCode::ArrowLeft) {
+                step = -1;
+            }
+            if keys.just_pressed(KeyCode::ArrowRight) {
+                step = 1;
+            }
+        }
+        FocusKind::Stepper { minus, plus } => {
+            if keys.just_pressed(KeyCode::ArrowLeft) {
+                presses.write(MenuPress(minus));
+            }
+            if keys.just_pressed(KeyCode::ArrowRight) {
+                presses.write(MenuPress(plus));
+            }
+        }
+        // Left and Right move the text cursor instead
+        FocusKind::TextField => {
+            if keys.just_pressed(KeyCode::Enter) {
+                presses.write(MenuPress(entity));
+            }
+        }
+    }
+
+    let next = (index as i32 + step).rem_euclid(order.len() as i32) as usize;
+    if next != focus.0 {
+        focus.0 = next;
+    }
+}
+
+/// Highlights the focused control and restores the base color of the others.
+pub fn update_focus_highlight(
+    focus: Res<MenuFocus>,
+    controls: Query<(Entity, &Focusable, &ComputedNode, &GlobalTransform)>,
+    mut colors: Query<&mut BackgroundColor>,
+) {
+    let focused = focused_control(&focus, &controls);
+    for (entity, focusable, _, _) in &controls {
+        let color = if focused == Some(entity) {
+            FOCUS_COLOR
+        } else {
+            focusable.base
+        };
+        if let Ok(mut background) = colors.get_mut(entity)
+            && background.0 != color
+        {
+            background.0 = color;
+        }
+    }
+}
+
+/// Visible controls sorted top to bottom, then left to right.
+///
+/// Controls on hidden pages have no layout size and are skipped.
+fn focus_order<'a>(
+    controls: &'a Query<(Entity, &Focusable, &ComputedNode, &GlobalTransform)>,
+) -> Vec<(Entity, &'a Focusable)> {
+    let mut visible: Vec<_> = controls
+        .iter()
+        .filter(|(_, _, node, _)| node.size() != Vec2::ZERO)
+        .map(|(entity, focusable, _, transform)| (entity, focusable, transform.translation()))
+        .collect();
+    visible.sort_by(|a, b| a.2.y.total_cmp(&b.2.y).then(a.2.x.total_cmp(&b.2.x)));
+    visible
+        .into_iter()
+        .map(|(entity, focusable, _)| (entity, focusable))
+        .collect()
+}
+
+/// The control holding the keyboard focus, if any is visible.
+fn focused_control(
+    focus: &MenuFocus,
+    controls: &Query<(Entity, &Focusable, &ComputedNode, &GlobalTransform)>,
+) -> Option<Entity> {
+    let order = focus_order(controls);
+    order
+        .get(focus.0.min(order.len().saturating_sub(1)))
+        .map(|(entity, _)| *entity)
+}
+
+fn world_name_label(name: &WorldName) -> String {
+    format!("Name: {}_", name.0)
+}
+
+fn render_label(params: &WorldParams) -> String {
+    format!("Render Width: {}", params.render_radius())
+}
+
+fn orbit_label(params: &WorldParams) -> String {
+    format!("Camera: {}", if params.orbit { "Orbit" } else { "Free" })
+}
+
+/// Current seed and the key copying it, shared with the pause screen.
+pub fn seed_label(settings: &NoiseSettings, bindings: &KeyBindings) -> String {
+    format!(
+        "Seed: {} ({} to copy)",
+        settings.seed,
+        key_label(bindings.key(Action::CopySeed))
+    )
+}
+
+fn seed_input_label(input: &SeedInput) -> String {
+    let (before, after) = input.text.split_at(input.cursor);
+    format!("Enter Seed: {before}|{after}")
+}
+
+fn terrain_label(field: TerrainField, settings: &NoiseSettings) -> String {
+    match field {
+        TerrainField::BaseHeight => format!("Base Height: {}", settings.base_height),
+        TerrainField::RidgeHeight => format!("Ridge Height: {:.0}", settings.ridge_height),
+        TerrainField::TreeDensity => format!("Tree Density: {:.1}", settings.tree_density),
+        TerrainField::BoulderDensity => {
+            format!("Boulder Density: {:.1}", settings.boulder_density)
+        }
+    }
+}
+
+fn switch_label(field: SwitchField, settings: &NoiseSettings) -> String {
+    let on = field.get(settings);
+    let name = match field {
+        SwitchField::Superflat => "Superflat",
+        SwitchField::NormalizeHeight => "Normalize Height",
+        SwitchField::Overhangs => "Overhangs",
+        SwitchField::ScaledRidges => {
+            return format!("Ridges: {}", if on { "Scaled" } else { "Added" });
+        }
+        SwitchField::Caves => "Caves",
+        SwitchField::Bedrock => "Bedrock",
+        SwitchField::Trees => "Trees",
+        SwitchField::Boulders => "Boulders",
+    };
+    format!("{name}: {}", if on { "On" } else { "Off" })
+}
+
+fn quality_label(display: &DisplaySettings) -> String {
+    format!(
+        "Quality: {}",
+        display.quality.map_or("Custom", QualityPreset::label)
+    )
+}
+
+fn display_label(field: DisplayField, display: &DisplaySettings) -> String {
+    match field {
+        DisplayField::VSync => {
+            format!("VSync: {}", if display.vsync { "On" } else { "Off" })
+        }
+        DisplayField::FpsCap => match display.fps_cap {
+            Some(cap) => format!("FPS Cap: {cap}"),
+            None => "FPS Cap: Off".to_string(),
+        },
+        DisplayField::Msaa => match display.msaa {
+            1 => "MSAA: Off".to_string(),
+            samples => format!("MSAA: {samples}x"),
+        },
+        DisplayField::Shadows => {
+            format!("Shadows: {}", if display.shadows { "On" } else { "Off" })
+        }
+        DisplayField::Brightness => {
+            format!("Brightness: {:.0}%", display.brightness * 100.0)
+        }
+        DisplayField::SkyColor => {
+            let name = SKY_COLORS
+                .iter()
+                .find(|(_, color)| *color == display.sky_color)
+                .map_or("Custom", |(name, _)| name);
+            format!("Sky: {name}")
+        }
+        DisplayField::SunAzimuth => format!("Sun Bearing: {:.0}°", display.sun_azimuth),
+        DisplayField::SunElevation => format!("Sun Height: {:.0}°", display.sun_elevation),
+        DisplayField::SunIlluminance => {
+            format!("Sunlight: {:.0} lux", display.sun_illuminance)
+        }
+    }
+}
+
+/// Switches the visible settings page when a tab is pressed.
+pub fn page_actions(
+    mut presses: EventReader<MenuPress>,
+    tabs: Query<&PageButton>,
+    mut active: ResMut<ActivePage>,
+) {
+    for press in presses.read() {
+        if let Ok(button) = tabs.get(press.0) {
+            active.0 = button.0;
+        }
+    }
+}
+
+/// Shows the active page and highlights its tab.
+pub fn update_pages(
+    active: Res<ActivePage>,
+    mut pages: Query<(&MenuPage, &mut Node)>,
+    mut tabs: Query<(&PageButton, &mut Focusable)>,
+) {
+    if !active.is_changed() {
+        return;
+    }
+    for (page, mut node) in &mut pages {
+        node.display = if *page == active.0 {
+            Display::Flex
+        } else {
+            Display::None
+        };
+    }
+    for (tab, mut focusable) in &mut tabs {
+        focusable.base = if tab.0 == active.0 {
+            ACTIVE_TAB_COLOR
+        } else {
+            BUTTON_COLOR
+        };
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn menu_actions(
-    mut interaction_q: Query<
-        (
-            &Interaction,
-            Option<&ViewButton>,
-            Option<&StartButton>,
-            Option<&ExitButton>,
-        ),
-        Changed<Interaction>,
-    >,
+    mut presses: EventReader<MenuPress>,
+    buttons_q: Query<(
+        Option<&ViewButton>,
+        Option<&RenderButton>,
+        Option<&HeightButton>,
+        Option<&OrbitButton>,
+        Option<&StartButton>,
+        Option<&ExitButton>,
+    )>,
     mut params: ResMut<WorldParams>,
+    mut display: ResMut<DisplaySettings>,
+    mut world: ResMut<ActiveWorld>,
     mut next_state: ResMut<NextState<AppState>>,
     mut exit: EventWriter<AppExit>,
+    settings: Res<NoiseSettings>,
+    saved: Res<SavedNoise>,
+    mut dialogs: Query<&mut Node, With<ExitDialog>>,
 ) {
-    for (interaction, view_button, start, exit_button) in &mut interaction_q {
-        if *interaction != Interaction::Pressed {
+    for press in presses.read() {
+        let Ok((view_button, render_button, height_button, orbit_button, start, exit_button)) =
+            buttons_q.get(press.0)
+        else {
             continue;
-        }
+        };
 
         if let Some(view_button) = view_button {
             params.view_width = (params.view_width + view_button.delta).max(1);
+            display.quality = None;
+        }
+
+        if let Some(render_button) = render_button {
+            // starts from the effective value so stepping down responds at once
+            params.render_width =
+                (params.render_radius() + render_button.delta).clamp(1, params.view_width);
+            display.quality = None;
+        }
+
+        if let Some(height_button) = height_button {
+            params.height_chunks =
+                (params.height_chunks + height_button.delta).clamp(1, MAX_HEIGHT_CHUNKS);
+        }
+
+        if let Some(orbit_button) = orbit_button {
+            params.orbit = orbit_button.delta > 0;
         }
 
         if start.is_some() {
+            // a plain game is not saved into the last loaded world
+            *world = ActiveWorld::default();
             next_state.set(AppState::Playing);
         }
 
         if exit_button.is_some() {
-            exit.write(AppExit::Success);
+            if saved.differs(&settings) {
+                for mut dialog in &mut dialogs {
+                    dialog.display = Display::Flex;
+                }
+            } else {
+                exit.write(AppExit::Success);
+            }
         }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn noise_actions(
-    mut interaction_q: Query<(&Interaction, &NoiseButton), Changed<Interaction>>,
+    mut presses: EventReader<MenuPress>,
+    buttons: Query<&NoiseButton>,
+    randomize_buttons: Query<(), With<RandomizeButton>>,
+    switch_buttons: Query<&SwitchButton>,
+    terrain_buttons: Query<&TerrainButton>,
+    scale_buttons: Query<&ScaleButton>,
+    params: Res<WorldParams>,
     mut settings: ResMut<NoiseSettings>,
 ) {
-    for (interaction, button) in &mut interaction_q {
-        if *interaction != Interaction::Pressed {
-            continue;
+    for press in presses.read() {
+        if randomize_buttons.contains(press.0) {
+            settings.seed = rand::random();
+        }
+        if let Ok(button) = switch_buttons.get(press.0) {
+            button.field.set(&mut settings, button.delta > 0);
+        }
+        if let Ok(button) = terrain_buttons.get(press.0) {
+            button
+                .field
+                .apply(&mut settings, button.delta, params.max_height());
         }
+        if let Ok(button) = scale_buttons.get(press.0) {
+            match button.field {
+                NoiseField::Amplitude => settings.scale_amplitudes(button.factor),
+                NoiseField::Frequency => settings.scale_frequencies(button.factor),
+                NoiseField::Octaves => {}
+            }
+        }
+        let Ok(button) = buttons.get(press.0) else {
+            continue;
+        };
         let layer = &mut settings.layers[button.layer];
         match button.field {
             NoiseField::Amplitude => {
@@ -382,30 +1545,552 @@ pub fn noise_actions(
             NoiseField::Frequency => {
                 layer.frequency = (layer.frequency + button.delta).max(0.0);
             }
+            NoiseField::Octaves => {
+                layer.fractal.octaves =
+                    (layer.fractal.octaves + button.delta as i32).clamp(1, MAX_OCTAVES);
+            }
+        }
+    }
+}
+
+/// Toggles VSync and shadows, steps through [`FPS_CAPS`], [`MSAA_SAMPLES`],
+/// [`BRIGHTNESS_LEVELS`] and [`SKY_COLORS`] and applies quality presets.
+pub fn display_actions(
+    mut presses: EventReader<MenuPress>,
+    buttons: Query<&DisplayButton>,
+    presets: Query<&QualityButton>,
+    mut display: ResMut<DisplaySettings>,
+    mut params: ResMut<WorldParams>,
+) {
+    for press in presses.read() {
+        if let Ok(QualityButton(preset)) = presets.get(press.0) {
+            preset.apply(&mut display);
+            params.apply_quality(*preset);
+        }
+        let Ok(button) = buttons.get(press.0) else {
+            continue;
+        };
+        match button.field {
+            DisplayField::VSync => display.vsync = button.delta > 0,
+            DisplayField::Shadows => {
+                display.shadows = button.delta > 0;
+                display.quality = None;
+            }
+            DisplayField::FpsCap => {
+                let current = FPS_CAPS
+                    .iter()
+                    .position(|cap| *cap == display.fps_cap)
+                    .unwrap_or(0) as i32;
+                let next = (current + button.delta).clamp(0, FPS_CAPS.len() as i32 - 1);
+                display.fps_cap = FPS_CAPS[next as usize];
+            }
+            DisplayField::Msaa => {
+                let current = MSAA_SAMPLES
+                    .iter()
+                    .position(|samples| *samples == display.msaa)
+                    .unwrap_or(0) as i32;
+                let next = (current + button.delta).clamp(0, MSAA_SAMPLES.len() as i32 - 1);
+                display.msaa = MSAA_SAMPLES[next as usize];
+                display.quality = None;
+            }
+            DisplayField::Brightness => {
+                let current = BRIGHTNESS_LEVELS
+                    .iter()
+                    .position(|level| *level == display.brightness)
+                    .unwrap_or(2) as i32;
+                let next = (current + button.delta).clamp(0, BRIGHTNESS_LEVELS.len() as i32 - 1);
+                display.brightness = BRIGHTNESS_LEVELS[next as usize];
+            }
+            DisplayField::SkyColor => {
+                let current = SKY_COLORS
+                    .iter()
+                    .position(|(_, color)| *color == display.sky_color)
+                    .unwrap_or(0) as i32;
+                let next = (current + button.delta).clamp(0, SKY_COLORS.len() as i32 - 1);
+                display.sky_color = SKY_COLORS[next as usize].1;
+            }
+            DisplayField::SunAzimuth => {
+                let turned = display.sun_azimuth + button.delta as f32 * SUN_ANGLE_STEP;
+                display.sun_azimuth = turned.rem_euclid(360.0);
+            }
+            DisplayField::SunElevation => {
+                let tilted = display.sun_elevation + button.delta as f32 * SUN_ANGLE_STEP;
+                display.sun_elevation =
+                    tilted.clamp(*SUN_ELEVATIONS.start(), *SUN_ELEVATIONS.end());
+            }
+            DisplayField::SunIlluminance => {
+                let current = SUN_ILLUMINANCES
+                    .iter()
+                    .position(|level| *level == display.sun_illuminance)
+                    .unwrap_or(2) as i32;
+                let next = (current + button.delta).clamp(0, SUN_ILLUMINANCES.len() as i32 - 1);
+                display.sun_illuminance = SUN_ILLUMINANCES[next as usize];
+            }
         }
     }
 }
 
+/// Steps the simulation rate through [`TICK_RATE_STEPS`].
+pub fn tick_rate_actions(
+    mut presses: EventReader<MenuPress>,
+    buttons: Query<&TickRateButton>,
+    mut physics: ResMut<PhysicsSettings>,
+) {
+    for press in presses.read() {
+        if let Ok(TickRateButton(delta)) = buttons.get(press.0) {
+            physics.tick_rate = step_tick_rate(physics.tick_rate, *delta);
+        }
+    }
+}
+
+/// Next of [`TICK_RATE_STEPS`] from `rate` in the direction of `delta`; a
+/// rate set in settings.json between two steps moves to the step beside it.
+fn step_tick_rate(rate: f32, delta: i32) -> f32 {
+    let current = TICK_RATE_STEPS
+        .iter()
+        .position(|step| *step >= rate)
+        .unwrap_or(TICK_RATE_STEPS.len() - 1) as i32;
+    let current = if TICK_RATE_STEPS[current as usize] != rate && delta > 0 {
+        current - 1
+    } else {
+        current
+    };
+    let next = (current + delta).clamp(0, TICK_RATE_STEPS.len() as i32 - 1);
+    TICK_RATE_STEPS[next as usize]
+}
+
+fn tick_rate_label(physics: &PhysicsSettings) -> String {
+    format!("Tick Rate: {:.0} Hz", physics.tick_rate)
+}
+
+pub fn update_tick_rate_text(
+    physics: Res<PhysicsSettings>,
+    mut q: Query<&mut Text, With<TickRateText>>,
+) {
+    if !physics.is_changed() {
+        return;
+    }
+    for mut text in &mut q {
+        *text = Text::new(tick_rate_label(&physics));
+    }
+}
+
+pub fn update_display_text(
+    display: Res<DisplaySettings>,
+    mut q: Query<(&mut Text, &DisplayText)>,
+    mut quality: Query<&mut Text, (With<QualityText>, Without<DisplayText>)>,
+) {
+    if !display.is_changed() {
+        return;
+    }
+    for (mut text, field) in &mut q {
+        *text = Text::new(display_label(field.0, &display));
+    }
+    for mut text in &mut quality {
+        *text = Text::new(quality_label(&display));
+    }
+}
+
 pub fn update_noise_text(settings: Res<NoiseSettings>, mut q: Query<(&mut Text, &NoiseText)>) {
     if !settings.is_changed() {
         return;
     }
     for (mut text, info) in &mut q {
-        let layer = &settings.layers[info.layer];
-        *text = Text::new(match info.field {
-            NoiseField::Amplitude => {
-                format!("Layer {} Amp: {:.2}", info.layer + 1, layer.amplitude)
+        *text = Text::new(noise_label(info.layer, info.field, &settings));
+    }
+}
+
+pub fn update_switch_text(settings: Res<NoiseSettings>, mut q: Query<(&mut Text, &SwitchText)>) {
+    if !settings.is_changed() {
+        return;
+    }
+    for (mut text, field) in &mut q {
+        *text = Text::new(switch_label(field.0, &settings));
+    }
+}
+
+pub fn update_terrain_text(settings: Res<NoiseSettings>, mut q: Query<(&mut Text, &TerrainText)>) {
+    if !settings.is_changed() {
+        return;
+    }
+    for (mut text, field) in &mut q {
+        *text = Text::new(terrain_label(field.0, &settings));
+    }
+}
+
+/// Regenerates the terrain preview whenever the noise settings or world height change.
+pub fn update_preview(
+    settings: Res<NoiseSettings>,
+    params: Res<WorldParams>,
+    mut images: ResMut<Assets<Image>>,
+    q: Query<&ImageNode, With<PreviewImage>>,
+) {
+    if !settings.is_changed() && !params.is_changed() {
+        return;
+    }
+    for node in &q {
+        images.insert(
+            &node.image,
+            heightmap_preview(&settings, params.max_height()),
+        );
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn save_settings_on_l(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    settings: Res<NoiseSettings>,
+    display: Res<DisplaySettings>,
+    physics: Res<PhysicsSettings>,
+    look: Res<LookSettings>,
+    mut saved: ResMut<SavedNoise>,
+    page: Res<ActivePage>,
+    mut q: Query<(&mut Text, &mut StatusMessage)>,
+) {
+    // the key may be part of a world name being typed
+    if !keys.just_pressed(bindings.key(Action::SaveSettings)) || page.0 == MenuPage::Worlds {
+        return;
+    }
+    let path = settings_path().display();
+    let message = match save_settings(&settings, &display, &physics, &look, &bindings) {
+        Ok(()) => {
+            info!("settings saved to {path}");
+            *saved = SavedNoise::new(&settings);
+            format!("Settings saved to {path}")
+        }
+        Err(err) => {
+            warn!("could not save settings to {path}: {err}");
+            format!("Save failed: {err}")
+        }
+    };
+    show_status(&mut q, message);
+}
+
+/// Copies the world seed when `C` (by default) is pressed.
+pub fn copy_seed_on_c(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    settings: Res<NoiseSettings>,
+    page: Res<ActivePage>,
+    mut q: Query<(&mut Text, &mut StatusMessage)>,
+) {
+    // the key may be part of a world name being typed
+    if !keys.just_pressed(bindings.key(Action::CopySeed)) || page.0 == MenuPage::Worlds {
+        return;
+    }
+    show_status(&mut q, copy_seed(settings.seed));
+}
+
+pub fn update_seed_text(
+    settings: Res<NoiseSettings>,
+    bindings: Res<KeyBindings>,
+    mut q: Query<&mut Text, With<SeedText>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+    for mut text in &mut q {
+        *text = Text::new(seed_label(&settings, &bindings));
+    }
+}
+
+/// Saves and exits, exits without saving or closes the [`ExitDialog`].
+///
+/// A failed save keeps the app open with the error on the status line.
+#[allow(clippy::too_many_arguments)]
+pub fn exit_dialog_actions(
+    mut presses: EventReader<MenuPress>,
+    choices: Query<&ExitChoice>,
+    settings: Res<NoiseSettings>,
+    display: Res<DisplaySettings>,
+    physics: Res<PhysicsSettings>,
+    look: Res<LookSettings>,
+    bindings: Res<KeyBindings>,
+    mut saved: ResMut<SavedNoise>,
+    mut dialogs: Query<&mut Node, With<ExitDialog>>,
+    mut status: Query<(&mut Text, &mut StatusMessage)>,
+    mut exit: EventWriter<AppExit>,
+) {
+    for press in presses.read() {
+        let Ok(choice) = choices.get(press.0) else {
+            continue;
+        };
+        match choice {
+            ExitChoice::Save => {
+                let path = settings_path().display();
+                match save_settings(&settings, &display, &physics, &look, &bindings) {
+                    Ok(()) => {
+                        info!("settings saved to {path}");
+                        *saved = SavedNoise::new(&settings);
+                        exit.write(AppExit::Success);
+                    }
+                    Err(err) => {
+                        warn!("could not save settings to {path}: {err}");
+                        show_status(&mut status, format!("Save failed: {err}"));
+                    }
+                }
             }
-            NoiseField::Frequency => {
-                format!("Layer {} Freq: {:.2}", info.layer + 1, layer.frequency)
+            ExitChoice::Discard => {
+                exit.write(AppExit::Success);
             }
-        });
+            ExitChoice::Cancel => {}
+        }
+        for mut dialog in &mut dialogs {
+            dialog.display = Display::None;
+        }
     }
 }
 
-pub fn save_settings_on_l(keys: Res<ButtonInput<KeyCode>>, settings: Res<NoiseSettings>) {
-    if keys.just_pressed(KeyCode::KeyL) {
-        settings.save();
+/// Exits when the window is closed on the menu without unsaved noise
+/// settings.
+///
+/// Otherwise it returns to the menu first, so leaving a game saves the
+/// active world, and [`open_requested_exit_dialog`] exits or asks there.
+///
+/// Needs `WindowPlugin::close_when_requested` off, which would otherwise
+/// close the window before the question is asked.
+pub fn close_requested(
+    mut closes: EventReader<WindowCloseRequested>,
+    settings: Res<NoiseSettings>,
+    saved: Res<SavedNoise>,
+    mut requested: ResMut<ExitRequested>,
+    state: Res<State<AppState>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if closes.read().count() == 0 {
+        return;
+    }
+    if *state.get() != AppState::Menu {
+        requested.0 = true;
+        next_state.set(AppState::Menu);
+    } else if saved.differs(&settings) {
+        requested.0 = true;
+    } else {
+        exit.write(AppExit::Success);
+    }
+}
+
+/// Finishes a close asked for by [`close_requested`] once on the menu: exits,
+/// or opens the [`ExitDialog`] while the noise settings have unsaved changes.
+pub fn open_requested_exit_dialog(
+    mut requested: ResMut<ExitRequested>,
+    settings: Res<NoiseSettings>,
+    saved: Res<SavedNoise>,
+    mut exit: EventWriter<AppExit>,
+    mut dialogs: Query<&mut Node, With<ExitDialog>>,
+) {
+    if !requested.0 {
+        return;
+    }
+    requested.0 = false;
+    if !saved.differs(&settings) {
+        exit.write(AppExit::Success);
+        return;
+    }
+    for mut dialog in &mut dialogs {
+        dialog.display = Display::Flex;
+    }
+}
+
+/// Shows `message` in the status line for [`STATUS_MESSAGE_SECS`].
+fn show_status(q: &mut Query<(&mut Text, &mut StatusMessage)>, message: String) {
+    for (mut text, mut status) in q {
+        *text = Text::new(message.clone());
+        status.timer.reset();
+    }
+}
+
+/// Edits the new world name while the Worlds page is shown.
+pub fn world_name_input(
+    mut keys: EventReader<KeyboardInput>,
+    page: Res<ActivePage>,
+    mut name: ResMut<WorldName>,
+) {
+    for ev in keys.read() {
+        if page.0 != MenuPage::Worlds || ev.state != ButtonState::Pressed {
+            continue;
+        }
+        match &ev.logical_key {
+            Key::Backspace => {
+                name.0.pop();
+            }
+            Key::Space if name.0.len() < MAX_NAME_LEN => name.0.push(' '),
+            Key::Character(text) => {
+                for c in text.chars() {
+                    let mut typed = name.0.clone();
+                    typed.push(c);
+                    if valid_name(&typed) {
+                        name.0 = typed;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Focuses the seed field when clicked and edits it while it holds the
+/// keyboard focus.
+#[allow(clippy::type_complexity)]
+pub fn seed_input(
+    mut keys: EventReader<KeyboardInput>,
+    mut focus: ResMut<MenuFocus>,
+    controls: Query<(Entity, &Focusable, &ComputedNode, &GlobalTransform)>,
+    fields: Query<(), With<SeedField>>,
+    clicked: Query<(Entity, &Interaction), (Changed<Interaction>, With<SeedField>)>,
+    mut input: ResMut<SeedInput>,
+) {
+    for (entity, interaction) in &clicked {
+        if *interaction == Interaction::Pressed
+            && let Some(index) = focus_order(&controls)
+                .iter()
+                .position(|(e, _)| *e == entity)
+        {
+            focus.0 = index;
+        }
+    }
+    let focused = focused_control(&focus, &controls).is_some_and(|e| fields.contains(e));
+    for ev in keys.read() {
+        if !focused || ev.state != ButtonState::Pressed {
+            continue;
+        }
+        input.edit(&ev.logical_key);
+    }
+}
+
+/// Applies the typed seed when Enter is pressed on the seed field.
+pub fn seed_field_actions(
+    mut presses: EventReader<MenuPress>,
+    fields: Query<(), With<SeedField>>,
+    mut input: ResMut<SeedInput>,
+    mut settings: ResMut<NoiseSettings>,
+    mut status: Query<(&mut Text, &mut StatusMessage)>,
+) {
+    for press in presses.read() {
+        if !fields.contains(press.0) {
+            continue;
+        }
+        match input.text.parse::<i32>() {
+            Ok(seed) => {
+                settings.seed = seed;
+                *input = SeedInput::default();
+            }
+            Err(_) if input.text.is_empty() => {}
+            Err(_) => show_status(
+                &mut status,
+                format!("Seeds range from {} to {}", i32::MIN, i32::MAX),
+            ),
+        }
+    }
+}
+
+pub fn update_seed_input_text(input: Res<SeedInput>, mut q: Query<&mut Text, With<SeedInputText>>) {
+    if !input.is_changed() {
+        return;
+    }
+    for mut text in &mut q {
+        *text = Text::new(seed_input_label(&input));
+    }
+}
+
+pub fn update_world_name_text(name: Res<WorldName>, mut q: Query<&mut Text, With<WorldNameText>>) {
+    if !name.is_changed() {
+        return;
+    }
+    for mut text in &mut q {
+        *text = Text::new(world_name_label(&name));
+    }
+}
+
+/// Creates, loads and deletes named worlds from the Worlds page.
+#[allow(clippy::too_many_arguments)]
+pub fn world_actions(
+    mut commands: Commands,
+    mut presses: EventReader<MenuPress>,
+    create: Query<(), With<CreateWorldButton>>,
+    load: Query<&LoadWorldButton>,
+    delete: Query<&DeleteWorldButton>,
+    name: Res<WorldName>,
+    mut settings: ResMut<NoiseSettings>,
+    mut saved: ResMut<SavedNoise>,
+    mut params: ResMut<WorldParams>,
+    mut spawn: ResMut<SpawnPoint>,
+    mut world: ResMut<ActiveWorld>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut status: Query<(&mut Text, &mut StatusMessage)>,
+) {
+    let dir = worlds_dir();
+    for press in presses.read() {
+        if create.contains(press.0) {
+            let name = name.0.trim().to_string();
+            if !valid_name(&name) {
+                show_status(&mut status, "Type a name for the new world".into());
+            } else if list_worlds(&dir).contains(&name) {
+                show_status(&mut status, format!("World `{name}` already exists"));
+            } else {
+                let manifest = WorldManifest::capture(&settings, &params, &spawn, None);
+                match save_world(&dir, &name, &manifest) {
+                    Ok(()) => {
+                        // as on load, the world file holds the noise settings now
+                        *saved = SavedNoise::new(&settings);
+                        *world = ActiveWorld {
+                            name: Some(name),
+                            resume: None,
+                        };
+                        next_state.set(AppState::Playing);
+                    }
+                    Err(err) => {
+                        warn!("could not create world `{name}`: {err}");
+                        show_status(&mut status, format!("Create failed: {err}"));
+                    }
+                }
+            }
+        }
+
+        if let Ok(LoadWorldButton(name)) = load.get(press.0) {
+            match load_world(&dir, name) {
+                Ok(manifest) => {
+                    manifest.apply_params(&mut params);
+                    *settings = manifest.noise;
+                    // the world file holds these now, so only later changes are unsaved
+                    *saved = SavedNoise::new(&settings);
+                    spawn.custom = manifest.spawn.map(Vec3::from_array);
+                    *world = ActiveWorld {
+                        name: Some(name.clone()),
+                        resume: manifest.player,
+                    };
+                    next_state.set(AppState::Playing);
+                }
+                Err(err) => {
+                    warn!("could not load world `{name}`: {err}");
+                    show_status(&mut status, format!("Load failed: {err}"));
+                }
+            }
+        }
+
+        if let Ok(button) = delete.get(press.0) {
+            match delete_world(&dir, &button.name) {
+                Ok(()) => {
+                    commands.entity(button.row).despawn();
+                    show_status(&mut status, format!("Deleted `{}`", button.name));
+                }
+                Err(err) => {
+                    warn!("could not delete world `{}`: {err}", button.name);
+                    show_status(&mut status, format!("Delete failed: {err}"));
+                }
+            }
+        }
+    }
+}
+
+/// Clears the status message once it has been shown long enough.
+pub fn update_status_message(time: Res<Time>, mut q: Query<(&mut Text, &mut StatusMessage)>) {
+    for (mut text, mut status) in &mut q {
+        if status.timer.tick(time.delta()).just_finished() {
+            text.0.clear();
+        }
     }
 }
 
@@ -418,6 +2103,33 @@ pub fn update_view_text(params: Res<WorldParams>, mut q: Query<&mut Text, With<V
     }
 }
 
+pub fn update_render_text(params: Res<WorldParams>, mut q: Query<&mut Text, With<RenderText>>) {
+    if !params.is_changed() {
+        return;
+    }
+    for mut text in &mut q {
+        *text = Text::new(render_label(&params));
+    }
+}
+
+pub fn update_orbit_text(params: Res<WorldParams>, mut q: Query<&mut Text, With<OrbitText>>) {
+    if !params.is_changed() {
+        return;
+    }
+    for mut text in &mut q {
+        *text = Text::new(orbit_label(&params));
+    }
+}
+
+pub fn update_height_text(params: Res<WorldParams>, mut q: Query<&mut Text, With<HeightText>>) {
+    if !params.is_changed() {
+        return;
+    }
+    for mut text in &mut q {
+        *text = Text::new(format!("World Height: {} chunks", params.height_chunks));
+    }
+}
+
 pub fn menu_cleanup(
     mut commands: Commands,
     roots: Query<Entity, With<MenuRoot>>,
@@ -430,3 +2142,133 @@ pub fn menu_cleanup(
         commands.entity(e).despawn();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(keys: &[Key]) -> SeedInput {
+        let mut input = SeedInput::default();
+        for key in keys {
+            input.edit(key);
+        }
+        input
+    }
+
+    fn chars(text: &str) -> Key {
+        Key::Character(text.into())
+    }
+
+    #[test]
+    fn seed_input_keeps_to_digits_and_a_leading_minus() {
+        let input = typed(&[chars("1a2-"), Key::Home, chars("-"), chars("-")]);
+        assert_eq!((input.text.as_str(), input.cursor), ("-12", 1));
+
+        // nothing goes in front of the minus
+        let input = typed(&[chars("-5"), Key::Home, chars("7")]);
+        assert_eq!(input.text, "-5");
+
+        let input = typed(&[chars("123456789012345")]);
+        assert_eq!(input.text.len(), MAX_SEED_LEN);
+    }
+
+    #[test]
+    fn seed_input_edits_at_the_cursor() {
+        let mut input = typed(&[chars("1234"), Key::ArrowLeft, Key::ArrowLeft]);
+        assert_eq!(input.cursor, 2);
+        input.edit(&Key::Backspace);
+        assert_eq!((input.text.as_str(), input.cursor), ("134", 1));
+        input.edit(&Key::Delete);
+        assert_eq!((input.text.as_str(), input.cursor), ("14", 1));
+        input.edit(&chars("9"));
+        assert_eq!((input.text.as_str(), input.cursor), ("194", 2));
+
+        // the cursor stops at both ends, where the edits do nothing
+        input.edit(&Key::End);
+        input.edit(&Key::ArrowRight);
+        input.edit(&Key::Delete);
+        assert_eq!((input.text.as_str(), input.cursor), ("194", 3));
+        input.edit(&Key::Home);
+        input.edit(&Key::ArrowLeft);
+        input.edit(&Key::Backspace);
+        assert_eq!((input.text.as_str(), input.cursor), ("194", 0));
+    }
+
+    #[test]
+    fn seed_field_rejects_seeds_out_of_range() {
+        let mut world = World::new();
+        world.init_resource::<Events<MenuPress>>();
+        let mut settings = NoiseSettings::fallback();
+        settings.seed = 7;
+        world.insert_resource(settings);
+        let field = world.spawn(SeedField).id();
+        let status = world
+            .spawn((
+                Text::default(),
+                StatusMessage {
+                    timer: Timer::from_seconds(1.0, TimerMode::Once),
+                },
+            ))
+            .id();
+        let enter = |world: &mut World, text: &str| {
+            world.insert_resource(typed(&[chars(text)]));
+            world.send_event(MenuPress(field));
+            world.run_system_cached(seed_field_actions).unwrap();
+        };
+
+        enter(&mut world, "99999999999");
+        assert_eq!(world.resource::<NoiseSettings>().seed, 7);
+        assert_eq!(world.resource::<SeedInput>().text, "99999999999");
+        let message = &world.get::<Text>(status).unwrap().0;
+        assert!(message.starts_with("Seeds range from"), "{message}");
+
+        enter(&mut world, "-2147483648");
+        assert_eq!(world.resource::<NoiseSettings>().seed, i32::MIN);
+        assert!(world.resource::<SeedInput>().text.is_empty());
+    }
+
+    #[test]
+    fn randomize_rolls_an_unsaved_seed() {
+        let mut world = World::new();
+        world.init_resource::<Events<MenuPress>>();
+        world.insert_resource(WorldParams::default());
+        let settings = NoiseSettings::fallback();
+        world.insert_resource(SavedNoise::new(&settings));
+        world.insert_resource(settings.clone());
+        let button = world.spawn(RandomizeButton).id();
+
+        world.send_event(MenuPress(button));
+        world.run_system_cached(noise_actions).unwrap();
+        // one roll in 2^32 keeps the seed
+        let rolled = world.resource::<NoiseSettings>();
+        assert_ne!(rolled.seed, settings.seed);
+        assert!(world.resource::<SavedNoise>().differs(rolled));
+    }
+
+    #[test]
+    fn closing_in_game_returns_to_the_menu_before_exiting() {
+        let mut world = World::new();
+        world.init_resource::<Events<WindowCloseRequested>>();
+        world.init_resource::<Events<AppExit>>();
+        world.init_resource::<ExitRequested>();
+        world.insert_resource(State::new(AppState::Playing));
+        world.init_resource::<NextState<AppState>>();
+        let settings = NoiseSettings::fallback();
+        world.insert_resource(SavedNoise::new(&settings));
+        world.insert_resource(settings);
+
+        world.send_event(WindowCloseRequested {
+            window: Entity::PLACEHOLDER,
+        });
+        world.run_system_cached(close_requested).unwrap();
+        // leaving the game first lets `save_active_world` run
+        assert!(world.resource::<Events<AppExit>>().is_empty());
+        assert!(matches!(
+            world.resource::<NextState<AppState>>(),
+            NextState::Pending(AppState::Menu)
+        ));
+
+        world.run_system_cached(open_requested_exit_dialog).unwrap();
+        assert!(!world.resource::<Events<AppExit>>().is_empty());
+    }
+}
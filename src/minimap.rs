@@ -0,0 +1,304 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::prelude::*;
+
+use crate::player::PlayerCam;
+use crate::settings::{Action, KeyBindings};
+use crate::state::AppState;
+use crate::world::{CHUNK_SIZE, Chunk, NoiseResources, TerrainNoise, map_color, map_image};
+
+/// Edge length in pixels of the minimap image.
+const MINIMAP_SIZE: u32 = 128;
+/// World blocks covered by one minimap pixel.
+const MINIMAP_STRIDE: i32 = 4;
+/// Map pixels along one edge of a chunk column.
+const TILE: i32 = CHUNK_SIZE / MINIMAP_STRIDE;
+/// Color of chunk columns that are not loaded.
+const UNLOADED_COLOR: [u8; 4] = [10, 12, 16, 160];
+const MARKER_COLOR: [u8; 4] = [255, 60, 40, 255];
+/// Length in pixels of the heading line of the player marker.
+const MARKER_LENGTH: f32 = 7.0;
+/// Chunk columns sampled per frame, spreading the noise work of a newly
+/// loaded area over several frames.
+const TILES_PER_FRAME: usize = 8;
+
+const _: () = assert!(CHUNK_SIZE % MINIMAP_STRIDE == 0);
+
+/// Whether the minimap (`M` by default) is shown. Kept across games.
+#[derive(Resource)]
+pub struct ShowMinimap(pub bool);
+
+impl Default for ShowMinimap {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+/// Map pixels of the loaded chunk columns, sampled from the terrain noise
+/// once per column like the menu preview.
+#[derive(Resource, Default)]
+struct MinimapTiles {
+    /// Row-major [`TILE`] by [`TILE`] pixels of every chunk column.
+    tiles: HashMap<IVec2, Vec<[u8; 4]>>,
+    /// Whether tiles came or went since the map was last drawn.
+    dirty: bool,
+}
+
+#[derive(Component)]
+struct MinimapImage;
+
+/// Plugin drawing a north-up map of the loaded chunks in the bottom right
+/// corner while playing, with a marker showing where the player faces.
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShowMinimap>()
+            .init_resource::<MinimapTiles>()
+            .add_systems(OnEnter(AppState::Playing), minimap_setup)
+            .add_systems(
+                Update,
+                (toggle_minimap, update_minimap_tiles, draw_minimap)
+                    .chain()
+                    .run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(OnExit(AppState::Playing), minimap_cleanup);
+    }
+}
+
+fn minimap_setup(
+    mut commands: Commands,
+    show: Res<ShowMinimap>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    let pixels = (MINIMAP_SIZE * MINIMAP_SIZE) as usize;
+    let image = images.add(map_image(MINIMAP_SIZE, UNLOADED_COLOR.repeat(pixels)));
+    commands.spawn((
+        ImageNode::new(image),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(5.0),
+            right: Val::Px(5.0),
+            width: Val::Px(MINIMAP_SIZE as f32),
+            height: Val::Px(MINIMAP_SIZE as f32),
+            ..Default::default()
+        },
+        minimap_visibility(show.0),
+        MinimapImage,
+    ));
+}
+
+fn toggle_minimap(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut show: ResMut<ShowMinimap>,
+    mut q: Query<&mut Visibility, With<MinimapImage>>,
+) {
+    if !keys.just_pressed(bindings.key(Action::ToggleMinimap)) {
+        return;
+    }
+    show.0 = !show.0;
+    for mut vis in &mut q {
+        *vis = minimap_visibility(show.0);
+    }
+}
+
+/// Drops the tiles of unloaded chunk columns and samples a few missing ones
+/// inside the map, nearest to the player first.
+fn update_minimap_tiles(
+    show: Res<ShowMinimap>,
+    noise: Res<TerrainNoise>,
+    chunks: Query<&Chunk>,
+    cams: Query<&Transform, With<PlayerCam>>,
+    mut map: ResMut<MinimapTiles>,
+) {
+    let map = &mut *map;
+    // checked while hidden too, so a change then isn't missed
+    if noise.is_changed() {
+        map.tiles.clear();
+        map.dirty = true;
+    }
+    if !show.0 {
+        return;
+    }
+    let Ok(cam) = cams.single() else {
+        return;
+    };
+
+    let loaded: HashSet<IVec2> = chunks.iter().map(|c| c.coord.xz()).collect();
+    let before = map.tiles.len();
+    map.tiles.retain(|column, _| loaded.contains(column));
+    map.dirty |= map.tiles.len() != before;
+
+    let center = cam
+        .translation
+        .xz()
+        .floor()
+        .as_ivec2()
+        .div_euclid(IVec2::splat(CHUNK_SIZE));
+    let reach = MINIMAP_SIZE as i32 / 2 / TILE + 1;
+    let mut missing: Vec<IVec2> = loaded
+        .into_iter()
+        .filter(|c| !map.tiles.contains_key(c) && (*c - center).abs().max_element() <= reach)
+        .collect();
+    missing.sort_by_key(|c| (*c - center).length_squared());
+    for column in missing.into_iter().take(TILES_PER_FRAME) {
+        map.tiles.insert(column, sample_tile(&noise.0, column));
+        map.dirty = true;
+    }
+}
+
+/// Redraws the map when the player moved by a pixel, turned or the tiles
+/// changed.
+fn draw_minimap(
+    show: Res<ShowMinimap>,
+    mut map: ResMut<MinimapTiles>,
+    cams: Query<(&Transform, &PlayerCam)>,
+    q: Query<&ImageNode, With<MinimapImage>>,
+    mut images: ResMut<Assets<Image>>,
+    mut drawn: Local<Option<(IVec2, IVec2)>>,
+) {
+    if !show.0 {
+        return;
+    }
+    let Ok((transform, cam)) = cams.single() else {
+        return;
+    };
+    let center = transform.translation.xz().floor().as_ivec2();
+    let heading = heading(cam.yaw);
+    let key = (
+        center.div_euclid(IVec2::splat(MINIMAP_STRIDE)),
+        (heading * MARKER_LENGTH).round().as_ivec2(),
+    );
+    if !map.dirty && *drawn == Some(key) {
+        return;
+    }
+    map.dirty = false;
+    *drawn = Some(key);
+    let data = compose(&map.tiles, center, heading);
+    for node in &q {
+        if let Some(image) = images.get_mut(&node.image) {
+            image.data = Some(data.clone());
+        }
+    }
+}
+
+fn minimap_cleanup(
+    mut commands: Commands,
+    mut map: ResMut<MinimapTiles>,
+    q: Query<Entity, With<MinimapImage>>,
+) {
+    for e in &q {
+        commands.entity(e).despawn();
+    }
+    *map = MinimapTiles::default();
+}
+
+fn minimap_visibility(show: bool) -> Visibility {
+    if show {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    }
+}
+
+/// Map pixels of the chunk column `column`.
+fn sample_tile(noise: &NoiseResources, column: IVec2) -> Vec<[u8; 4]> {
+    let origin = column * CHUNK_SIZE;
+    (0..TILE)
+        .flat_map(|tz| {
+            (0..TILE).map(move |tx| {
+                map_color(
+                    noise,
+                    origin.x + tx * MINIMAP_STRIDE,
+                    origin.y + tz * MINIMAP_STRIDE,
+                )
+            })
+        })
+        .collect()
+}
+
+/// Direction the camera faces on the map for `yaw`, with north (-Z) up and
+/// east (+X) right like the compass.
+fn heading(yaw: f32) -> Vec2 {
+    Vec2::new(-yaw.sin(), -yaw.cos())
+}
+
+/// Map image pixels centered on the block column `center`, with the player
+/// marker pointing along `heading`.
+fn compose(tiles: &HashMap<IVec2, Vec<[u8; 4]>>, center: IVec2, heading: Vec2) -> Vec<u8> {
+    let size = MINIMAP_SIZE as i32;
+    let half = size / 2;
+    let origin = center.div_euclid(IVec2::splat(MINIMAP_STRIDE)) - half;
+    let mut pixels = Vec::with_capacity((size * size) as usize);
+    for pz in 0..size {
+        for px in 0..size {
+            let pixel = origin + IVec2::new(px, pz);
+            let column = pixel.div_euclid(IVec2::splat(TILE));
+            let local = pixel.rem_euclid(IVec2::splat(TILE));
+            pixels.push(tiles.get(&column).map_or(UNLOADED_COLOR, |tile| {
+                tile[(local.y * TILE + local.x) as usize]
+            }));
+        }
+    }
+
+    let mut mark = |p: IVec2| {
+        if p.cmpge(IVec2::ZERO).all() && p.cmplt(IVec2::splat(size)).all() {
+            pixels[(p.y * size + p.x) as usize] = MARKER_COLOR;
+        }
+    };
+    for dz in -1..=1 {
+        for dx in -1..=1 {
+            mark(IVec2::new(half + dx, half + dz));
+        }
+    }
+    // half-pixel steps leave no gaps in diagonal lines
+    for step in 0..=(2.0 * MARKER_LENGTH) as i32 {
+        let point = Vec2::splat(half as f32) + heading * step as f32 * 0.5;
+        mark(point.round().as_ivec2());
+    }
+    pixels.concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    #[test]
+    fn marker_points_where_the_compass_does() {
+        let half = MINIMAP_SIZE as i32 / 2;
+        let pixel = |data: &[u8], x: i32, y: i32| {
+            let i = ((y * MINIMAP_SIZE as i32 + x) * 4) as usize;
+            [data[i], data[i + 1], data[i + 2], data[i + 3]]
+        };
+        let ahead = |p: Vec2| (Vec2::splat(half as f32) + p * MARKER_LENGTH).as_ivec2();
+
+        // a yaw of zero faces north, up the map
+        let north = compose(&HashMap::new(), IVec2::ZERO, heading(0.0));
+        let tip = ahead(Vec2::NEG_Y);
+        assert_eq!(pixel(&north, tip.x, tip.y), MARKER_COLOR);
+        let behind = ahead(Vec2::Y);
+        assert_eq!(pixel(&north, behind.x, behind.y), UNLOADED_COLOR);
+
+        // turning right (negative yaw) by a quarter faces east
+        let east = compose(&HashMap::new(), IVec2::ZERO, heading(-FRAC_PI_2));
+        let tip = ahead(Vec2::X);
+        assert_eq!(pixel(&east, tip.x, tip.y), MARKER_COLOR);
+    }
+
+    #[test]
+    fn loaded_columns_are_drawn_where_they_lie() {
+        let color = [1, 2, 3, 255];
+        let tiles = HashMap::from([(IVec2::ZERO, vec![color; (TILE * TILE) as usize])]);
+        let data = compose(&tiles, IVec2::ZERO, heading(0.0));
+        let half = MINIMAP_SIZE as i32 / 2;
+        let pixel = |x: i32, y: i32| {
+            let i = ((y * MINIMAP_SIZE as i32 + x) * 4) as usize;
+            [data[i], data[i + 1], data[i + 2], data[i + 3]]
+        };
+        // the column south-east of the origin is loaded, the one west is not
+        assert_eq!(pixel(half + TILE - 1, half + TILE - 1), color);
+        assert_eq!(pixel(half - 3, half + TILE - 1), UNLOADED_COLOR);
+    }
+}
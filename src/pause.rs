@@ -0,0 +1,298 @@
+use bevy::ecs::hierarchy::ChildSpawnerCommands;
+use bevy::prelude::*;
+
+use crate::clipboard::copy_seed;
+use crate::game::{grab_cursor, release_cursor};
+use crate::menu::{ExitChoice, seed_label};
+use crate::settings::{
+    Action, DisplaySettings, KeyBindings, LookSettings, NoiseSettings, PhysicsSettings, SavedNoise,
+    save_settings, settings_path,
+};
+use crate::state::{AppState, GameState};
+
+#[derive(Component)]
+pub struct PauseRoot;
+
+#[derive(Component)]
+pub struct ResumeButton;
+
+#[derive(Component)]
+pub struct PauseMenuButton;
+
+/// Seed readout of the pause overlay, replaced by the copy result.
+#[derive(Component)]
+pub struct PauseSeedText;
+
+/// Set while the pause overlay asks what to do with unsaved noise settings
+/// before returning to the menu.
+#[derive(Resource, Default)]
+pub struct ConfirmReturn(pub bool);
+
+/// Overlay of the pause screen shown while [`ConfirmReturn`] is set.
+#[derive(Component)]
+struct ReturnDialog;
+
+/// Question of the [`ReturnDialog`], replaced by the error of a failed save.
+#[derive(Component)]
+struct ReturnDialogText;
+
+/// Plugin providing the pause overlay shown while [`GameState::Paused`] is active.
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ConfirmReturn>()
+            .add_systems(OnEnter(GameState::Running), grab_cursor)
+            .add_systems(OnEnter(GameState::Paused), (release_cursor, pause_setup))
+            .add_systems(OnExit(GameState::Paused), pause_cleanup)
+            .add_systems(
+                Update,
+                (
+                    pause_actions,
+                    return_dialog_actions,
+                    update_return_dialog,
+                    copy_seed_on_key,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Paused)),
+            );
+    }
+}
+
+fn pause_setup(
+    mut commands: Commands,
+    settings: Res<NoiseSettings>,
+    bindings: Res<KeyBindings>,
+    confirm: Res<ConfirmReturn>,
+) {
+    commands
+        .spawn((
+            Node {
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                flex_direction: FlexDirection::Column,
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                ..Default::default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+            PauseRoot,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Paused"),
+                TextFont {
+                    font_size: 40.0,
+                    ..Default::default()
+                },
+            ));
+            parent.spawn((
+                Text::new(seed_label(&settings, &bindings)),
+                TextFont {
+                    font_size: 18.0,
+                    ..Default::default()
+                },
+                PauseSeedText,
+            ));
+
+            spawn_pause_button(parent, "Resume", ResumeButton);
+            spawn_pause_button(parent, "Return to Menu", PauseMenuButton);
+
+            parent
+                .spawn((
+                    Node {
+                        position_type: PositionType::Absolute,
+                        flex_direction: FlexDirection::Column,
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        width: Val::Percent(100.0),
+                        height: Val::Percent(100.0),
+                        display: dialog_display(confirm.0),
+                        ..Default::default()
+                    },
+                    BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+                    ReturnDialog,
+                ))
+                .with_children(|dialog| {
+                    dialog.spawn((
+                        Text::new(RETURN_QUESTION),
+                        TextFont {
+                            font_size: 28.0,
+                            ..Default::default()
+                        },
+                        ReturnDialogText,
+                    ));
+                    dialog
+                        .spawn(Node {
+                            flex_direction: FlexDirection::Row,
+                            ..Default::default()
+                        })
+                        .with_children(|buttons| {
+                            spawn_pause_button(buttons, "Save", ExitChoice::Save);
+                            spawn_pause_button(buttons, "Discard", ExitChoice::Discard);
+                            spawn_pause_button(buttons, "Cancel", ExitChoice::Cancel);
+                        });
+                });
+        });
+}
+
+const RETURN_QUESTION: &str = "The noise settings have unsaved changes.";
+
+fn spawn_pause_button(parent: &mut ChildSpawnerCommands, label: &str, marker: impl Bundle) {
+    parent
+        .spawn((
+            Button,
+            Node {
+                padding: UiRect::axes(Val::Px(10.0), Val::Px(5.0)),
+                margin: UiRect::all(Val::Px(5.0)),
+                ..Default::default()
+            },
+            BackgroundColor(Color::srgb(0.15, 0.15, 0.15)),
+            marker,
+        ))
+        .with_children(|p| {
+            p.spawn((
+                Text::new(label),
+                TextFont {
+                    font_size: 24.0,
+                    ..Default::default()
+                },
+                TextColor::default(),
+            ));
+        });
+}
+
+fn dialog_display(shown: bool) -> Display {
+    if shown { Display::Flex } else { Display::None }
+}
+
+#[allow(clippy::type_complexity)]
+fn pause_actions(
+    interaction_q: Query<
+        (
+            &Interaction,
+            Option<&ResumeButton>,
+            Option<&PauseMenuButton>,
+        ),
+        Changed<Interaction>,
+    >,
+    mut next_game_state: ResMut<NextState<GameState>>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    settings: Res<NoiseSettings>,
+    saved: Res<SavedNoise>,
+    mut confirm: ResMut<ConfirmReturn>,
+) {
+    for (interaction, resume, menu) in &interaction_q {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        if resume.is_some() {
+            next_game_state.set(GameState::Running);
+        }
+
+        if menu.is_some() {
+            if saved.differs(&settings) {
+                confirm.0 = true;
+            } else {
+                next_app_state.set(AppState::Menu);
+            }
+        }
+    }
+}
+
+/// Saves and returns to the menu, returns after restoring the saved noise
+/// settings or closes the [`ReturnDialog`].
+///
+/// A failed save keeps the dialog open with the error in place of the question.
+#[allow(clippy::too_many_arguments)]
+fn return_dialog_actions(
+    choices: Query<(&Interaction, &ExitChoice), Changed<Interaction>>,
+    mut settings: ResMut<NoiseSettings>,
+    display: Res<DisplaySettings>,
+    physics: Res<PhysicsSettings>,
+    look: Res<LookSettings>,
+    bindings: Res<KeyBindings>,
+    mut saved: ResMut<SavedNoise>,
+    mut confirm: ResMut<ConfirmReturn>,
+    mut texts: Query<&mut Text, With<ReturnDialogText>>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+) {
+    for (interaction, choice) in &choices {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match choice {
+            ExitChoice::Save => {
+                let path = settings_path().display();
+                match save_settings(&settings, &display, &physics, &look, &bindings) {
+                    Ok(()) => {
+                        info!("settings saved to {path}");
+                        *saved = SavedNoise::new(&settings);
+                        next_app_state.set(AppState::Menu);
+                    }
+                    Err(err) => {
+                        warn!("could not save settings to {path}: {err}");
+                        for mut text in &mut texts {
+                            *text = Text::new(format!("Save failed: {err}"));
+                        }
+                        continue;
+                    }
+                }
+            }
+            ExitChoice::Discard => {
+                if let Some(restored) = saved.restore() {
+                    *settings = restored;
+                }
+                next_app_state.set(AppState::Menu);
+            }
+            ExitChoice::Cancel => {}
+        }
+        confirm.0 = false;
+    }
+}
+
+fn update_return_dialog(
+    confirm: Res<ConfirmReturn>,
+    mut dialogs: Query<&mut Node, With<ReturnDialog>>,
+    mut texts: Query<&mut Text, With<ReturnDialogText>>,
+) {
+    if !confirm.is_changed() {
+        return;
+    }
+    for mut dialog in &mut dialogs {
+        dialog.display = dialog_display(confirm.0);
+    }
+    if confirm.0 {
+        for mut text in &mut texts {
+            *text = Text::new(RETURN_QUESTION);
+        }
+    }
+}
+
+/// Copies the world seed when `C` (by default) is pressed while paused.
+fn copy_seed_on_key(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    settings: Res<NoiseSettings>,
+    mut texts: Query<&mut Text, With<PauseSeedText>>,
+) {
+    if !keys.just_pressed(bindings.key(Action::CopySeed)) {
+        return;
+    }
+    let message = copy_seed(settings.seed);
+    for mut text in &mut texts {
+        *text = Text::new(message.clone());
+    }
+}
+
+fn pause_cleanup(
+    mut commands: Commands,
+    roots: Query<Entity, With<PauseRoot>>,
+    mut confirm: ResMut<ConfirmReturn>,
+) {
+    confirm.0 = false;
+    for e in &roots {
+        commands.entity(e).despawn();
+    }
+}
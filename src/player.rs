@@ -1,14 +1,160 @@
 use bevy::input::mouse::MouseMotion;
 use bevy::prelude::*;
 
-#[derive(Component)]
+use crate::settings::{Action, KeyBindings, LookSettings, PhysicsSettings};
+use crate::world::{
+    CHUNK_SIZE, NoiseResources, TerrainNoise, WorldParams, is_underwater, terrain_height,
+};
+
+/// Height of the camera above the ground block it stands on.
+pub const EYE_HEIGHT: f32 = 1.7;
+
+/// Fraction of the normal speed left while moving through water.
+const WATER_SPEED: f32 = 0.5;
+
+/// Upward drift in blocks per second while submerged without vertical input.
+const BUOYANCY: f32 = 1.5;
+
+/// Accepted range of [`PhysicsSettings::tick_rate`].
+const TICK_RATES: std::ops::RangeInclusive<f32> = 10.0..=240.0;
+
+/// Steepest accepted [`LookSettings::pitch_limit`], just short of straight
+/// up or down where the camera's forward direction would lose its heading.
+pub const MAX_PITCH: f32 = std::f32::consts::FRAC_PI_2 - 0.01;
+
+/// Rate per second at which the camera closes the gap to
+/// [`WorldParams::camera_floor`] once below it.
+const FLOOR_SPRING: f32 = 6.0;
+
+/// Angular speed of the orbit camera in radians per second.
+const ORBIT_SPEED: f32 = 0.15;
+
+/// Flying speed of the player camera.
+#[derive(Resource)]
+pub struct MoveSpeed {
+    /// Top speed in blocks per second.
+    pub max: f32,
+    /// Blocks per second gained while a movement key is held, and lost
+    /// again once it is released.
+    pub acceleration: f32,
+}
+
+impl Default for MoveSpeed {
+    fn default() -> Self {
+        Self {
+            max: 40.0,
+            acceleration: 50.0,
+        }
+    }
+}
+
+/// Where the player returns to with `R`.
+#[derive(Resource, Default)]
+pub struct SpawnPoint {
+    /// Position recorded with `T`; the surface above the origin otherwise.
+    pub custom: Option<Vec3>,
+}
+
+impl SpawnPoint {
+    pub fn position(&self, noise: &NoiseResources) -> Vec3 {
+        self.custom.unwrap_or_else(|| surface_spawn(noise, 0, 0))
+    }
+}
+
+/// Runs the `FixedUpdate` simulation at the configured tick rate.
+pub fn apply_tick_rate(physics: Res<PhysicsSettings>, mut time: ResMut<Time<Fixed>>) {
+    if physics.is_changed() {
+        let rate = physics
+            .tick_rate
+            .clamp(*TICK_RATES.start(), *TICK_RATES.end());
+        time.set_timestep_hz(rate as f64);
+    }
+}
+
+/// Camera positions before and after the latest `FixedUpdate` step.
+///
+/// The rendered camera sits between the two by the fraction of a tick that
+/// has passed since, so motion stays smooth at frame rates above the tick
+/// rate. A translation that differs from the one last shown was written
+/// outside the simulation (a teleport or the orbit camera) and is adopted
+/// as is.
+#[derive(Component, Default)]
+pub struct TickBlend {
+    start: Vec3,
+    end: Vec3,
+    shown: Option<Vec3>,
+}
+
+impl TickBlend {
+    /// Simulated position to step from, given the current `translation`.
+    fn resume(&mut self, translation: Vec3) -> Vec3 {
+        if self.shown != Some(translation) {
+            self.start = translation;
+            self.end = translation;
+        }
+        self.end
+    }
+
+    /// Position shown `fraction` of a tick past the latest step.
+    fn show(&mut self, fraction: f32) -> Vec3 {
+        let shown = self.start.lerp(self.end, fraction);
+        self.shown = Some(shown);
+        shown
+    }
+}
+
+/// Puts the camera back at its simulated position before the fixed steps.
+pub fn resume_tick_position(mut q: Query<(&mut Transform, &mut TickBlend)>) {
+    for (mut transform, mut blend) in &mut q {
+        transform.translation = blend.resume(transform.translation);
+    }
+}
+
+pub fn start_tick(mut q: Query<(&Transform, &mut TickBlend)>) {
+    for (transform, mut blend) in &mut q {
+        blend.start = transform.translation;
+    }
+}
+
+pub fn end_tick(mut q: Query<(&Transform, &mut TickBlend)>) {
+    for (transform, mut blend) in &mut q {
+        blend.end = transform.translation;
+    }
+}
+
+/// Shows the camera between its last two simulated positions.
+pub fn interpolate_camera(time: Res<Time<Fixed>>, mut q: Query<(&mut Transform, &mut TickBlend)>) {
+    for (mut transform, mut blend) in &mut q {
+        transform.translation = blend.show(time.overstep_fraction());
+    }
+}
+
+/// Camera position standing on the terrain surface of column `wx`, `wz`.
+pub fn surface_spawn(noise: &NoiseResources, wx: i32, wz: i32) -> Vec3 {
+    let ground = terrain_height(noise, wx, wz);
+    Vec3::new(
+        wx as f32 + 0.5,
+        ground as f32 + 1.0 + EYE_HEIGHT,
+        wz as f32 + 0.5,
+    )
+}
+
+#[derive(Component, Default)]
+#[require(TickBlend)]
 pub struct PlayerCam {
     pub yaw: f32,
     pub pitch: f32,
+    /// Whether the camera is inside water, updated every frame.
+    pub submerged: bool,
+    /// Current velocity in blocks per second.
+    pub velocity: Vec3,
+    /// Walks on the terrain under gravity instead of flying.
+    pub walking: bool,
 }
 
 pub fn mouse_look(
     mut mouse_events: EventReader<MouseMotion>,
+    look: Res<LookSettings>,
     mut q: Query<(&mut Transform, &mut PlayerCam)>,
 ) {
     let mut delta = Vec2::ZERO;
@@ -19,45 +165,421 @@ pub fn mouse_look(
         return;
     }
     if let Ok((mut transform, mut cam)) = q.single_mut() {
-        let sensitivity = 0.002;
-        cam.yaw -= delta.x * sensitivity;
-        cam.pitch -= delta.y * sensitivity;
-        cam.pitch = cam.pitch.clamp(-1.54, 1.54);
-        transform.rotation =
-            Quat::from_axis_angle(Vec3::Y, cam.yaw) * Quat::from_axis_angle(Vec3::X, cam.pitch);
+        turn(&mut cam, delta, &look);
+        transform.rotation = look_rotation(cam.yaw, cam.pitch);
     }
 }
 
+/// Turns `cam` by a mouse motion of `delta` pixels, keeping the pitch inside
+/// the configured limit.
+fn turn(cam: &mut PlayerCam, delta: Vec2, look: &LookSettings) {
+    let limit = look.pitch_limit.clamp(0.0, MAX_PITCH);
+    // wrapped so the angle keeps its precision over long sessions
+    cam.yaw = (cam.yaw - delta.x * look.sensitivity).rem_euclid(std::f32::consts::TAU);
+    cam.pitch = (cam.pitch - delta.y * look.sensitivity).clamp(-limit, limit);
+}
+
+/// Camera rotation facing `yaw` around the vertical axis and tilted by
+/// `pitch`, rebuilt from the two angles so no roll can ever creep in.
+pub fn look_rotation(yaw: f32, pitch: f32) -> Quat {
+    Quat::from_axis_angle(Vec3::Y, yaw) * Quat::from_axis_angle(Vec3::X, pitch)
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn keyboard_move(
     time: Res<Time>,
     keys: Res<ButtonInput<KeyCode>>,
-    mut q: Query<&mut Transform, With<PlayerCam>>,
+    bindings: Res<KeyBindings>,
+    speed: Res<MoveSpeed>,
+    physics: Res<PhysicsSettings>,
+    noise: Res<TerrainNoise>,
+    params: Res<WorldParams>,
+    mut q: Query<(&mut Transform, &mut PlayerCam)>,
 ) {
-    if let Ok(mut transform) = q.single_mut() {
+    if let Ok((mut transform, mut cam)) = q.single_mut() {
+        if cam.walking {
+            walk(
+                &time,
+                &keys,
+                &bindings,
+                &speed,
+                &physics,
+                &noise.0,
+                &mut transform,
+                &mut cam,
+            );
+            keep_in_bounds(&params, &mut transform, &mut cam);
+            return;
+        }
         let mut direction = Vec3::ZERO;
         let forward = transform.forward();
         let right = transform.right();
-        if keys.pressed(KeyCode::KeyW) {
+        let pressed = |action| keys.pressed(bindings.key(action));
+        if pressed(Action::MoveForward) {
             direction += *forward;
         }
-        if keys.pressed(KeyCode::KeyS) {
+        if pressed(Action::MoveBack) {
             direction -= *forward;
         }
-        if keys.pressed(KeyCode::KeyA) {
+        if pressed(Action::MoveLeft) {
             direction -= *right;
         }
-        if keys.pressed(KeyCode::KeyD) {
+        if pressed(Action::MoveRight) {
             direction += *right;
         }
-        if keys.pressed(KeyCode::Space) {
+        if pressed(Action::MoveUp) {
             direction += Vec3::Y;
         }
-        if keys.pressed(KeyCode::ShiftLeft) {
+        if pressed(Action::MoveDown) {
             direction -= Vec3::Y;
         }
-        if direction.length_squared() > 0.0 {
-            let speed = 25.0;
-            transform.translation += direction.normalize() * speed * time.delta_secs();
+        let rising = direction.y != 0.0;
+        let max = if cam.submerged {
+            speed.max * WATER_SPEED
+        } else {
+            speed.max
+        };
+        let target = fly_target(direction, max, &physics);
+        cam.velocity = if target != Vec3::ZERO && pressed(Action::InstantSpeed) {
+            target
+        } else {
+            cam.velocity
+                .move_towards(target, speed.acceleration * time.delta_secs())
+        };
+        if let Some(floor) = params.camera_floor {
+            let cam = &mut *cam;
+            clamp_to_floor(
+                &mut transform.translation,
+                &mut cam.velocity,
+                floor,
+                time.delta_secs(),
+            );
+        }
+        transform.translation += cam.velocity * time.delta_secs();
+        if cam.submerged && !rising {
+            transform.translation.y += BUOYANCY * time.delta_secs();
         }
+        keep_in_bounds(&params, &mut transform, &mut cam);
+    }
+}
+
+/// Flying velocity aimed for along `direction` at a top speed of `max`,
+/// scaled by the horizontal and vertical speed multipliers.
+fn fly_target(direction: Vec3, max: f32, physics: &PhysicsSettings) -> Vec3 {
+    let scale = Vec3::new(
+        physics.horizontal_speed,
+        physics.vertical_speed,
+        physics.horizontal_speed,
+    );
+    direction.normalize_or_zero() * max * scale.max(Vec3::ZERO)
+}
+
+/// Holds the camera inside [`WorldParams::bounds`], stopping its motion
+/// along the axes it was pushed back on.
+fn keep_in_bounds(params: &WorldParams, transform: &mut Transform, cam: &mut PlayerCam) {
+    let Some(bounds) = params.bounds else {
+        return;
+    };
+    let clamped = bounds.clamp(transform.translation);
+    if clamped.x != transform.translation.x {
+        cam.velocity.x = 0.0;
+    }
+    if clamped.z != transform.translation.z {
+        cam.velocity.z = 0.0;
+    }
+    transform.translation = clamped;
+}
+
+/// Stops the camera sinking any further once at or below `floor` and eases
+/// it back up, softly enough that flying into the floor does not jolt the
+/// view. Runs before the velocity is applied.
+fn clamp_to_floor(translation: &mut Vec3, velocity: &mut Vec3, floor: f32, dt: f32) {
+    if translation.y > floor {
+        return;
+    }
+    velocity.y = velocity.y.max(0.0);
+    translation.y += (floor - translation.y) * (1.0 - (-FLOOR_SPRING * dt).exp());
+}
+
+/// Forward and right directions along the ground for `yaw`.
+///
+/// Derived from the yaw alone rather than by flattening the camera's
+/// forward vector, which shrinks to nothing when looking straight down, so
+/// walking keeps its heading and full speed wherever the camera points.
+fn ground_axes(yaw: f32) -> (Vec3, Vec3) {
+    let rotation = Quat::from_rotation_y(yaw);
+    (rotation * Vec3::NEG_Z, rotation * Vec3::X)
+}
+
+/// Moves the camera along the ground, falling under gravity and jumping with
+/// the up key.
+#[allow(clippy::too_many_arguments)]
+fn walk(
+    time: &Time,
+    keys: &ButtonInput<KeyCode>,
+    bindings: &KeyBindings,
+    speed: &MoveSpeed,
+    physics: &PhysicsSettings,
+    noise: &NoiseResources,
+    transform: &mut Transform,
+    cam: &mut PlayerCam,
+) {
+    let dt = time.delta_secs();
+    let pressed = |action| keys.pressed(bindings.key(action));
+    let (forward, right) = ground_axes(cam.yaw);
+    let mut direction = Vec3::ZERO;
+    if pressed(Action::MoveForward) {
+        direction += forward;
+    }
+    if pressed(Action::MoveBack) {
+        direction -= forward;
+    }
+    if pressed(Action::MoveLeft) {
+        direction -= right;
+    }
+    if pressed(Action::MoveRight) {
+        direction += right;
+    }
+    let max = if cam.submerged {
+        speed.max * WATER_SPEED
+    } else {
+        speed.max
+    };
+    let target = direction.normalize_or_zero() * max;
+    let horizontal = Vec3::new(cam.velocity.x, 0.0, cam.velocity.z);
+    let horizontal = if target != Vec3::ZERO && pressed(Action::InstantSpeed) {
+        target
+    } else {
+        horizontal.move_towards(target, speed.acceleration * dt)
+    };
+
+    let pos = transform.translation;
+    let ground = surface_spawn(noise, pos.x.floor() as i32, pos.z.floor() as i32).y;
+    let grounded = pos.y <= ground + 0.01;
+    let vertical = if grounded && pressed(Action::MoveUp) {
+        physics.jump_speed
+    } else {
+        (cam.velocity.y - physics.gravity * dt).max(-physics.terminal_velocity)
+    };
+
+    cam.velocity = horizontal.with_y(vertical);
+    transform.translation += cam.velocity * dt;
+    if transform.translation.y < ground {
+        transform.translation.y = ground;
+        cam.velocity.y = cam.velocity.y.max(0.0);
+    }
+}
+
+/// Switches between flying and walking when `G` (by default) is pressed.
+pub fn toggle_walk(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut q: Query<&mut PlayerCam>,
+) {
+    if !keys.just_pressed(bindings.key(Action::ToggleWalk)) {
+        return;
+    }
+    for mut cam in &mut q {
+        cam.walking = !cam.walking;
+        cam.velocity = Vec3::ZERO;
+    }
+}
+
+/// Flags the camera as submerged while it is inside water.
+pub fn update_submerged(noise: Res<TerrainNoise>, mut q: Query<(&Transform, &mut PlayerCam)>) {
+    for (transform, mut cam) in &mut q {
+        let submerged = is_underwater(&noise.0, transform.translation);
+        if cam.submerged != submerged {
+            cam.submerged = submerged;
+        }
+    }
+}
+
+/// Run condition for the orbit camera, see [`WorldParams::orbit`].
+pub fn orbit_mode(params: Res<WorldParams>) -> bool {
+    params.orbit
+}
+
+/// Circles the camera around the terrain at the world origin, looking at it.
+///
+/// The orbit angle is kept in `yaw` and the downward tilt in `pitch`, so the
+/// free camera would keep facing the same way.
+pub fn orbit_camera(
+    time: Res<Time>,
+    noise: Res<TerrainNoise>,
+    params: Res<WorldParams>,
+    mut q: Query<(&mut Transform, &mut PlayerCam)>,
+) {
+    let ground = terrain_height(&noise.0, 0, 0) as f32;
+    let focus = Vec3::new(0.5, ground + 1.0, 0.5);
+    let radius = params.render_radius() as f32 * CHUNK_SIZE as f32 * 0.5;
+    for (mut transform, mut cam) in &mut q {
+        cam.yaw = (cam.yaw + ORBIT_SPEED * time.delta_secs()) % std::f32::consts::TAU;
+        let offset = Vec3::new(cam.yaw.sin(), 0.5, cam.yaw.cos()) * radius;
+        cam.pitch = -(0.5f32).atan();
+        transform.translation = focus + offset;
+        transform.rotation = look_rotation(cam.yaw, cam.pitch);
+    }
+}
+
+/// Teleports the camera to the spawn point when `R` (by default) is pressed.
+pub fn teleport_to_spawn(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    spawn: Res<SpawnPoint>,
+    noise: Res<TerrainNoise>,
+    mut q: Query<(&mut Transform, &mut PlayerCam)>,
+) {
+    if !keys.just_pressed(bindings.key(Action::TeleportToSpawn)) {
+        return;
+    }
+    if let Ok((mut transform, mut cam)) = q.single_mut() {
+        transform.translation = spawn.position(&noise.0);
+        cam.velocity = Vec3::ZERO;
+    }
+}
+
+/// Records the current camera position as the spawn point when `T` (by
+/// default) is pressed.
+pub fn set_spawn_point(
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut spawn: ResMut<SpawnPoint>,
+    q: Query<&Transform, With<PlayerCam>>,
+) {
+    if keys.just_pressed(bindings.key(Action::SetSpawn))
+        && let Ok(transform) = q.single()
+    {
+        spawn.custom = Some(transform.translation);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn looking_around_never_rolls_the_camera() {
+        let look = LookSettings {
+            sensitivity: 0.002,
+            pitch_limit: 1.2,
+        };
+        let mut cam = PlayerCam::default();
+        // fast diagonal flicks in every direction, far past the pitch limit
+        for i in 0..10_000 {
+            let delta = Vec2::new(350.0, 240.0) * if i % 7 < 4 { 1.0 } else { -1.0 };
+            turn(&mut cam, delta, &look);
+            assert!(cam.pitch.abs() <= 1.2);
+            let rotation = look_rotation(cam.yaw, cam.pitch);
+            let (_, _, roll) = rotation.to_euler(EulerRot::YXZ);
+            assert!(roll.abs() < 1e-4, "roll of {roll} after {i} turns");
+            // the right vector stays level with the horizon
+            assert!((rotation * Vec3::X).y.abs() < 1e-4);
+        }
+
+        let steep = LookSettings {
+            pitch_limit: 10.0,
+            ..look
+        };
+        turn(&mut cam, Vec2::new(0.0, -1e6), &steep);
+        assert_eq!(cam.pitch, MAX_PITCH);
+    }
+
+    #[test]
+    fn walking_ignores_the_pitch() {
+        for yaw in [0.0, 1.0, -2.5, 4.0] {
+            let (forward, right) = ground_axes(yaw);
+            assert_eq!(forward.y, 0.0);
+            assert_eq!(right.y, 0.0);
+            assert!((forward.length() - 1.0).abs() < 1e-5);
+            for pitch in [-MAX_PITCH, -0.7, 0.0, 0.7, MAX_PITCH] {
+                // the heading matches the camera's, however far it looks down
+                let camera = look_rotation(yaw, pitch) * Vec3::NEG_Z;
+                assert!(camera.xz().normalize().distance(forward.xz()) < 1e-3);
+                assert!((look_rotation(yaw, pitch) * Vec3::X).distance(right) < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn vertical_flight_has_its_own_speed() {
+        let physics = PhysicsSettings {
+            gravity: 25.0,
+            terminal_velocity: 50.0,
+            jump_speed: 8.0,
+            tick_rate: 64.0,
+            horizontal_speed: 1.5,
+            vertical_speed: 0.25,
+        };
+        let up = fly_target(Vec3::Y, 40.0, &physics);
+        let ahead = fly_target(Vec3::NEG_Z, 40.0, &physics);
+        assert!((up.y - 10.0).abs() < 1e-4);
+        assert!((ahead.z + 60.0).abs() < 1e-4);
+        // climbing while moving keeps the heading's share of the speed
+        let both = fly_target(Vec3::new(0.0, 1.0, -1.0), 40.0, &physics);
+        assert!((both.z / both.y + 6.0).abs() < 1e-4);
+        assert_eq!(fly_target(Vec3::ZERO, 40.0, &physics), Vec3::ZERO);
+    }
+
+    #[test]
+    fn flying_down_stops_at_the_floor() {
+        let floor = -8.0;
+        let mut translation = Vec3::new(0.0, 10.0, 0.0);
+        let mut velocity = Vec3::new(3.0, -50.0, 0.0);
+        let dt = 1.0 / 60.0;
+        let mut lowest = translation.y;
+        for _ in 0..600 {
+            // holding the down key keeps pushing
+            velocity.y = velocity.y.min(-50.0);
+            clamp_to_floor(&mut translation, &mut velocity, floor, dt);
+            translation += velocity * dt;
+            lowest = lowest.min(translation.y);
+        }
+        // dips by at most a frame of movement, then settles on the floor
+        assert!(lowest > floor - 50.0 * dt - 0.01);
+        assert!((translation.y - floor).abs() < 1.0);
+        assert_eq!(velocity.x, 3.0);
+
+        // released, it eases the rest of the way back
+        velocity = Vec3::ZERO;
+        for _ in 0..120 {
+            clamp_to_floor(&mut translation, &mut velocity, floor, dt);
+        }
+        assert!((translation.y - floor).abs() < 0.01);
+    }
+
+    #[test]
+    fn tick_rate_sets_the_fixed_step_within_range() {
+        let mut world = World::new();
+        world.insert_resource(Time::<Fixed>::default());
+        for (rate, hz) in [(120.0, 120.0), (1.0, 10.0), (1000.0, 240.0)] {
+            world.insert_resource(PhysicsSettings {
+                tick_rate: rate,
+                ..PhysicsSettings::fallback()
+            });
+            world.run_system_cached(apply_tick_rate).unwrap();
+            let step = world.resource::<Time<Fixed>>().timestep().as_secs_f64();
+            assert!((step - 1.0 / hz).abs() < 1e-6, "{rate} Hz stepped {step}");
+        }
+    }
+
+    #[test]
+    fn rendered_camera_blends_between_ticks_and_keeps_teleports() {
+        let mut blend = TickBlend::default();
+        let spawn = Vec3::new(0.0, 10.0, 0.0);
+        assert_eq!(blend.resume(spawn), spawn);
+
+        // one tick moved the camera two blocks along x
+        blend.start = spawn;
+        blend.end = spawn + Vec3::X * 2.0;
+        let shown = blend.show(0.25);
+        assert_eq!(shown, spawn + Vec3::X * 0.5);
+        // the next frame steps on from the simulated position
+        assert_eq!(blend.resume(shown), spawn + Vec3::X * 2.0);
+
+        // a teleport between frames is taken over without blending back
+        let teleport = Vec3::new(100.0, 40.0, -30.0);
+        assert_eq!(blend.resume(teleport), teleport);
+        assert_eq!(blend.show(0.7), teleport);
     }
 }
@@ -0,0 +1,220 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::player::{PlayerCam, SpawnPoint};
+use crate::settings::{NoiseSettings, settings_path, write_atomic};
+use crate::world::{MAX_HEIGHT_CHUNKS, WorldParams};
+
+/// Directory next to the settings file holding one folder per named world.
+const WORLDS_DIR: &str = "worlds";
+/// Manifest file inside each world folder.
+const MANIFEST_FILE: &str = "world.json";
+/// Longest accepted world name.
+pub const MAX_NAME_LEN: usize = 24;
+
+/// Camera position and orientation to resume a world at.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct PlayerPose {
+    pub position: [f32; 3],
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+/// Everything needed to regenerate a named world and resume it.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct WorldManifest {
+    pub noise: NoiseSettings,
+    pub view_width: i32,
+    /// Drawn distance; manifests saved before it existed draw the full
+    /// `view_width`.
+    #[serde(default)]
+    pub render_width: Option<i32>,
+    pub height_chunks: i32,
+    /// Spawn point recorded with `T`, if any.
+    #[serde(default)]
+    pub spawn: Option<[f32; 3]>,
+    /// Where the player left the world; `None` starts at the spawn point.
+    #[serde(default)]
+    pub player: Option<PlayerPose>,
+}
+
+/// World the current game is saved to when it ends, if any.
+#[derive(Resource, Default)]
+pub struct ActiveWorld {
+    pub name: Option<String>,
+    /// Pose the next game starts at, taken by `setup_game`.
+    pub resume: Option<PlayerPose>,
+}
+
+/// Folder holding the named worlds, next to the [`settings_path`] file.
+pub fn worlds_dir() -> PathBuf {
+    settings_path()
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(WORLDS_DIR)
+}
+
+/// Whether `name` can be used as a world folder name.
+pub fn valid_name(name: &str) -> bool {
+    !name.trim().is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, ' ' | '-' | '_'))
+}
+
+/// Names of the worlds in `dir` that have a manifest, sorted.
+pub fn list_worlds(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().join(MANIFEST_FILE).is_file())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    names.sort();
+    names
+}
+
+pub fn load_world(dir: &Path, name: &str) -> io::Result<WorldManifest> {
+    let data = fs::read_to_string(dir.join(name).join(MANIFEST_FILE))?;
+    Ok(serde_json::from_str(&data)?)
+}
+
+pub fn save_world(dir: &Path, name: &str, manifest: &WorldManifest) -> io::Result<()> {
+    if !valid_name(name) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid world name `{name}`"),
+        ));
+    }
+    let folder = dir.join(name);
+    fs::create_dir_all(&folder)?;
+    let json = serde_json::to_string_pretty(manifest)?;
+    write_atomic(&folder.join(MANIFEST_FILE), &json)
+}
+
+pub fn delete_world(dir: &Path, name: &str) -> io::Result<()> {
+    // never let a crafted name reach outside the worlds folder
+    if !valid_name(name) {
+        return Err(io::ErrorKind::InvalidInput.into());
+    }
+    fs::remove_dir_all(dir.join(name))
+}
+
+impl WorldManifest {
+    /// Manifest of the live world state.
+    pub fn capture(
+        settings: &NoiseSettings,
+        params: &WorldParams,
+        spawn: &SpawnPoint,
+        player: Option<PlayerPose>,
+    ) -> Self {
+        Self {
+            noise: settings.clone(),
+            view_width: params.view_width,
+            render_width: Some(params.render_width),
+            height_chunks: params.height_chunks,
+            spawn: spawn.custom.map(|p| p.to_array()),
+            player,
+        }
+    }
+
+    /// Restores the saved world size onto `params`, clamped to what the
+    /// menu allows.
+    pub fn apply_params(&self, params: &mut WorldParams) {
+        params.view_width = self.view_width.max(1);
+        params.render_width = self
+            .render_width
+            .unwrap_or(params.view_width)
+            .clamp(1, params.view_width);
+        params.height_chunks = self.height_chunks.clamp(1, MAX_HEIGHT_CHUNKS);
+    }
+}
+
+/// Writes the active world's manifest with the current player pose.
+///
+/// Runs on leaving gameplay, before `game_cleanup` forgets the spawn point.
+pub fn save_active_world(
+    active: Res<ActiveWorld>,
+    settings: Res<NoiseSettings>,
+    params: Res<WorldParams>,
+    spawn: Res<SpawnPoint>,
+    player: Query<(&Transform, &PlayerCam)>,
+) {
+    let Some(name) = &active.name else {
+        return;
+    };
+    let pose = player.single().ok().map(|(transform, cam)| PlayerPose {
+        position: transform.translation.to_array(),
+        yaw: cam.yaw,
+        pitch: cam.pitch,
+    });
+    let manifest = WorldManifest::capture(&settings, &params, &spawn, pose);
+    match save_world(&worlds_dir(), name, &manifest) {
+        Ok(()) => info!("saved world `{name}`"),
+        Err(err) => warn!("could not save world `{name}`: {err}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn worlds_round_trip_through_their_folder() {
+        let dir = std::env::temp_dir().join(format!("rube-worlds-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        assert!(list_worlds(&dir).is_empty());
+
+        let params = WorldParams {
+            view_width: 12,
+            render_width: 8,
+            height_chunks: 16,
+            ..default()
+        };
+        let spawn = SpawnPoint {
+            custom: Some(Vec3::new(1.0, 2.0, 3.0)),
+        };
+        let pose = PlayerPose {
+            position: [4.0, 5.0, 6.0],
+            yaw: 0.5,
+            pitch: -0.25,
+        };
+        let settings = NoiseSettings {
+            seed: 99,
+            ..NoiseSettings::fallback()
+        };
+        let manifest = WorldManifest::capture(&settings, &params, &spawn, Some(pose));
+        save_world(&dir, "MyWorld", &manifest).unwrap();
+        save_world(&dir, "Another", &manifest).unwrap();
+        assert!(save_world(&dir, "../escape", &manifest).is_err());
+        assert_eq!(list_worlds(&dir), ["Another", "MyWorld"]);
+
+        let loaded = load_world(&dir, "MyWorld").unwrap();
+        assert_eq!(loaded.noise.seed, 99);
+        let mut restored = WorldParams::default();
+        loaded.apply_params(&mut restored);
+        assert_eq!(restored.view_width, 12);
+        assert_eq!(restored.render_width, 8);
+        assert_eq!(restored.height_chunks, 16);
+        assert_eq!(loaded.spawn, Some([1.0, 2.0, 3.0]));
+        assert_eq!(loaded.player, Some(pose));
+
+        // older manifests without a render width draw everything generated
+        let mut json = serde_json::to_value(&loaded).unwrap();
+        json.as_object_mut().unwrap().remove("render_width");
+        let old: WorldManifest = serde_json::from_value(json).unwrap();
+        old.apply_params(&mut restored);
+        assert_eq!(restored.render_width, 12);
+
+        delete_world(&dir, "Another").unwrap();
+        assert_eq!(list_worlds(&dir), ["MyWorld"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
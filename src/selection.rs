@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+use bevy::render::mesh::PrimitiveTopology;
+use bevy::render::render_asset::RenderAssetUsages;
+
+use crate::player::PlayerCam;
+use crate::state::AppState;
+use crate::world::{TerrainNoise, raycast_block};
+
+/// Distance in blocks up to which the targeted block is outlined.
+pub const REACH: f32 = 6.0;
+
+/// Half edge of the outline cube, slightly larger than a block so the lines
+/// are not hidden inside the block faces.
+const OUTLINE_HALF: f32 = 0.502;
+
+/// Wireframe cube snapped to the block the camera is looking at.
+#[derive(Component)]
+pub struct BlockOutline;
+
+/// Plugin outlining the block under the crosshair during gameplay.
+pub struct SelectionPlugin;
+
+impl Plugin for SelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Playing), outline_setup)
+            .add_systems(Update, update_outline.run_if(in_state(AppState::Playing)))
+            .add_systems(OnExit(AppState::Playing), outline_cleanup);
+    }
+}
+
+/// Line list of the twelve edges of a cube centred on the origin.
+fn outline_mesh() -> Mesh {
+    let corner = |i: u32| {
+        Vec3::new(
+            if i & 1 == 0 { -1.0 } else { 1.0 },
+            if i & 2 == 0 { -1.0 } else { 1.0 },
+            if i & 4 == 0 { -1.0 } else { 1.0 },
+        ) * OUTLINE_HALF
+    };
+    let mut positions = Vec::with_capacity(24);
+    for a in 0..8 {
+        // connect every corner to the neighbours differing in one higher bit
+        for bit in [1, 2, 4] {
+            if a & bit == 0 {
+                positions.push(corner(a).to_array());
+                positions.push(corner(a | bit).to_array());
+            }
+        }
+    }
+    Mesh::new(PrimitiveTopology::LineList, RenderAssetUsages::RENDER_WORLD)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+}
+
+fn outline_setup(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn((
+        Mesh3d(meshes.add(outline_mesh())),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            base_color: Color::BLACK,
+            unlit: true,
+            ..default()
+        })),
+        Transform::default(),
+        Visibility::Hidden,
+        BlockOutline,
+    ));
+}
+
+/// Moves the outline onto the block the camera looks at, hiding it when no
+/// block lies within [`REACH`].
+fn update_outline(
+    noise: Res<TerrainNoise>,
+    cam: Query<&Transform, (With<PlayerCam>, Without<BlockOutline>)>,
+    mut outline: Query<(&mut Transform, &mut Visibility), With<BlockOutline>>,
+) {
+    let (Ok(cam), Ok((mut transform, mut visibility))) = (cam.single(), outline.single_mut())
+    else {
+        return;
+    };
+    match raycast_block(&noise.0, cam.translation, *cam.forward(), REACH) {
+        Some(voxel) => {
+            transform.translation = voxel.as_vec3() + Vec3::splat(0.5);
+            *visibility = Visibility::Visible;
+        }
+        None => *visibility = Visibility::Hidden,
+    }
+}
+
+fn outline_cleanup(mut commands: Commands, outlines: Query<Entity, With<BlockOutline>>) {
+    for e in &outlines {
+        commands.entity(e).despawn();
+    }
+}
@@ -1,62 +1,1266 @@
 use bevy::prelude::*;
-use serde::{Deserialize, Serialize};
+use bevy::reflect::{DynamicEnum, DynamicVariant, TypeInfo, Typed, VariantInfo};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+use std::ffi::OsString;
 use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::biome::BiomeSettings;
+
+/// Settings file used when neither `--settings` nor `RUBE_SETTINGS` is given.
+const DEFAULT_SETTINGS_PATH: &str = "settings.json";
+
+/// Environment variable overriding the settings file location.
+const SETTINGS_ENV: &str = "RUBE_SETTINGS";
+
+/// Location of the settings file, resolved on first use.
+///
+/// A `--settings <path>` (or `--settings=<path>`) argument wins over the
+/// `RUBE_SETTINGS` environment variable, which wins over `settings.json` in
+/// the working directory.
+pub fn settings_path() -> &'static Path {
+    static PATH: OnceLock<PathBuf> = OnceLock::new();
+    PATH.get_or_init(|| {
+        resolve_settings_path(std::env::args().skip(1), std::env::var_os(SETTINGS_ENV))
+    })
+}
+
+fn resolve_settings_path(mut args: impl Iterator<Item = String>, env: Option<OsString>) -> PathBuf {
+    while let Some(arg) = args.next() {
+        if arg == "--settings" {
+            if let Some(path) = args.next() {
+                return path.into();
+            }
+        } else if let Some(path) = arg.strip_prefix("--settings=") {
+            return path.into();
+        }
+    }
+    env.filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| DEFAULT_SETTINGS_PATH.into())
+}
+
+/// Largest chunk generation thread count accepted, see
+/// [`DisplaySettings::compute_threads`].
+pub const MAX_COMPUTE_THREADS: usize = 64;
+
+/// Thread count of the async compute pool that generates chunks, read once
+/// at startup since the pool cannot be resized later.
+///
+/// A `--compute-threads <n>` (or `--compute-threads=<n>`) argument wins over
+/// [`DisplaySettings::compute_threads`]; `None` keeps Bevy's default.
+pub fn compute_threads() -> Option<usize> {
+    resolve_compute_threads(
+        std::env::args().skip(1),
+        DisplaySettings::default().compute_threads,
+    )
+}
+
+fn resolve_compute_threads(
+    mut args: impl Iterator<Item = String>,
+    saved: Option<usize>,
+) -> Option<usize> {
+    let mut threads = saved;
+    while let Some(arg) = args.next() {
+        let value = if arg == "--compute-threads" {
+            args.next()
+        } else {
+            arg.strip_prefix("--compute-threads=").map(str::to_string)
+        };
+        let Some(value) = value else {
+            continue;
+        };
+        match value.parse::<usize>() {
+            Ok(n) => threads = Some(n),
+            Err(err) => warn!("ignoring --compute-threads {value}: {err}"),
+        }
+    }
+    threads.map(|n| n.clamp(1, MAX_COMPUTE_THREADS))
+}
+
+/// Frame rate caps selectable in the menu; `None` leaves the frame rate uncapped.
+pub const FPS_CAPS: [Option<u32>; 6] = [None, Some(30), Some(60), Some(120), Some(144), Some(240)];
+
+/// Simulation rates in Hz selectable in the menu.
+pub const TICK_RATE_STEPS: [f32; 6] = [20.0, 30.0, 60.0, 64.0, 120.0, 240.0];
+
+/// Multisample anti-aliasing sample counts selectable in the menu; 1 is off.
+pub const MSAA_SAMPLES: [u32; 3] = [1, 2, 4];
+
+/// World brightness levels selectable in the menu; 1 is the unmodified look.
+pub const BRIGHTNESS_LEVELS: [f32; 7] = [0.5, 0.75, 1.0, 1.25, 1.5, 1.75, 2.0];
+
+/// Background colors selectable in the menu, in sRGB; the first is the
+/// horizon of the default sky.
+pub const SKY_COLORS: [(&str, [f32; 3]); 5] = [
+    ("Day", [0.75, 0.85, 0.95]),
+    ("Haze", [0.8, 0.8, 0.76]),
+    ("Dusk", [0.95, 0.6, 0.45]),
+    ("Night", [0.05, 0.07, 0.15]),
+    ("Black", [0.0, 0.0, 0.0]),
+];
+
+/// Degrees one menu step turns or tilts the sun by.
+pub const SUN_ANGLE_STEP: f32 = 15.0;
+
+/// Lowest and highest sun elevation in degrees; lower suns cast longer
+/// shadows.
+pub const SUN_ELEVATIONS: std::ops::RangeInclusive<f32> = 5.0..=90.0;
+
+/// Sunlight illuminance levels in lux selectable in the menu; the middle one
+/// is Bevy's default.
+pub const SUN_ILLUMINANCES: [f32; 5] = [2_500.0, 5_000.0, 10_000.0, 20_000.0, 40_000.0];
+
+/// Performance tier setting a consistent group of display and view values
+/// at once, from frame rate first (`Low`) to looks first (`High`).
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum QualityPreset {
+    Low,
+    Medium,
+    High,
+}
+
+impl QualityPreset {
+    pub const ALL: [QualityPreset; 3] = [
+        QualityPreset::Low,
+        QualityPreset::Medium,
+        QualityPreset::High,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            QualityPreset::Low => "Low",
+            QualityPreset::Medium => "Medium",
+            QualityPreset::High => "High",
+        }
+    }
+
+    /// View width in chunks and full-detail radius in blocks of the tier,
+    /// in the units of the [`crate::world::WorldParams`] defaults, which
+    /// `High` matches; see [`crate::world::WorldParams::apply_quality`].
+    pub fn distances(self) -> (i32, i32) {
+        match self {
+            QualityPreset::Low => (4, 64),
+            QualityPreset::Medium => (12, 128),
+            QualityPreset::High => (24, 192),
+        }
+    }
+
+    /// Writes the tier's display values into `display` and records it as
+    /// the chosen tier.
+    pub fn apply(self, display: &mut DisplaySettings) {
+        let (msaa, shadows) = match self {
+            QualityPreset::Low => (1, false),
+            QualityPreset::Medium => (2, true),
+            QualityPreset::High => (4, true),
+        };
+        display.msaa = msaa;
+        display.shadows = shadows;
+        display.quality = Some(self);
+    }
+}
+
+/// Gradient noise a terrain layer samples.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum LayerNoise {
+    #[default]
+    Perlin,
+    /// Smoother noise without Perlin's faint grid-aligned ridges.
+    OpenSimplex2S,
+}
+
+/// Most octaves a terrain layer may stack, see [`LayerFractal::octaves`].
+pub const MAX_OCTAVES: i32 = 8;
+
+/// How the octaves of a fractal terrain layer combine.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum FractalMode {
+    /// Fractional Brownian motion: finer octaves add detail to the rolling
+    /// base shape.
+    #[default]
+    Fbm,
+    /// Sharp crests where the noise crosses zero, for mountain chains.
+    Ridged,
+    /// Terraced bands folded back and forth.
+    PingPong,
+}
+
+/// Fractal octave stacking of a terrain layer's noise. One octave, the
+/// default, samples the plain noise.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(default)]
+pub struct LayerFractal {
+    pub mode: FractalMode,
+    /// Noise octaves summed, each at a higher frequency and lower amplitude
+    /// than the last; at most [`MAX_OCTAVES`].
+    pub octaves: i32,
+    /// Amplitude of each octave relative to the previous one.
+    pub gain: f32,
+    /// Frequency of each octave relative to the previous one.
+    pub lacunarity: f32,
+}
+
+impl Default for LayerFractal {
+    fn default() -> Self {
+        LayerFractal {
+            mode: FractalMode::Fbm,
+            octaves: 1,
+            gain: 0.5,
+            lacunarity: 2.0,
+        }
+    }
+}
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct NoiseLayer {
     pub seed: i32,
     pub frequency: f32,
     pub amplitude: f32,
+    #[serde(default)]
+    pub noise: LayerNoise,
+    #[serde(default)]
+    pub fractal: LayerFractal,
+}
+
+/// How the ridged cliff noise combines with the layered terrain.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum RidgeMode {
+    /// Ridges add the same cliff height on every column.
+    #[default]
+    Additive,
+    /// Ridges grow with the height the layers lift a column to, so cliffs
+    /// rise on mountains while the lowlands stay smooth.
+    Multiplicative,
+}
+
+/// Domain warping of the terrain layers: their sample positions are pushed
+/// around by another noise first, bending straight ridges into swirls.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct WarpSettings {
+    pub seed: i32,
+    /// Frequency of the warp noise; lower values produce wider swirls.
+    pub frequency: f32,
+    /// Farthest a sample position is displaced, in blocks; zero disables
+    /// warping.
+    pub strength: f32,
+}
+
+impl Default for WarpSettings {
+    fn default() -> Self {
+        Self {
+            seed: 41,
+            frequency: 0.004,
+            strength: 0.0,
+        }
+    }
+}
+
+/// Rivers carved along the zero crossings of a low-frequency noise.
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct RiverSettings {
+    pub seed: i32,
+    /// Frequency of the river noise; lower values produce longer bends.
+    pub frequency: f32,
+    /// Half width of the channels in noise units; zero disables rivers.
+    pub width: f32,
+    /// Depth of the channel centre below [`crate::world::SEA_LEVEL`].
+    pub depth: i32,
+}
+
+impl Default for RiverSettings {
+    fn default() -> Self {
+        Self {
+            seed: 31,
+            frequency: 0.002,
+            width: 0.04,
+            depth: 3,
+        }
+    }
 }
 
 #[derive(Resource, Serialize, Deserialize, Clone)]
 pub struct NoiseSettings {
+    /// World seed added to the seed of every noise generator.
+    #[serde(default)]
+    pub seed: i32,
     pub layers: [NoiseLayer; 5],
+    /// Blocks along x and z by which every noise is sampled shifted, panning
+    /// the terrain of the same seed past the world origin. At most
+    /// [`crate::world::MAX_OFFSET`] either way.
+    #[serde(default)]
+    pub offset: [i32; 2],
+    /// Terrain height before any noise layer is added. It places the ground
+    /// relative to [`crate::world::SEA_LEVEL`]: lower values flood more land.
+    #[serde(default = "default_base_height")]
+    pub base_height: i32,
+    /// Height the ridged cliff noise adds at most, at a biome ridge weight of
+    /// one; zero leaves smooth hills. At most [`crate::world::MAX_RIDGE_HEIGHT`].
+    #[serde(default = "default_ridge_height")]
+    pub ridge_height: f32,
+    #[serde(default)]
+    pub ridge_mode: RidgeMode,
+    #[serde(default)]
+    pub biome: BiomeSettings,
+    /// Fraction of grass surface blocks carrying a grass tuft or flower.
+    #[serde(default = "default_decoration_density")]
+    pub decoration_density: f32,
+    /// Generates a flat grass, dirt and stone world instead of noise terrain.
+    #[serde(default)]
+    pub superflat: bool,
+    /// Scales the layer amplitudes down so the highest possible terrain fits
+    /// inside the world instead of being cut off into plateaus.
+    #[serde(default)]
+    pub normalize_height: bool,
+    #[serde(default)]
+    pub rivers: RiverSettings,
+    #[serde(default)]
+    pub warp: WarpSettings,
+    /// Decides solid voxels from a height-biased 3D density field, producing
+    /// overhangs and arches a single surface height per column cannot.
+    #[serde(default)]
+    pub overhangs: bool,
+    /// How far grass and leaves tint towards dry yellow or lush green across
+    /// regions; zero keeps the uniform colors.
+    #[serde(default = "default_foliage_variation")]
+    pub foliage_variation: f32,
+    /// Fills caves up to [`NoiseSettings::lava_level`] with lava.
+    #[serde(default)]
+    pub lava: bool,
+    /// World height up to which carved caves hold lava.
+    #[serde(default = "default_lava_level")]
+    pub lava_level: i32,
+    /// Carves caves, and with them lava pools, out of the terrain.
+    #[serde(default = "default_true")]
+    pub caves: bool,
+    /// Keeps the bottom layer of the world solid, so caves never open into
+    /// the void below it.
+    #[serde(default = "default_true")]
+    pub bedrock: bool,
+    /// Grows trees; off skips the pass regardless of the tree density.
+    #[serde(default = "default_true")]
+    pub trees: bool,
+    /// Places boulders; off skips the pass regardless of the boulder density.
+    #[serde(default = "default_true")]
+    pub boulders: bool,
+    /// Scale of the biome tree density; zero grows no trees at all.
+    #[serde(default = "default_tree_density")]
+    pub tree_density: f32,
+    /// Scale of the biome boulder density; zero places no boulders.
+    #[serde(default = "default_boulder_density")]
+    pub boulder_density: f32,
+    /// Smallest distance in blocks between the bases of two trees or
+    /// boulders; five keeps neighbouring canopies from touching.
+    #[serde(default = "default_feature_spacing")]
+    pub feature_spacing: i32,
+    /// Averages the normals of faces meeting at a corner for soft shading
+    /// instead of the faceted block look.
+    #[serde(default)]
+    pub smooth_normals: bool,
+    /// Merges neighbouring faces of the same block into larger quads. When
+    /// off every exposed block face gets its own quad, which shows the
+    /// voxel grid and tells greedy merging artifacts apart from others.
+    #[serde(default = "default_true")]
+    pub greedy_meshing: bool,
+    /// Shares vertices that quads meeting at a corner have in common,
+    /// shrinking the chunk vertex buffers at some meshing cost.
+    #[serde(default)]
+    pub weld_vertices: bool,
+}
+
+/// Noise settings as last loaded from or written to the settings file, to
+/// tell whether leaving would lose changes.
+#[derive(Resource)]
+pub struct SavedNoise(serde_json::Value);
+
+impl FromWorld for SavedNoise {
+    fn from_world(world: &mut World) -> Self {
+        SavedNoise::new(world.resource::<NoiseSettings>())
+    }
+}
+
+impl SavedNoise {
+    pub fn new(settings: &NoiseSettings) -> Self {
+        SavedNoise(serde_json::to_value(settings).unwrap_or_default())
+    }
+
+    /// Whether `settings` differ from the saved snapshot.
+    pub fn differs(&self, settings: &NoiseSettings) -> bool {
+        serde_json::to_value(settings).unwrap_or_default() != self.0
+    }
+
+    /// The saved settings, to discard the changes made since.
+    pub fn restore(&self) -> Option<NoiseSettings> {
+        serde_json::from_value(self.0.clone()).ok()
+    }
+}
+
+/// Reads and parses the settings file, logging why `what` falls back to its
+/// defaults when that fails.
+fn load_settings<T: DeserializeOwned>(what: &str) -> Option<T> {
+    read_settings(settings_path(), what)
+}
+
+fn read_settings<T: DeserializeOwned>(path: &Path, what: &str) -> Option<T> {
+    let data = match fs::read_to_string(path) {
+        Ok(data) => data,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            info!("{} not found, using default {what}", path.display());
+            return None;
+        }
+        Err(err) => {
+            warn!(
+                "could not read {}: {err}; using default {what}",
+                path.display()
+            );
+            return None;
+        }
+    };
+    match serde_json::from_str(&data) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            warn!(
+                "invalid {what} in {}: {err}; using defaults",
+                path.display()
+            );
+            None
+        }
+    }
+}
+
+fn default_base_height() -> i32 {
+    40
+}
+
+fn default_ridge_height() -> f32 {
+    20.0
+}
+
+fn default_decoration_density() -> f32 {
+    0.15
+}
+
+fn default_foliage_variation() -> f32 {
+    0.5
+}
+
+fn default_lava_level() -> i32 {
+    16
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_tree_density() -> f32 {
+    0.5
+}
+
+fn default_boulder_density() -> f32 {
+    0.3
+}
+
+fn default_feature_spacing() -> i32 {
+    5
 }
 
 impl Default for NoiseSettings {
     fn default() -> Self {
-        if let Ok(data) = fs::read_to_string("settings.json") {
-            if let Ok(cfg) = serde_json::from_str::<NoiseSettings>(&data) {
-                return cfg;
-            }
-        }
+        load_settings("noise settings").unwrap_or_else(NoiseSettings::fallback)
+    }
+}
+
+impl NoiseSettings {
+    /// Built-in settings used when the settings file is missing or invalid.
+    pub(crate) fn fallback() -> Self {
         NoiseSettings {
+            seed: 0,
             layers: [
                 NoiseLayer {
                     seed: 0,
                     frequency: 0.01,
                     amplitude: 10.0,
+                    noise: LayerNoise::Perlin,
+                    fractal: LayerFractal::default(),
                 },
                 NoiseLayer {
                     seed: 1,
                     frequency: 0.03,
                     amplitude: 5.0,
+                    noise: LayerNoise::Perlin,
+                    fractal: LayerFractal::default(),
                 },
                 NoiseLayer {
                     seed: 2,
                     frequency: 0.08,
                     amplitude: 2.0,
+                    noise: LayerNoise::Perlin,
+                    fractal: LayerFractal::default(),
                 },
                 NoiseLayer {
                     seed: 4,
                     frequency: 0.16,
                     amplitude: 1.0,
+                    noise: LayerNoise::Perlin,
+                    fractal: LayerFractal::default(),
                 },
                 NoiseLayer {
                     seed: 5,
                     frequency: 0.32,
                     amplitude: 0.5,
+                    noise: LayerNoise::Perlin,
+                    fractal: LayerFractal::default(),
                 },
             ],
+            offset: [0, 0],
+            base_height: default_base_height(),
+            ridge_height: default_ridge_height(),
+            ridge_mode: RidgeMode::Additive,
+            biome: BiomeSettings::default(),
+            decoration_density: default_decoration_density(),
+            superflat: false,
+            normalize_height: false,
+            rivers: RiverSettings::default(),
+            warp: WarpSettings::default(),
+            overhangs: false,
+            foliage_variation: default_foliage_variation(),
+            lava: false,
+            lava_level: default_lava_level(),
+            caves: true,
+            bedrock: true,
+            trees: true,
+            boulders: true,
+            tree_density: default_tree_density(),
+            boulder_density: default_boulder_density(),
+            feature_spacing: default_feature_spacing(),
+            smooth_normals: false,
+            greedy_meshing: true,
+            weld_vertices: false,
+        }
+    }
+
+    /// Multiplies every layer amplitude by `factor`, keeping their balance.
+    pub fn scale_amplitudes(&mut self, factor: f32) {
+        for layer in &mut self.layers {
+            layer.amplitude = (layer.amplitude * factor).max(0.0);
+        }
+    }
+
+    /// Multiplies every layer frequency by `factor`, keeping their balance.
+    pub fn scale_frequencies(&mut self, factor: f32) {
+        for layer in &mut self.layers {
+            layer.frequency = (layer.frequency * factor).max(0.0);
         }
     }
 }
 
-impl NoiseSettings {
-    pub fn save(&self) {
-        if let Ok(json) = serde_json::to_string_pretty(self) {
-            let _ = fs::write("settings.json", json);
+/// Persisted window presentation settings, stored under `display` in
+/// `settings.json` next to the noise settings.
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default = "DisplaySettings::fallback")]
+pub struct DisplaySettings {
+    pub vsync: bool,
+    /// Upper bound on frames per second, enforced by a frame-time limiter.
+    pub fps_cap: Option<u32>,
+    /// MSAA sample count, one of [`MSAA_SAMPLES`].
+    pub msaa: u32,
+    /// Whether the sun casts shadows onto the terrain.
+    pub shadows: bool,
+    /// Multiplier of the terrain color and the ambient light, one of
+    /// [`BRIGHTNESS_LEVELS`].
+    pub brightness: f32,
+    /// Tier last picked in the menu, applied again at startup; `None` once
+    /// one of its values is changed by hand.
+    pub quality: Option<QualityPreset>,
+    /// Threads generating chunks in the background; `None` uses Bevy's
+    /// default of a quarter of the cores, at most four.
+    ///
+    /// More threads fill in the terrain faster on many-core machines, but
+    /// past the number of spare cores they compete with the main and render
+    /// threads and cost frame rate instead.
+    pub compute_threads: Option<usize>,
+    /// Window clear color in sRGB, seen behind the menu and wherever the
+    /// sky dome leaves the background bare. The sky dome fades from it at
+    /// the horizon.
+    pub sky_color: [f32; 3],
+    /// Compass bearing of the sun in degrees, clockwise from north (-Z).
+    pub sun_azimuth: f32,
+    /// Height of the sun above the horizon in degrees, within
+    /// [`SUN_ELEVATIONS`].
+    pub sun_elevation: f32,
+    /// Sunlight illuminance in lux, one of [`SUN_ILLUMINANCES`].
+    pub sun_illuminance: f32,
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        #[derive(Deserialize)]
+        struct File {
+            #[serde(default)]
+            display: Option<DisplaySettings>,
+        }
+
+        if let Some(File {
+            display: Some(display),
+        }) = load_settings("display settings")
+        {
+            return display;
+        }
+        DisplaySettings::fallback()
+    }
+}
+
+impl DisplaySettings {
+    /// Built-in settings used for missing entries.
+    ///
+    /// Kept apart from `Default`, which reads the settings file and would
+    /// recurse when used to fill in fields while deserializing it.
+    fn fallback() -> Self {
+        DisplaySettings {
+            vsync: true,
+            fps_cap: None,
+            msaa: 4,
+            shadows: true,
+            brightness: 1.0,
+            quality: None,
+            compute_threads: None,
+            sky_color: SKY_COLORS[0].1,
+            // roughly where the fixed sun used to stand, high in the south-east
+            sun_azimuth: 135.0,
+            sun_elevation: 55.0,
+            sun_illuminance: SUN_ILLUMINANCES[2],
+        }
+    }
+
+    /// Unit vector from the ground towards the sun.
+    pub fn sun_direction(&self) -> Vec3 {
+        let (azimuth, elevation) = (
+            self.sun_azimuth.to_radians(),
+            self.sun_elevation.to_radians(),
+        );
+        Vec3::new(
+            azimuth.sin() * elevation.cos(),
+            elevation.sin(),
+            -azimuth.cos() * elevation.cos(),
+        )
+    }
+}
+
+/// Persisted walking and flying physics, stored under `physics` in
+/// `settings.json`.
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default = "PhysicsSettings::fallback")]
+pub struct PhysicsSettings {
+    /// Downward acceleration while walking, in blocks per second squared.
+    pub gravity: f32,
+    /// Fastest falling speed in blocks per second.
+    pub terminal_velocity: f32,
+    /// Upward speed of a jump in blocks per second.
+    pub jump_speed: f32,
+    /// Simulation steps per second of the `FixedUpdate` schedule that moves
+    /// the player, independent of the frame rate.
+    pub tick_rate: f32,
+    /// Multiplier of the flying speed along the ground.
+    pub horizontal_speed: f32,
+    /// Multiplier of the flying speed up and down; below one makes altitude
+    /// easier to set precisely without slowing down travel.
+    pub vertical_speed: f32,
+}
+
+impl Default for PhysicsSettings {
+    fn default() -> Self {
+        #[derive(Deserialize)]
+        struct File {
+            #[serde(default)]
+            physics: Option<PhysicsSettings>,
+        }
+
+        if let Some(File {
+            physics: Some(physics),
+        }) = load_settings("physics settings")
+        {
+            return physics;
+        }
+        PhysicsSettings::fallback()
+    }
+}
+
+impl PhysicsSettings {
+    /// Built-in settings used for missing entries, see
+    /// [`DisplaySettings::fallback`].
+    pub(crate) fn fallback() -> Self {
+        PhysicsSettings {
+            gravity: 25.0,
+            terminal_velocity: 50.0,
+            jump_speed: 8.0,
+            tick_rate: 64.0,
+            horizontal_speed: 1.0,
+            vertical_speed: 1.0,
+        }
+    }
+}
+
+/// Persisted mouse look, stored under `look` in `settings.json`.
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default = "LookSettings::fallback")]
+pub struct LookSettings {
+    /// Radians the camera turns per pixel of mouse motion.
+    pub sensitivity: f32,
+    /// Steepest angle, in radians, the camera may look up or down, at most
+    /// [`crate::player::MAX_PITCH`].
+    pub pitch_limit: f32,
+}
+
+impl Default for LookSettings {
+    fn default() -> Self {
+        #[derive(Deserialize)]
+        struct File {
+            #[serde(default)]
+            look: Option<LookSettings>,
+        }
+
+        if let Some(File { look: Some(look) }) = load_settings("look settings") {
+            return look;
+        }
+        LookSettings::fallback()
+    }
+}
+
+impl LookSettings {
+    /// Built-in settings used for missing entries, see
+    /// [`DisplaySettings::fallback`].
+    fn fallback() -> Self {
+        LookSettings {
+            sensitivity: 0.002,
+            pitch_limit: 1.54,
+        }
+    }
+}
+
+/// Logical input actions that can be rebound in `settings.json`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Action {
+    MoveForward,
+    MoveBack,
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    /// Held with a movement key to skip the speed ramp.
+    InstantSpeed,
+    /// Switches between flying and walking on the terrain.
+    ToggleWalk,
+    TeleportToSpawn,
+    SetSpawn,
+    Pause,
+    ReturnToMenu,
+    ToggleHud,
+    ToggleDebug,
+    FreezeChunks,
+    /// Regenerates the targeted chunk, or the one the camera is in.
+    RegenerateChunk,
+    /// Outlines the loaded chunks, colored by level of detail.
+    ToggleChunkBorders,
+    ToggleMinimap,
+    /// Shows the scrolling frame-time graph.
+    ToggleFrameGraph,
+    /// Switches rendering to the third-person drone following the player.
+    ToggleDrone,
+    DroneOrbitLeft,
+    DroneOrbitRight,
+    /// Copies the world seed to the clipboard from the menu or pause screen.
+    CopySeed,
+    SaveSettings,
+    /// Points the sun along the camera, see [`crate::display::Headlamp`].
+    ToggleHeadlamp,
+}
+
+impl Action {
+    pub const ALL: [Action; 25] = [
+        Action::MoveForward,
+        Action::MoveBack,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::InstantSpeed,
+        Action::ToggleWalk,
+        Action::TeleportToSpawn,
+        Action::SetSpawn,
+        Action::Pause,
+        Action::ReturnToMenu,
+        Action::ToggleHud,
+        Action::ToggleDebug,
+        Action::FreezeChunks,
+        Action::RegenerateChunk,
+        Action::ToggleChunkBorders,
+        Action::ToggleMinimap,
+        Action::ToggleFrameGraph,
+        Action::ToggleDrone,
+        Action::DroneOrbitLeft,
+        Action::DroneOrbitRight,
+        Action::CopySeed,
+        Action::SaveSettings,
+        Action::ToggleHeadlamp,
+    ];
+
+    fn default_key(self) -> KeyCode {
+        match self {
+            Action::MoveForward => KeyCode::KeyW,
+            Action::MoveBack => KeyCode::KeyS,
+            Action::MoveLeft => KeyCode::KeyA,
+            Action::MoveRight => KeyCode::KeyD,
+            Action::MoveUp => KeyCode::Space,
+            Action::MoveDown => KeyCode::ShiftLeft,
+            Action::InstantSpeed => KeyCode::ControlLeft,
+            Action::ToggleWalk => KeyCode::KeyG,
+            Action::TeleportToSpawn => KeyCode::KeyR,
+            Action::SetSpawn => KeyCode::KeyT,
+            Action::Pause => KeyCode::Escape,
+            Action::ReturnToMenu => KeyCode::KeyP,
+            Action::ToggleHud => KeyCode::F1,
+            Action::ToggleDebug => KeyCode::F3,
+            Action::FreezeChunks => KeyCode::F4,
+            Action::RegenerateChunk => KeyCode::F5,
+            Action::ToggleChunkBorders => KeyCode::F6,
+            Action::ToggleMinimap => KeyCode::KeyM,
+            Action::ToggleFrameGraph => KeyCode::F7,
+            Action::ToggleDrone => KeyCode::KeyV,
+            Action::DroneOrbitLeft => KeyCode::KeyQ,
+            Action::DroneOrbitRight => KeyCode::KeyE,
+            Action::CopySeed => KeyCode::KeyC,
+            Action::SaveSettings => KeyCode::KeyL,
+            Action::ToggleHeadlamp => KeyCode::F8,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::MoveForward => "Move Forward",
+            Action::MoveBack => "Move Back",
+            Action::MoveLeft => "Move Left",
+            Action::MoveRight => "Move Right",
+            Action::MoveUp => "Move Up",
+            Action::MoveDown => "Move Down",
+            Action::InstantSpeed => "Instant Max Speed",
+            Action::ToggleWalk => "Toggle Walking",
+            Action::TeleportToSpawn => "Teleport to Spawn",
+            Action::SetSpawn => "Set Spawn",
+            Action::Pause => "Pause",
+            Action::ReturnToMenu => "Return to Menu",
+            Action::ToggleHud => "Toggle HUD",
+            Action::ToggleDebug => "Toggle Debug",
+            Action::FreezeChunks => "Freeze Chunks",
+            Action::RegenerateChunk => "Regenerate Chunk",
+            Action::ToggleChunkBorders => "Chunk Borders",
+            Action::ToggleMinimap => "Toggle Minimap",
+            Action::ToggleFrameGraph => "Frame Graph",
+            Action::ToggleDrone => "Drone Camera",
+            Action::DroneOrbitLeft => "Drone Orbit Left",
+            Action::DroneOrbitRight => "Drone Orbit Right",
+            Action::CopySeed => "Copy Seed",
+            Action::SaveSettings => "Save Settings",
+            Action::ToggleHeadlamp => "Headlamp",
         }
     }
 }
+
+/// Keys bound to each [`Action`], stored under `keys` in `settings.json` as
+/// action names mapped to `KeyCode` variant names such as `"KeyW"`.
+///
+/// Missing entries and names that are not a known action or key fall back
+/// to the default binding.
+#[derive(Resource, Clone)]
+pub struct KeyBindings {
+    keys: HashMap<Action, KeyCode>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        #[derive(Deserialize)]
+        struct File {
+            #[serde(default)]
+            keys: Option<KeyBindings>,
+        }
+
+        if let Some(File { keys: Some(keys) }) = load_settings("key bindings") {
+            return keys;
+        }
+        KeyBindings {
+            keys: HashMap::new(),
+        }
+    }
+}
+
+impl KeyBindings {
+    pub fn key(&self, action: Action) -> KeyCode {
+        self.keys
+            .get(&action)
+            .copied()
+            .unwrap_or_else(|| action.default_key())
+    }
+}
+
+impl Serialize for KeyBindings {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(
+            Action::ALL
+                .iter()
+                .map(|action| (format!("{action:?}"), format!("{:?}", self.key(*action)))),
+        )
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyBindings {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let names = HashMap::<String, String>::deserialize(deserializer)?;
+        let keys = names
+            .iter()
+            .filter_map(|(action, key)| {
+                let action = Action::ALL
+                    .into_iter()
+                    .find(|a| format!("{a:?}") == *action)?;
+                Some((action, key_from_name(key)?))
+            })
+            .collect();
+        Ok(KeyBindings { keys })
+    }
+}
+
+/// Parses the name of a field-less `KeyCode` variant, e.g. `"ShiftLeft"`.
+fn key_from_name(name: &str) -> Option<KeyCode> {
+    // `from_reflect` panics on variants that do not exist
+    let TypeInfo::Enum(info) = KeyCode::type_info() else {
+        return None;
+    };
+    if !matches!(info.variant(name), Some(VariantInfo::Unit(_))) {
+        return None;
+    }
+    KeyCode::from_reflect(&DynamicEnum::new(name, DynamicVariant::Unit))
+}
+
+/// Short human readable name of `key`, e.g. `W` for `KeyCode::KeyW`.
+pub fn key_label(key: KeyCode) -> String {
+    let name = format!("{key:?}");
+    match name
+        .strip_prefix("Key")
+        .or_else(|| name.strip_prefix("Digit"))
+    {
+        Some(short) if !short.is_empty() => short.to_string(),
+        _ => name,
+    }
+}
+
+/// Writes every persisted setting to the [`settings_path`] file.
+///
+/// Noise settings stay at the top level so older files keep loading.
+pub fn save_settings(
+    noise: &NoiseSettings,
+    display: &DisplaySettings,
+    physics: &PhysicsSettings,
+    look: &LookSettings,
+    keys: &KeyBindings,
+) -> io::Result<()> {
+    #[derive(Serialize)]
+    struct File<'a> {
+        #[serde(flatten)]
+        noise: &'a NoiseSettings,
+        display: &'a DisplaySettings,
+        physics: &'a PhysicsSettings,
+        look: &'a LookSettings,
+        keys: &'a KeyBindings,
+    }
+
+    let json = serde_json::to_string_pretty(&File {
+        noise,
+        display,
+        physics,
+        look,
+        keys,
+    })?;
+    write_atomic(settings_path(), &json)
+}
+
+/// Writes `contents` to a sibling temp file and renames it over `path`.
+///
+/// The rename replaces the file in one step, so a crash or a full disk
+/// mid-write leaves the previous file intact instead of a truncated one.
+pub fn write_atomic(path: &Path, contents: &str) -> io::Result<()> {
+    write_atomic_with(path, |file| file.write_all(contents.as_bytes()))
+}
+
+/// [`write_atomic`] with the temp file's contents written by `write`.
+fn write_atomic_with(
+    path: &Path,
+    write: impl FnOnce(&mut fs::File) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    let result = fs::File::create(&tmp).and_then(|mut file| {
+        write(&mut file)?;
+        file.sync_all()
+    });
+    match result {
+        Ok(()) => fs::rename(&tmp, path),
+        Err(err) => {
+            let _ = fs::remove_file(&tmp);
+            Err(err)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_bindings_round_trip_and_fall_back() {
+        let json = r#"{"MoveForward": "ArrowUp", "Pause": "NotAKey", "Fly": "KeyF"}"#;
+        let keys: KeyBindings = serde_json::from_str(json).unwrap();
+        assert_eq!(keys.key(Action::MoveForward), KeyCode::ArrowUp);
+        assert_eq!(keys.key(Action::Pause), KeyCode::Escape);
+        assert_eq!(keys.key(Action::MoveBack), KeyCode::KeyS);
+
+        let saved = serde_json::to_string(&keys).unwrap();
+        let reloaded: KeyBindings = serde_json::from_str(&saved).unwrap();
+        assert!(Action::ALL.iter().all(|a| reloaded.key(*a) == keys.key(*a)));
+        assert_eq!(key_label(KeyCode::KeyW), "W");
+        assert_eq!(key_label(KeyCode::ShiftLeft), "ShiftLeft");
+    }
+
+    /// Serializes `value` and parses it back, asserting nothing was lost.
+    fn round_trip<T: Serialize + DeserializeOwned>(value: &T) -> T {
+        let json = serde_json::to_value(value).unwrap();
+        let parsed: T = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), json);
+        parsed
+    }
+
+    #[test]
+    fn settings_round_trip_through_json() {
+        let mut noise = NoiseSettings::fallback();
+        noise.seed = 7;
+        noise.superflat = true;
+        noise.lava_level = -3;
+        noise.rivers.width = 0.1;
+        noise.layers[2].amplitude = 42.0;
+        let parsed = round_trip(&noise);
+        assert_eq!(parsed.seed, 7);
+        assert_eq!(parsed.layers[2].amplitude, 42.0);
+
+        let display = DisplaySettings {
+            fps_cap: Some(144),
+            brightness: 1.5,
+            sky_color: [0.1, 0.2, 0.3],
+            sun_elevation: 20.0,
+            ..DisplaySettings::fallback()
+        };
+        assert!(round_trip(&display) == display);
+        let physics = PhysicsSettings {
+            tick_rate: 120.0,
+            vertical_speed: 0.25,
+            ..PhysicsSettings::fallback()
+        };
+        assert!(round_trip(&physics) == physics);
+        let look = LookSettings {
+            pitch_limit: 1.2,
+            ..LookSettings::fallback()
+        };
+        assert!(round_trip(&look) == look);
+    }
+
+    #[test]
+    fn saved_snapshot_tracks_unsaved_noise_changes() {
+        let mut noise = NoiseSettings::fallback();
+        let mut saved = SavedNoise::new(&noise);
+        assert!(!saved.differs(&noise));
+        noise.layers[1].frequency *= 2.0;
+        assert!(saved.differs(&noise));
+        // discarding brings back the saved values
+        assert!(!saved.differs(&saved.restore().unwrap()));
+        saved = SavedNoise::new(&noise);
+        assert!(!saved.differs(&noise));
+        // a value changed and changed back is nothing to save
+        noise.seed += 1;
+        noise.seed -= 1;
+        assert!(!saved.differs(&noise));
+    }
+
+    #[test]
+    fn older_settings_fill_in_missing_fields() {
+        // a settings file from before any optional field existed
+        let layers = r#"{"layers": [
+            {"seed": 0, "frequency": 0.01, "amplitude": 10.0},
+            {"seed": 1, "frequency": 0.03, "amplitude": 5.0},
+            {"seed": 2, "frequency": 0.08, "amplitude": 2.0},
+            {"seed": 4, "frequency": 0.16, "amplitude": 1.0},
+            {"seed": 5, "frequency": 0.32, "amplitude": 0.5}
+        ]}"#;
+        let noise: NoiseSettings = serde_json::from_str(layers).unwrap();
+        let fallback = NoiseSettings::fallback();
+        assert_eq!(
+            serde_json::to_value(&noise).unwrap(),
+            serde_json::to_value(&fallback).unwrap()
+        );
+
+        let display: DisplaySettings = serde_json::from_str(r#"{"vsync": false}"#).unwrap();
+        assert!(!display.vsync);
+        assert_eq!(display.msaa, DisplaySettings::fallback().msaa);
+        assert_eq!(display.brightness, 1.0);
+        assert_eq!(display.quality, None);
+        assert_eq!(display.sky_color, SKY_COLORS[0].1);
+        assert_eq!(display.sun_illuminance, SUN_ILLUMINANCES[2]);
+        let physics: PhysicsSettings = serde_json::from_str(r#"{"gravity": 10.0}"#).unwrap();
+        assert_eq!(physics.gravity, 10.0);
+        assert_eq!(physics.tick_rate, PhysicsSettings::fallback().tick_rate);
+        assert_eq!(physics.vertical_speed, 1.0);
+    }
+
+    #[test]
+    fn quality_presets_trade_detail_for_speed() {
+        let tier = |preset: QualityPreset| {
+            let mut display = DisplaySettings::fallback();
+            preset.apply(&mut display);
+            assert_eq!(display.quality, Some(preset));
+            display
+        };
+        let [low, medium, high] = QualityPreset::ALL.map(QualityPreset::distances);
+        assert!(low.0 < medium.0 && medium.0 < high.0);
+        assert!(low.1 < medium.1 && medium.1 < high.1);
+        let (low_display, high_display) = (tier(QualityPreset::Low), tier(QualityPreset::High));
+        assert!(!low_display.shadows && low_display.msaa == 1);
+
+        // the top tier is the out-of-the-box look
+        assert_eq!(high_display.msaa, DisplaySettings::fallback().msaa);
+
+        let saved = serde_json::to_string(&high_display).unwrap();
+        let loaded: DisplaySettings = serde_json::from_str(&saved).unwrap();
+        assert_eq!(loaded.quality, Some(QualityPreset::High));
+    }
+
+    #[test]
+    fn sun_direction_follows_azimuth_and_elevation() {
+        let sun = |sun_azimuth, sun_elevation| {
+            DisplaySettings {
+                sun_azimuth,
+                sun_elevation,
+                ..DisplaySettings::fallback()
+            }
+            .sun_direction()
+        };
+        // north is -Z and east +X, like the compass
+        assert!(sun(0.0, 0.0).abs_diff_eq(Vec3::NEG_Z, 1e-5));
+        assert!(sun(90.0, 0.0).abs_diff_eq(Vec3::X, 1e-5));
+        assert!(sun(200.0, 90.0).abs_diff_eq(Vec3::Y, 1e-5));
+        // a lower sun leans further away from the zenith
+        assert!(sun(135.0, 20.0).y < sun(135.0, 55.0).y);
+        assert!((sun(135.0, 20.0).length() - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn unreadable_settings_files_fall_back() {
+        let dir = std::env::temp_dir().join(format!("rube-garbage-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("settings.json");
+        assert!(read_settings::<NoiseSettings>(&path, "noise settings").is_none());
+
+        fs::write(&path, "{\"layers\": [1, 2], \u{0}garbage").unwrap();
+        assert!(read_settings::<NoiseSettings>(&path, "noise settings").is_none());
+        assert!(read_settings::<PhysicsSettings>(&path, "physics settings").is_none());
+        // wrong types for a known field are rejected rather than half applied
+        fs::write(&path, r#"{"vsync": "yes"}"#).unwrap();
+        assert!(read_settings::<DisplaySettings>(&path, "display settings").is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn settings_path_prefers_argument_over_environment() {
+        let args = |list: &[&str]| {
+            list.iter()
+                .map(|a| a.to_string())
+                .collect::<Vec<_>>()
+                .into_iter()
+        };
+        let env = Some(OsString::from("env.json"));
+        assert_eq!(
+            resolve_settings_path(args(&["--settings", "a.json"]), env.clone()),
+            PathBuf::from("a.json")
+        );
+        assert_eq!(
+            resolve_settings_path(args(&["--settings=b.json"]), env.clone()),
+            PathBuf::from("b.json")
+        );
+        assert_eq!(
+            resolve_settings_path(args(&[]), env),
+            PathBuf::from("env.json")
+        );
+        assert_eq!(
+            resolve_settings_path(args(&["--settings"]), None),
+            PathBuf::from(DEFAULT_SETTINGS_PATH)
+        );
+    }
+
+    #[test]
+    fn compute_threads_prefer_argument_over_settings() {
+        let args = |list: &[&str]| {
+            list.iter()
+                .map(|a| a.to_string())
+                .collect::<Vec<_>>()
+                .into_iter()
+        };
+        assert_eq!(resolve_compute_threads(args(&[]), None), None);
+        assert_eq!(resolve_compute_threads(args(&[]), Some(3)), Some(3));
+        assert_eq!(
+            resolve_compute_threads(args(&["--compute-threads", "6"]), Some(3)),
+            Some(6)
+        );
+        assert_eq!(
+            resolve_compute_threads(args(&["--compute-threads=2"]), None),
+            Some(2)
+        );
+        // unparsable values are ignored and the count stays in range
+        assert_eq!(
+            resolve_compute_threads(args(&["--compute-threads", "many"]), Some(3)),
+            Some(3)
+        );
+        assert_eq!(
+            resolve_compute_threads(args(&["--compute-threads=0"]), None),
+            Some(1)
+        );
+        assert_eq!(
+            resolve_compute_threads(args(&["--compute-threads=1000"]), None),
+            Some(MAX_COMPUTE_THREADS)
+        );
+    }
+
+    #[test]
+    fn interrupted_save_keeps_the_original_file() {
+        let dir = std::env::temp_dir().join(format!("rube-save-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("settings.json");
+        fs::write(&path, "original").unwrap();
+
+        // the writer fails after putting half the contents down, like a full disk
+        let tmp = dir.join("settings.json.tmp");
+        let result = write_atomic_with(&path, |file| {
+            file.write_all(b"repla")?;
+            Err(io::Error::new(io::ErrorKind::StorageFull, "disk full"))
+        });
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::StorageFull);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "original");
+        assert!(!tmp.exists());
+
+        write_atomic(&path, "replacement").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "replacement");
+        assert!(!tmp.exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,95 @@
+use bevy::pbr::{NotShadowCaster, NotShadowReceiver};
+use bevy::prelude::*;
+use bevy::render::mesh::VertexAttributeValues;
+
+use crate::player::PlayerCam;
+
+/// Radius of the sky dome mesh, scaled to the camera far plane every frame.
+const SKY_RADIUS: f32 = 990.0;
+/// Fraction of the far plane the sky dome sits at, so it is never clipped.
+const SKY_FAR_FRACTION: f32 = 0.99;
+
+/// Colors of the gradient sky.
+#[derive(Resource, Clone)]
+pub struct SkyColors {
+    /// Color at and below the horizon.
+    pub horizon: Color,
+    /// Color straight overhead.
+    pub zenith: Color,
+}
+
+impl Default for SkyColors {
+    fn default() -> Self {
+        Self {
+            horizon: Color::srgb(0.75, 0.85, 0.95),
+            zenith: Color::srgb(0.2, 0.4, 0.8),
+        }
+    }
+}
+
+/// Inverted sphere drawn around the camera as the sky.
+#[derive(Component)]
+pub struct SkyDome;
+
+/// Spawns the sky dome centred on `center`.
+pub fn spawn_sky(
+    commands: &mut Commands,
+    meshes: &mut Assets<Mesh>,
+    materials: &mut Assets<StandardMaterial>,
+    colors: &SkyColors,
+    center: Vec3,
+) {
+    let mut mesh = Sphere::new(SKY_RADIUS).mesh().uv(32, 16);
+    if let Some(VertexAttributeValues::Float32x3(positions)) =
+        mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+    {
+        let gradient: Vec<[f32; 4]> = positions
+            .iter()
+            .map(|p| {
+                sky_color(colors, p[1] / SKY_RADIUS)
+                    .to_linear()
+                    .to_f32_array()
+            })
+            .collect();
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, gradient);
+    }
+
+    commands.spawn((
+        Mesh3d(meshes.add(mesh)),
+        MeshMaterial3d(materials.add(StandardMaterial {
+            unlit: true,
+            fog_enabled: false,
+            // seen from the inside
+            cull_mode: None,
+            ..default()
+        })),
+        Transform::from_translation(center),
+        NotShadowCaster,
+        NotShadowReceiver,
+        SkyDome,
+    ));
+}
+
+/// Sky color at `height`, the sine of the elevation angle.
+fn sky_color(colors: &SkyColors, height: f32) -> Color {
+    colors.horizon.mix(&colors.zenith, height.clamp(0.0, 1.0))
+}
+
+/// Keeps the sky centred on the player so it never comes closer, and just
+/// inside the far plane so terrain up to it is drawn in front of the sky.
+pub fn follow_camera(
+    cams: Query<(&Transform, &Projection), With<PlayerCam>>,
+    mut skies: Query<&mut Transform, (With<SkyDome>, Without<PlayerCam>)>,
+) {
+    let Ok((cam, projection)) = cams.single() else {
+        return;
+    };
+    let scale = match projection {
+        Projection::Perspective(perspective) => perspective.far * SKY_FAR_FRACTION / SKY_RADIUS,
+        _ => 1.0,
+    };
+    for mut sky in &mut skies {
+        sky.translation = cam.translation;
+        sky.scale = Vec3::splat(scale);
+    }
+}
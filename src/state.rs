@@ -6,3 +6,15 @@ pub enum AppState {
     Menu,
     Playing,
 }
+
+/// Gameplay sub-state that only exists while in [`AppState::Playing`].
+///
+/// Pausing switches this state instead of leaving `Playing`, so the world
+/// and its chunks stay alive while the simulation is frozen.
+#[derive(SubStates, Default, Debug, Clone, Eq, PartialEq, Hash)]
+#[source(AppState = AppState::Playing)]
+pub enum GameState {
+    #[default]
+    Running,
+    Paused,
+}
@@ -0,0 +1,87 @@
+use bevy::asset::{load_internal_asset, weak_handle};
+use bevy::pbr::{
+    ExtendedMaterial, MaterialExtension, MaterialExtensionKey, MaterialExtensionPipeline,
+};
+use bevy::prelude::*;
+use bevy::render::mesh::{MeshVertexAttribute, MeshVertexBufferLayoutRef};
+use bevy::render::render_resource::{
+    AsBindGroup, RenderPipelineDescriptor, ShaderRef, SpecializedMeshPipelineError, VertexFormat,
+};
+
+/// Vertex shader swaying vegetation vertices in the wind.
+const WIND_SHADER: Handle<Shader> = weak_handle!("5b7e0a3c-2f41-4d8e-9a6b-c1d2e3f40525");
+
+/// Per-vertex weight of the wind animation: 0 stays put, 1 sways fully.
+pub const ATTRIBUTE_SWAY: MeshVertexAttribute =
+    MeshVertexAttribute::new("Sway", 982_113_574, VertexFormat::Float32);
+
+/// Material of every chunk mesh: the standard PBR material plus wind sway.
+pub type ChunkMaterial = ExtendedMaterial<StandardMaterial, WindExtension>;
+
+/// Global wind strength applied to all chunk materials.
+#[derive(Resource, Clone, Copy)]
+pub struct Wind {
+    /// Multiplier of the sway distance; zero disables the animation.
+    pub strength: f32,
+}
+
+impl Default for Wind {
+    fn default() -> Self {
+        Self { strength: 1.0 }
+    }
+}
+
+/// Material extension offsetting vertices with a [`ATTRIBUTE_SWAY`] weight.
+#[derive(Asset, AsBindGroup, Reflect, Debug, Clone)]
+pub struct WindExtension {
+    #[uniform(100)]
+    pub strength: f32,
+}
+
+impl MaterialExtension for WindExtension {
+    fn vertex_shader() -> ShaderRef {
+        WIND_SHADER.into()
+    }
+
+    fn specialize(
+        _pipeline: &MaterialExtensionPipeline,
+        descriptor: &mut RenderPipelineDescriptor,
+        layout: &MeshVertexBufferLayoutRef,
+        _key: MaterialExtensionKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        // prepasses keep their own shaders, which expect the default layout
+        if descriptor.vertex.shader.id() != WIND_SHADER.id() {
+            return Ok(());
+        }
+        let vertex_layout = layout.0.get_layout(&[
+            Mesh::ATTRIBUTE_POSITION.at_shader_location(0),
+            Mesh::ATTRIBUTE_NORMAL.at_shader_location(1),
+            Mesh::ATTRIBUTE_COLOR.at_shader_location(5),
+            ATTRIBUTE_SWAY.at_shader_location(8),
+        ])?;
+        descriptor.vertex.buffers = vec![vertex_layout];
+        Ok(())
+    }
+}
+
+/// Plugin registering the chunk material and its wind shader.
+pub struct WindPlugin;
+
+impl Plugin for WindPlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(app, WIND_SHADER, "wind.wgsl", Shader::from_wgsl);
+        app.add_plugins(MaterialPlugin::<ChunkMaterial>::default())
+            .init_resource::<Wind>()
+            .add_systems(Update, apply_wind);
+    }
+}
+
+/// Pushes a changed wind strength into every chunk material.
+fn apply_wind(wind: Res<Wind>, mut materials: ResMut<Assets<ChunkMaterial>>) {
+    if !wind.is_changed() || wind.is_added() {
+        return;
+    }
+    for (_, material) in materials.iter_mut() {
+        material.extension.strength = wind.strength;
+    }
+}
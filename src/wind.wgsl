@@ -0,0 +1,61 @@
+#import bevy_pbr::{
+    forward_io::VertexOutput,
+    mesh_functions,
+    mesh_view_bindings::globals,
+    view_transformations::position_world_to_clip,
+}
+
+@group(2) @binding(100) var<uniform> wind_strength: f32;
+
+struct Vertex {
+    @builtin(instance_index) instance_index: u32,
+    @location(0) position: vec3<f32>,
+    @location(1) normal: vec3<f32>,
+    @location(5) color: vec4<f32>,
+    @location(8) sway: f32,
+};
+
+// Largest horizontal offset in blocks at a wind strength of one.
+const MAX_SWAY: f32 = 0.08;
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+
+    let world_from_local = mesh_functions::get_world_from_local(vertex.instance_index);
+    var world_position = mesh_functions::mesh_position_local_to_world(
+        world_from_local,
+        vec4<f32>(vertex.position, 1.0),
+    );
+
+    // The phase only depends on the world position, so vertices shared by
+    // neighbouring faces move together and the geometry never tears open.
+    let phase = globals.time * 1.7 + dot(world_position.xz, vec2<f32>(0.35, 0.21));
+    let offset = vec2<f32>(sin(phase), cos(phase * 0.8)) * MAX_SWAY * wind_strength * vertex.sway;
+    world_position.x += offset.x;
+    world_position.z += offset.y;
+
+    out.world_position = world_position;
+    out.position = position_world_to_clip(world_position.xyz);
+    out.world_normal = mesh_functions::mesh_normal_local_to_world(
+        vertex.normal,
+        vertex.instance_index,
+    );
+
+#ifdef VERTEX_COLORS
+    out.color = vertex.color;
+#endif
+
+#ifdef VERTEX_OUTPUT_INSTANCE_INDEX
+    out.instance_index = vertex.instance_index;
+#endif
+
+#ifdef VISIBILITY_RANGE_DITHER
+    out.visibility_range_dither = mesh_functions::get_visibility_range_dither_level(
+        vertex.instance_index,
+        world_from_local[3],
+    );
+#endif
+
+    return out;
+}
@@ -1,42 +1,253 @@
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::{Duration, Instant};
 
 use bevy::math::Affine3A;
 use bevy::pbr::MeshMaterial3d;
 use bevy::prelude::*;
 use bevy::render::mesh::{Indices, Mesh, Mesh3d};
 use bevy::render::primitives::{Aabb, Frustum};
+use bevy::render::render_asset::RenderAssetUsages;
 use bevy::tasks::{AsyncComputeTaskPool, Task};
 use block_mesh::ndshape::{ConstShape3u32, Shape};
 use block_mesh::{
-    GreedyQuadsBuffer, MergeVoxel, RIGHT_HANDED_Y_UP_CONFIG, Voxel, VoxelVisibility, greedy_quads,
+    GreedyQuadsBuffer, MergeVoxel, RIGHT_HANDED_Y_UP_CONFIG, UnitQuadBuffer, UnorientedQuad, Voxel,
+    VoxelVisibility, greedy_quads, visible_block_faces,
 };
-use fastnoise_lite::{FastNoiseLite, NoiseType};
-use futures_lite::future;
+use fastnoise_lite::{DomainWarpType, FastNoiseLite, FractalType, NoiseType};
 
+use crate::biome::{BiomeSample, BiomeSampler, BiomeSettings, Ground};
 use crate::player::PlayerCam;
-use crate::settings::NoiseSettings;
-use crate::state::AppState;
+use crate::selection::REACH;
+use crate::settings::{
+    Action, FractalMode, KeyBindings, LayerNoise, MAX_OCTAVES, NoiseSettings, QualityPreset,
+    RidgeMode,
+};
+use crate::state::{AppState, GameState};
+use crate::wind::{ATTRIBUTE_SWAY, ChunkMaterial, Wind, WindExtension};
+
+#[cfg(all(feature = "chunk-16", feature = "chunk-64"))]
+compile_error!("features `chunk-16` and `chunk-64` are mutually exclusive");
 
 /// Size of one cubic chunk edge in blocks.
+///
+/// Chosen at compile time with the `chunk-16` or `chunk-64` features and 32
+/// otherwise. Smaller chunks mesh faster and react quicker to the player
+/// moving, but need more entities, draw calls and tasks for the same view
+/// distance; larger chunks are the opposite. Sizes must be even so the lod2
+/// mesher can halve them.
+#[cfg(feature = "chunk-16")]
+pub const CHUNK_SIZE: i32 = 16;
+#[cfg(all(feature = "chunk-64", not(feature = "chunk-16")))]
+pub const CHUNK_SIZE: i32 = 64;
+#[cfg(not(any(feature = "chunk-16", feature = "chunk-64")))]
 pub const CHUNK_SIZE: i32 = 32;
-/// Maximum vertical height of the world in blocks.
-pub const MAX_HEIGHT: i32 = 256;
-/// Number of vertical chunks making up the world height.
-pub const MAX_CHUNKS_Y: i32 = MAX_HEIGHT / CHUNK_SIZE;
+
+/// Largest selectable world height in chunks, 1024 blocks at any chunk size.
+pub const MAX_HEIGHT_CHUNKS: i32 = 1024 / CHUNK_SIZE;
+/// Default world height in chunks, 256 blocks at any chunk size.
+const DEFAULT_HEIGHT_CHUNKS: i32 = 256 / CHUNK_SIZE;
+/// Default radius in chunks of the circle around the player within which
+/// chunks use full detail, 192 blocks at any chunk size.
+const LOD1_RADIUS: i32 = 192 / CHUNK_SIZE;
 
 const CHUNK_SIZE_U32: u32 = CHUNK_SIZE as u32;
+const CHUNK_SIZE_F32: f32 = CHUNK_SIZE as f32;
 const LOD2_SIZE_U32: u32 = CHUNK_SIZE_U32 / 2;
+/// Voxel grid edge of the full-detail mesher: the chunk plus one block of
+/// padding on each side, plus one more so the greedy mesher sees the far
+/// boundary faces.
+const LOD1_PADDED: u32 = CHUNK_SIZE_U32 + 3;
+/// Voxel grid edge of the half-resolution mesher, padded like [`LOD1_PADDED`].
+const LOD2_PADDED: u32 = LOD2_SIZE_U32 + 3;
+
+const _: () = assert!(CHUNK_SIZE % 2 == 0 && CHUNK_SIZE >= 8);
+
+/// Edge length in pixels of the terrain preview image.
+pub const PREVIEW_SIZE: u32 = 128;
+/// World blocks covered by one preview pixel.
+const PREVIEW_STRIDE: i32 = 4;
+
+/// Closest accepted near clip plane; smaller values waste depth precision
+/// and make far terrain z-fight.
+const MIN_NEAR_PLANE: f32 = 0.05;
+/// Blocks the far clip plane reaches past the farthest loaded chunk corner.
+const FAR_PLANE_MARGIN: f32 = 64.0;
+/// Bevy's default far plane, kept as a floor for small view distances.
+const MIN_FAR_PLANE: f32 = 1000.0;
 
 /// Runtime-configurable world generation parameters.
 #[derive(Resource)]
 pub struct WorldParams {
     /// Number of chunks to generate outwards from the player along each axis.
     pub view_width: i32,
+    /// Number of chunks outwards from the player that are drawn.
+    ///
+    /// Chunks between this and `view_width` stay generated but hidden, so a
+    /// large buffer can be kept around while rendering less.
+    pub render_width: i32,
+    /// Number of vertically stacked chunk layers making up the world height.
+    pub height_chunks: i32,
+    /// Extra chunks past `view_width` a chunk may drift before it is unloaded.
+    ///
+    /// Keeps chunks at the edge from despawning and regenerating when the
+    /// player paces back and forth across a chunk boundary.
+    pub unload_margin: i32,
+    /// Radius in chunks of the circle around the player within which chunks
+    /// are meshed at full detail; chunks further out use the coarser lod.
+    pub lod1_radius: i32,
+    /// Centres generation on the world origin instead of the player, for an
+    /// orbiting camera and reproducible views of the same terrain.
+    pub orbit: bool,
+    /// Seconds a chunk cross-fades into its replacement after a LOD change.
+    ///
+    /// Zero swaps the meshes in a single frame without blending.
+    pub lod_fade: f32,
+    /// Most point lights emissive blocks may add across all loaded chunks.
+    ///
+    /// Bevy's clustered forward renderer only shades a limited number of
+    /// lights per view, so lights past the cap are simply not spawned.
+    pub max_block_lights: usize,
+    /// Vertex count of a single chunk above which generation logs a warning,
+    /// to tie upload hitches and memory spikes to the settings behind them.
+    pub max_chunk_vertices: usize,
+    /// Distance of the camera's near clip plane in blocks, at least
+    /// [`MIN_NEAR_PLANE`]. The far plane follows the view distance.
+    pub near_plane: f32,
+    /// Where chunk meshes are kept after upload.
+    ///
+    /// With [`RenderAssetUsages::MAIN_WORLD`] the vertex data stays readable
+    /// on the CPU for exporting, collision or editing, at the cost of holding
+    /// every loaded mesh twice; `RENDER_WORLD` alone frees it once uploaded.
+    pub mesh_usage: RenderAssetUsages,
+    /// Bytes of mesh data that unloaded chunks may keep in the
+    /// [`ChunkStash`] to reappear at once when the player walks back; the
+    /// oldest go first beyond it. Zero despawns unloaded chunks outright.
+    pub stash_budget: usize,
+    /// Height below which the flying camera is eased back up, keeping it
+    /// from sinking into the void under the world; `None` lets it fly
+    /// anywhere, for debugging.
+    pub camera_floor: Option<f32>,
+    /// Chunk columns the player is kept inside and chunks are generated in,
+    /// e.g. for demos that must not wander off into endless generation;
+    /// `None` leaves the world unbounded.
+    pub bounds: Option<ChunkBounds>,
+}
+
+/// Inclusive horizontal range of chunk columns, see [`WorldParams::bounds`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ChunkBounds {
+    pub min: IVec2,
+    pub max: IVec2,
+}
+
+impl ChunkBounds {
+    /// Columns up to `radius` chunks from the origin along both axes.
+    pub fn around_origin(radius: i32) -> Self {
+        ChunkBounds {
+            min: IVec2::splat(-radius),
+            max: IVec2::splat(radius),
+        }
+    }
+
+    pub fn contains(&self, column: IVec2) -> bool {
+        column.cmpge(self.min).all() && column.cmple(self.max).all()
+    }
+
+    /// `pos` moved horizontally onto the nearest column inside the bounds.
+    pub fn clamp(&self, pos: Vec3) -> Vec3 {
+        let low = (self.min * CHUNK_SIZE).as_vec2();
+        let high = ((self.max + 1) * CHUNK_SIZE).as_vec2();
+        let xz = pos.xz().clamp(low, high);
+        Vec3::new(xz.x, pos.y, xz.y)
+    }
 }
 
 impl Default for WorldParams {
     fn default() -> Self {
-        Self { view_width: 24 }
+        Self {
+            view_width: 24,
+            render_width: 24,
+            height_chunks: DEFAULT_HEIGHT_CHUNKS,
+            unload_margin: 4,
+            lod1_radius: LOD1_RADIUS,
+            lod_fade: 0.5,
+            orbit: false,
+            max_block_lights: 64,
+            max_chunk_vertices: 100_000,
+            near_plane: 0.1,
+            mesh_usage: RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+            stash_budget: 128 * 1024 * 1024,
+            camera_floor: Some(-8.0),
+            bounds: None,
+        }
+    }
+}
+
+impl WorldParams {
+    /// Vertical height of the world in blocks.
+    pub fn max_height(&self) -> i32 {
+        self.height_chunks * CHUNK_SIZE
+    }
+
+    /// Horizontal view distance in blocks.
+    pub fn view_distance(&self) -> f32 {
+        (self.view_width * CHUNK_SIZE) as f32
+    }
+
+    /// Near and far clip planes of the camera.
+    ///
+    /// The far plane reaches the farthest corner of the loaded area, diagonally
+    /// across the view distance and from the bottom to the top of the world,
+    /// so distant mountains are not cut off.
+    pub fn clip_planes(&self) -> (f32, f32) {
+        let reach =
+            (self.view_distance() * std::f32::consts::SQRT_2).hypot(self.max_height() as f32);
+        (
+            self.near_plane.max(MIN_NEAR_PLANE),
+            (reach + FAR_PLANE_MARGIN).max(MIN_FAR_PLANE),
+        )
+    }
+
+    /// Chunk that generation is centred on with the player at `player_pos`.
+    pub fn generation_center(&self, player_pos: Vec3) -> IVec3 {
+        let focus = if self.orbit { Vec3::ZERO } else { player_pos };
+        world_to_chunk(focus)
+    }
+
+    /// Sets the view, render and full-detail distances of `preset`, turning
+    /// its detail radius into chunks the way [`LOD1_RADIUS`] does.
+    pub fn apply_quality(&mut self, preset: QualityPreset) {
+        let (view_width, detail) = preset.distances();
+        self.view_width = view_width;
+        self.render_width = view_width;
+        self.lod1_radius = detail / CHUNK_SIZE;
+    }
+
+    /// Chunks further than this along x or z from the camera's chunk are
+    /// hidden.
+    ///
+    /// The distance is the larger of the two axis offsets, so the drawn area
+    /// is a square; only the lod bands inside it are round, see
+    /// [`WorldParams::lod1_radius`].
+    pub fn render_radius(&self) -> i32 {
+        self.render_width.clamp(1, self.view_width.max(1))
+    }
+
+    /// Loaded chunks further than this along x or z from the generation
+    /// center are dropped.
+    ///
+    /// Measured like [`WorldParams::render_radius`], as the larger of the
+    /// two axis offsets, so it follows the square that loading covers.
+    pub fn unload_radius(&self) -> i32 {
+        self.view_width + self.unload_margin.max(0)
+    }
+
+    /// Whether the chunk at `coord` lies inside [`WorldParams::bounds`].
+    pub fn in_bounds(&self, coord: IVec3) -> bool {
+        self.bounds.is_none_or(|b| b.contains(coord.xz()))
     }
 }
 
@@ -46,15 +257,166 @@ struct ChunkMap {
     entities: HashMap<IVec3, Entity>,
 }
 
+/// Output of a background chunk generation task.
+struct ChunkTaskResult {
+    coord: IVec3,
+    lod: u32,
+    meshes: Vec<(BlockType, Mesh)>,
+    surface: Vec<[f32; 4]>,
+    /// Lower bound of the terrain surface across the chunk's columns.
+    min_surface: i32,
+    /// Time spent meshing the chunk on the task pool.
+    duration: Duration,
+    lights: Vec<(BlockType, Vec3)>,
+    blocks: BlockCounts,
+}
+
+/// Finished chunks the generation timing is averaged over.
+const TIMING_WINDOW: usize = 64;
+
+/// Generation times of the most recently finished chunks, shown by the
+/// debug overlay.
+#[derive(Resource, Default)]
+pub struct GenerationStats {
+    recent: VecDeque<(u32, Duration)>,
+    /// Chunk results taken in since startup.
+    finished: u64,
+}
+
+impl GenerationStats {
+    fn record(&mut self, lod: u32, duration: Duration) {
+        if self.recent.len() == TIMING_WINDOW {
+            self.recent.pop_front();
+        }
+        self.recent.push_back((lod, duration));
+        self.finished += 1;
+    }
+
+    /// Chunk results taken in since startup, stale ones included.
+    pub fn finished(&self) -> u64 {
+        self.finished
+    }
+
+    /// Mean and slowest recent generation time at `lod`, if any finished.
+    pub fn timing(&self, lod: u32) -> Option<(Duration, Duration)> {
+        let times: Vec<Duration> = self
+            .recent
+            .iter()
+            .filter(|(l, _)| *l == lod)
+            .map(|(_, d)| *d)
+            .collect();
+        let slowest = times.iter().max().copied()?;
+        Some((times.iter().sum::<Duration>() / times.len() as u32, slowest))
+    }
+}
+
+/// Finished chunk results [`process_chunk_tasks`] takes in per frame, so a
+/// burst of completions spreads its mesh uploads over a few frames.
+const RESULTS_PER_FRAME: usize = 32;
+
+/// Chunk generation queued on the task pool.
+struct InFlight {
+    lod: u32,
+    /// Ties the task's result to this entry, so results of tasks cancelled
+    /// after they already finished are recognised as stale.
+    id: u64,
+    /// Dropping the handle cancels the task.
+    _task: Task<()>,
+}
+
 /// Pending background generation tasks.
 ///
 /// Each entry tracks the requested level of detail so that
 /// pending work can be cancelled or replaced if the player
 /// approaches a chunk and it needs to be regenerated at a
-/// higher resolution.
-#[derive(Resource, Default)]
+/// higher resolution. Tasks send their results into a channel, so
+/// completions are picked up without polling every queued task.
+#[derive(Resource)]
 struct PendingTasks {
-    tasks: HashMap<IVec3, (u32, Task<(IVec3, u32, Mesh, Vec<[f32; 4]>)>)>,
+    tasks: HashMap<IVec3, InFlight>,
+    /// Chunks queued by [`regenerate_chunk`], whose results are kept even
+    /// when the player has moved on since.
+    forced: HashSet<IVec3>,
+    next_id: u64,
+    sender: Sender<(u64, ChunkTaskResult)>,
+    /// Only ever drained through `&mut self`; the mutex just makes the
+    /// receiver shareable as a resource.
+    results: Mutex<Receiver<(u64, ChunkTaskResult)>>,
+}
+
+impl Default for PendingTasks {
+    fn default() -> Self {
+        let (sender, results) = mpsc::channel();
+        Self {
+            tasks: HashMap::new(),
+            forced: HashSet::new(),
+            next_id: 0,
+            sender,
+            results: Mutex::new(results),
+        }
+    }
+}
+
+impl PendingTasks {
+    /// Runs `generate` for the chunk at `coord` on the task pool, replacing
+    /// whatever was queued there before.
+    fn spawn(
+        &mut self,
+        coord: IVec3,
+        lod: u32,
+        generate: impl FnOnce() -> ChunkTaskResult + Send + 'static,
+    ) {
+        self.forced.remove(&coord);
+        let id = self.next_id;
+        self.next_id += 1;
+        let sender = self.sender.clone();
+        let task = AsyncComputeTaskPool::get().spawn(async move {
+            // the receiver only goes away with the app
+            let _ = sender.send((id, generate()));
+        });
+        self.tasks.insert(
+            coord,
+            InFlight {
+                lod,
+                id,
+                _task: task,
+            },
+        );
+    }
+
+    /// Cancels the task queued for `coord`, if any.
+    ///
+    /// Dropping a Bevy [`Task`] cancels it on the pool, so superseded work
+    /// stops as soon as it yields. A result it already sent no longer
+    /// matches any entry and is discarded by [`PendingTasks::take_finished`].
+    fn cancel(&mut self, coord: IVec3) {
+        self.tasks.remove(&coord);
+        self.forced.remove(&coord);
+    }
+
+    /// Up to `budget` finished results, each paired with whether it is still
+    /// the task queued for its chunk. Current ones stop being tracked.
+    fn take_finished(&mut self, budget: usize) -> Vec<(ChunkTaskResult, bool)> {
+        let results = self
+            .results
+            .get_mut()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let received: Vec<_> = results.try_iter().take(budget).collect();
+        received
+            .into_iter()
+            .map(|(id, result)| {
+                let current = self
+                    .tasks
+                    .get(&result.coord)
+                    .is_some_and(|task| task.id == id);
+                if current {
+                    debug_assert_eq!(self.tasks[&result.coord].lod, result.lod);
+                    self.tasks.remove(&result.coord);
+                }
+                (result, current)
+            })
+            .collect()
+    }
 }
 
 /// Cached top surface colors for generated chunks.
@@ -63,11 +425,176 @@ struct SurfaceCache {
     colors: HashMap<IVec3, Vec<[f32; 4]>>,
 }
 
+/// Chunk unloaded into the [`ChunkStash`].
+struct Stashed {
+    entity: Entity,
+    lod: u32,
+    bytes: usize,
+    /// Occluder height of the chunk's column, see [`OccluderHeights`].
+    min_surface: Option<i32>,
+}
+
+/// Chunks that drifted past the unload radius, kept as hidden entities
+/// without their [`Chunk`] component so culling and the overlays skip them.
+///
+/// Their meshes stay uploaded up to [`WorldParams::stash_budget`] bytes and
+/// the chunk is shown again instead of regenerated when it comes back into
+/// range.
+#[derive(Resource, Default)]
+struct ChunkStash {
+    chunks: HashMap<IVec3, Stashed>,
+    /// Stashed coords, oldest first.
+    order: VecDeque<IVec3>,
+    bytes: usize,
+}
+
+impl ChunkStash {
+    /// Takes the chunk stashed at `coord` out of the stash.
+    fn take(&mut self, coord: IVec3) -> Option<Stashed> {
+        let stashed = self.chunks.remove(&coord)?;
+        self.order.retain(|c| *c != coord);
+        self.bytes -= stashed.bytes;
+        Some(stashed)
+    }
+
+    /// Despawns the oldest chunks until at most `budget` bytes remain.
+    fn evict(&mut self, commands: &mut Commands, budget: usize) {
+        while self.bytes > budget {
+            let Some(coord) = self.order.pop_front() else {
+                break;
+            };
+            if let Some(stashed) = self.chunks.remove(&coord) {
+                self.bytes -= stashed.bytes;
+                commands.entity(stashed.entity).despawn();
+            }
+        }
+    }
+}
+
+/// Lowest terrain surface of every generated chunk column.
+///
+/// Terrain is solid everywhere below these heights (apart from caves, which
+/// are ignored), so they act as a coarse occluder heightfield.
+#[derive(Resource, Default)]
+struct OccluderHeights {
+    /// Lowest surface over the generated chunks of each column.
+    heights: HashMap<IVec2, i32>,
+    /// Lowest surface seen by each generated chunk, by column and chunk layer.
+    /// Chunks of one column may sample it at different lods.
+    chunks: HashMap<IVec2, HashMap<i32, i32>>,
+}
+
+impl OccluderHeights {
+    /// Records the lowest surface seen by the chunk at `coord`.
+    fn insert(&mut self, coord: IVec3, height: i32) {
+        let column = self.chunks.entry(coord.xz()).or_default();
+        column.insert(coord.y, height);
+        let lowest = column.values().copied().min().unwrap_or(height);
+        self.heights.insert(coord.xz(), lowest);
+    }
+
+    /// Forgets the chunk at `coord`, returning its lowest surface. The column
+    /// stays an occluder until its last chunk is gone.
+    fn remove(&mut self, coord: IVec3) -> Option<i32> {
+        let column = self.chunks.get_mut(&coord.xz())?;
+        let height = column.remove(&coord.y)?;
+        match column.values().copied().min() {
+            Some(lowest) => {
+                self.heights.insert(coord.xz(), lowest);
+            }
+            None => {
+                self.chunks.remove(&coord.xz());
+                self.heights.remove(&coord.xz());
+            }
+        }
+        Some(height)
+    }
+
+    fn clear(&mut self) {
+        self.heights.clear();
+        self.chunks.clear();
+    }
+}
+
+/// Chunks found to be hidden behind terrain by the occlusion passes.
+#[derive(Resource)]
+struct OcclusionState {
+    occluded: HashSet<IVec3>,
+    /// Chunks the running pass has yet to test, see
+    /// [`OCCLUSION_CHUNKS_PER_FRAME`].
+    queue: VecDeque<Entity>,
+    /// Chunks inside the view frustum last frame; ones that just came into
+    /// view are shown until a pass has tested them from the new angle.
+    in_frustum: HashSet<IVec3>,
+    timer: Timer,
+}
+
+impl Default for OcclusionState {
+    fn default() -> Self {
+        Self {
+            occluded: HashSet::new(),
+            queue: VecDeque::new(),
+            in_frustum: HashSet::new(),
+            timer: Timer::from_seconds(OCCLUSION_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+/// Seconds between occlusion passes; the test is too costly to run every frame.
+const OCCLUSION_INTERVAL: f32 = 0.25;
+/// Most chunks an occlusion pass tests per frame, so a large view spreads
+/// the pass over several frames instead of stalling one.
+const OCCLUSION_CHUNKS_PER_FRAME: usize = 256;
+/// Distance in blocks between samples taken along an occlusion ray.
+const OCCLUSION_STEP: f32 = CHUNK_SIZE_F32 / 4.0;
+
+/// Chunk culling counters, shown by the debug overlay.
+#[derive(Resource, Default)]
+pub struct CullStats {
+    /// Loaded chunk entities.
+    pub chunks: usize,
+    /// Chunks inside the view frustum and not occluded.
+    pub visible: usize,
+    /// Chunks inside the view frustum but hidden behind terrain.
+    pub occluded: usize,
+    /// Chunks hidden for lying beyond the render distance.
+    pub distant: usize,
+    /// Mesh parts drawn this frame.
+    pub draw_calls: usize,
+    /// Mesh parts of occluded chunks that would otherwise have been drawn.
+    pub saved_draw_calls: usize,
+}
+
+/// Multiplier of every chunk material's base color, following
+/// [`crate::settings::DisplaySettings::brightness`].
+#[derive(Resource, Clone, Copy)]
+pub struct ChunkBrightness(pub f32);
+
+impl Default for ChunkBrightness {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
 /// Component tagging a chunk mesh entity.
 #[derive(Component)]
 pub struct Chunk {
     pub coord: IVec3,
     pub lod: u32,
+    /// Size of the chunk's mesh data and block lights, counted against
+    /// [`WorldParams::stash_budget`].
+    pub bytes: usize,
+}
+
+/// Point light child of a [`Chunk`], cast by one of its emissive blocks.
+#[derive(Component)]
+struct BlockLight;
+
+/// Mesh child of a [`Chunk`], holding the geometry of one block type.
+#[derive(Component)]
+struct ChunkPart {
+    /// Whether the part is see-through and always alpha blended.
+    translucent: bool,
 }
 
 /// Fade direction for cross-fading chunk meshes.
@@ -83,6 +610,42 @@ struct Fade {
     dir: FadeDir,
 }
 
+/// Stops chunk loading and unloading so the loaded set stays fixed while the
+/// player flies around inspecting it. Cleared when the world is rebuilt.
+#[derive(Resource, Default)]
+pub struct FreezeChunks(pub bool);
+
+/// Run condition for chunk loading, see [`FreezeChunks`].
+pub fn chunks_frozen(freeze: Res<FreezeChunks>) -> bool {
+    freeze.0
+}
+
+/// Chunk distance around the player that has to exist before the player may
+/// move, see [`LoadingProgress`].
+const LOADING_RADIUS: i32 = 1;
+
+/// Generation progress of the chunks right around the player after a game
+/// starts or the world is rebuilt.
+#[derive(Resource, Default)]
+pub struct LoadingProgress {
+    pub done: usize,
+    pub needed: usize,
+    /// Set once every needed chunk exists, and kept until the world is cleared
+    /// so later streaming never brings the loading screen back.
+    pub finished: bool,
+}
+
+/// Run condition for gameplay that needs the ground around the player, see
+/// [`LoadingProgress`].
+pub fn world_loaded(progress: Res<LoadingProgress>) -> bool {
+    progress.finished
+}
+
+/// Request to throw away every generated chunk and rebuild the world around
+/// the player, e.g. after the noise settings changed.
+#[derive(Event)]
+pub struct RegenerateWorld;
+
 /// Plugin managing world chunk generation and rendering.
 pub struct WorldPlugin;
 
@@ -91,20 +654,99 @@ impl Plugin for WorldPlugin {
         app.init_resource::<ChunkMap>()
             .init_resource::<PendingTasks>()
             .init_resource::<SurfaceCache>()
+            .init_resource::<ChunkStash>()
+            .init_resource::<OccluderHeights>()
+            .init_resource::<OcclusionState>()
+            .init_resource::<CullStats>()
+            .init_resource::<FreezeChunks>()
+            .init_resource::<LoadingProgress>()
+            .init_resource::<GenerationStats>()
+            .init_resource::<Wind>()
+            .init_resource::<ChunkBrightness>()
+            .init_resource::<TerrainNoise>()
+            .add_event::<RegenerateWorld>()
+            .add_systems(PreUpdate, refresh_terrain_noise)
+            .add_systems(
+                Update,
+                cleanup_chunks
+                    .run_if(on_event::<RegenerateWorld>)
+                    .before(spawn_required_chunks)
+                    .run_if(in_state(AppState::Playing)),
+            )
             .add_systems(
                 Update,
                 (
-                    spawn_required_chunks,
+                    regenerate_chunk,
+                    spawn_required_chunks.run_if(not(chunks_frozen)),
+                    // keeps running while frozen so regenerated chunks come
+                    // back; nothing else gets queued then
                     process_chunk_tasks,
-                    frustum_cull_chunks,
-                    fade_chunks,
                 )
+                    .chain()
+                    .run_if(in_state(GameState::Running)),
+            )
+            .add_systems(
+                Update,
+                track_loading
+                    .after(process_chunk_tasks)
+                    .run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(
+                Update,
+                (occlusion_cull_chunks, frustum_cull_chunks, fade_chunks)
+                    .chain()
                     .run_if(in_state(AppState::Playing)),
             )
+            .add_systems(Update, apply_brightness)
             .add_systems(OnExit(AppState::Playing), cleanup_chunks);
     }
 }
 
+/// Noise generators for the current [`NoiseSettings`], shared by the systems
+/// that sample the terrain every frame outside chunk generation.
+#[derive(Resource)]
+pub struct TerrainNoise(pub NoiseResources);
+
+impl Default for TerrainNoise {
+    /// Replaced by [`refresh_terrain_noise`] before the first frame's systems
+    /// read it.
+    fn default() -> Self {
+        Self(NoiseResources::from_settings(
+            &NoiseSettings::fallback(),
+            WorldParams::default().max_height(),
+        ))
+    }
+}
+
+/// Rebuilds [`TerrainNoise`] when the noise settings or the world height
+/// change.
+fn refresh_terrain_noise(
+    settings: Res<NoiseSettings>,
+    params: Res<WorldParams>,
+    mut noise: ResMut<TerrainNoise>,
+) {
+    if settings.is_changed() || params.is_changed() {
+        noise.0 = NoiseResources::from_settings(&settings, params.max_height());
+    }
+}
+
+/// Pushes a changed [`ChunkBrightness`] into every chunk material, keeping
+/// the alpha of chunks that are fading.
+fn apply_brightness(
+    brightness: Res<ChunkBrightness>,
+    mut materials: ResMut<Assets<ChunkMaterial>>,
+) {
+    if !brightness.is_changed() || brightness.is_added() {
+        return;
+    }
+    let b = brightness.0;
+    for (_, material) in materials.iter_mut() {
+        let alpha = material.base.base_color.alpha();
+        material.base.base_color = Color::linear_rgba(b, b, b, alpha);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn spawn_required_chunks(
     mut commands: Commands,
     params: Res<WorldParams>,
@@ -112,42 +754,101 @@ fn spawn_required_chunks(
     mut pending: ResMut<PendingTasks>,
     mut map: ResMut<ChunkMap>,
     mut cache: ResMut<SurfaceCache>,
+    mut occluders: ResMut<OccluderHeights>,
+    mut stash: ResMut<ChunkStash>,
     player: Query<&Transform, With<PlayerCam>>,
     chunks: Query<&Chunk>,
+    fades: Query<(), With<Fade>>,
 ) {
-    let pool = AsyncComputeTaskPool::get();
     let player_pos = player.single().map(|t| t.translation).unwrap_or(Vec3::ZERO);
-    let player_chunk = IVec3::new(
-        (player_pos.x / CHUNK_SIZE as f32).floor() as i32,
-        (player_pos.y / CHUNK_SIZE as f32).floor() as i32,
-        (player_pos.z / CHUNK_SIZE as f32).floor() as i32,
-    );
+    let player_chunk = params.generation_center(player_pos);
 
-    // Despawn chunks far outside the view radius
+    // Stash or despawn chunks that drifted past the unload radius
+    let unload_radius = params.unload_radius();
     let mut to_remove = Vec::new();
     for (coord, entity) in map.entities.iter() {
         let dist = (coord.x - player_chunk.x)
             .abs()
             .max((coord.z - player_chunk.z).abs());
-        if dist > params.view_width + 2 {
-            commands.entity(*entity).despawn();
-            to_remove.push(*coord);
+        if dist > unload_radius || !params.in_bounds(*coord) {
+            to_remove.push((*coord, *entity));
         }
     }
-    for coord in to_remove {
+    for (coord, entity) in to_remove {
+        let min_surface = occluders.remove(coord);
         map.entities.remove(&coord);
         cache.colors.remove(&coord);
+        // chunks caught mid-fade would come back half transparent
+        match chunks.get(entity) {
+            Ok(chunk) if chunk.bytes <= params.stash_budget && !fades.contains(entity) => {
+                commands
+                    .entity(entity)
+                    .remove::<Chunk>()
+                    .insert(Visibility::Hidden);
+                stash.bytes += chunk.bytes;
+                stash.order.push_back(coord);
+                stash.chunks.insert(
+                    coord,
+                    Stashed {
+                        entity,
+                        lod: chunk.lod,
+                        bytes: chunk.bytes,
+                        min_surface,
+                    },
+                );
+            }
+            _ => commands.entity(entity).despawn(),
+        }
+    }
+    stash.evict(&mut commands, params.stash_budget);
+    // Work queued for chunks that are now out of range is no longer wanted
+    let far: Vec<IVec3> = pending
+        .tasks
+        .keys()
+        .filter(|coord| {
+            let dist = (coord.x - player_chunk.x)
+                .abs()
+                .max((coord.z - player_chunk.z).abs());
+            dist > unload_radius || !params.in_bounds(**coord)
+        })
+        .copied()
+        .collect();
+    for coord in far {
+        pending.cancel(coord);
     }
 
     // Queue missing chunks for generation
     for x in -params.view_width..=params.view_width {
         for z in -params.view_width..=params.view_width {
-            let dist = x.abs().max(z.abs());
-            let required_lod = if dist <= 6 { 1 } else { 2 };
-            for y in 0..MAX_CHUNKS_Y {
+            for y in 0..params.height_chunks {
                 let coord = IVec3::new(player_chunk.x + x, y, player_chunk.z + z);
+                let Some(required_lod) =
+                    required_lod(&params, player_chunk, coord, params.view_width)
+                else {
+                    continue;
+                };
 
-                if let Some(&entity) = map.entities.get(&coord) {
+                if let Some(stashed) = stash.take(coord) {
+                    // culling takes over the visibility again next frame
+                    commands.entity(stashed.entity).insert((
+                        Chunk {
+                            coord,
+                            lod: stashed.lod,
+                            bytes: stashed.bytes,
+                        },
+                        Visibility::default(),
+                    ));
+                    map.entities.insert(coord, stashed.entity);
+                    if let Some(height) = stashed.min_surface {
+                        occluders.insert(coord, height);
+                    }
+                    // a stashed chunk at another lod stays drawn until its
+                    // replacement is generated
+                    if stashed.lod == required_lod {
+                        continue;
+                    }
+                    pending.cancel(coord);
+                } else if let Some(&entity) = map.entities.get(&coord) {
                     if let Ok(chunk) = chunks.get(entity) {
                         if chunk.lod == required_lod {
                             continue;
@@ -157,339 +858,3929 @@ fn spawn_required_chunks(
                     }
                 }
 
-                if let Some((lod, _)) = pending.tasks.get(&coord) {
-                    if *lod == required_lod {
+                if let Some(task) = pending.tasks.get(&coord) {
+                    if task.lod == required_lod {
                         continue;
                     }
-                    pending.tasks.remove(&coord);
+                    pending.cancel(coord);
                 }
 
                 let settings = settings.clone();
                 let cached = cache.colors.get(&coord).cloned();
-                let task = pool.spawn(async move {
-                    let (mesh, surface) =
-                        generate_chunk_mesh(coord, required_lod, settings, cached);
-                    (coord, required_lod, mesh, surface)
+                let max_height = params.max_height();
+                let max_vertices = params.max_chunk_vertices;
+                let usage = params.mesh_usage;
+                pending.spawn(coord, required_lod, move || {
+                    generate_chunk_mesh(
+                        coord,
+                        required_lod,
+                        settings,
+                        cached,
+                        max_height,
+                        max_vertices,
+                        usage,
+                    )
                 });
-                pending.tasks.insert(coord, (required_lod, task));
             }
         }
     }
 }
 
+/// Level of detail the chunk at `coord` should have with the player in
+/// `player_chunk`, or `None` if it lies further than `radius` chunks away
+/// or outside the world bounds.
+///
+/// Loading covers a square, but the lod bands are rings at the Euclidean
+/// distance [`WorldParams::lod1_radius`], so detail falls off evenly in
+/// every direction instead of reaching further along the diagonals.
+fn required_lod(
+    params: &WorldParams,
+    player_chunk: IVec3,
+    coord: IVec3,
+    radius: i32,
+) -> Option<u32> {
+    let offset = (coord - player_chunk).xz();
+    if offset.abs().max_element() > radius
+        || !(0..params.height_chunks).contains(&coord.y)
+        || !params.in_bounds(coord)
+    {
+        return None;
+    }
+    let lod1 = params.lod1_radius.max(0);
+    Some(if offset.length_squared() <= lod1 * lod1 {
+        1
+    } else {
+        2
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
 fn process_chunk_tasks(
     mut commands: Commands,
+    params: Res<WorldParams>,
     mut pending: ResMut<PendingTasks>,
     mut map: ResMut<ChunkMap>,
     mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut materials: ResMut<Assets<ChunkMaterial>>,
     mut cache: ResMut<SurfaceCache>,
+    mut occluders: ResMut<OccluderHeights>,
+    mut stats: ResMut<GenerationStats>,
+    wind: Res<Wind>,
+    brightness: Res<ChunkBrightness>,
+    player: Query<&Transform, With<PlayerCam>>,
+    fades: Query<&Fade>,
+    block_lights: Query<&ChildOf, With<BlockLight>>,
+    chunks: Query<Option<&Fade>, With<Chunk>>,
 ) {
-    let mut finished = Vec::new();
-    for (coord, (_lod, task)) in pending.tasks.iter_mut() {
-        if let Some((c, lod, mesh, surface)) = future::block_on(future::poll_once(task)) {
-            let handle = meshes.add(mesh);
-            let old = map.entities.get(&c).copied();
-            let alpha = if old.is_some() { 0.0 } else { 1.0 };
-            let mat_handle = materials.add(StandardMaterial {
-                base_color: Color::srgba(1.0, 1.0, 1.0, alpha),
-                alpha_mode: AlphaMode::Blend,
-                ..default()
+    // stashed chunks lost their `Chunk` and fading ones are on their way out,
+    // so neither holds on to the budget
+    let lit = block_lights
+        .iter()
+        .filter(|child_of| {
+            chunks
+                .get(child_of.parent())
+                .is_ok_and(|fade| !fade.is_some_and(|f| matches!(f.dir, FadeDir::Out)))
+        })
+        .count();
+    let mut light_budget = params.max_block_lights.saturating_sub(lit);
+    let player_pos = player.single().map(|t| t.translation).unwrap_or(Vec3::ZERO);
+    let player_chunk = params.generation_center(player_pos);
+    for (result, current) in pending.take_finished(RESULTS_PER_FRAME) {
+        // stale results cost the same, so they count too
+        stats.record(result.lod, result.duration);
+        debug!(
+            "chunk {} at lod {} generated in {:?}",
+            result.coord, result.lod, result.duration
+        );
+        if !current {
+            continue;
+        }
+        // the player may have moved on since the task was queued
+        let wanted = required_lod(&params, player_chunk, result.coord, params.unload_radius());
+        if !pending.forced.remove(&result.coord) && wanted != Some(result.lod) {
+            continue;
+        }
+        let ChunkTaskResult {
+            coord: c,
+            lod,
+            meshes: parts,
+            surface,
+            min_surface,
+            lights,
+            blocks,
+            ..
+        } = result;
+        occluders.insert(c, min_surface);
+        let light_count = lights.len().min(light_budget);
+        light_budget -= light_count;
+        let bytes = parts
+            .iter()
+            .map(|(_, mesh)| mesh_bytes(mesh))
+            .sum::<usize>()
+            + light_count * BLOCK_LIGHT_BYTES;
+        let mut old = map.entities.get(&c).copied();
+        // a chunk that never finished fading in is replaced outright
+        if let Some(old_entity) = old
+            && fades
+                .get(old_entity)
+                .is_ok_and(|f| matches!(f.dir, FadeDir::In))
+        {
+            commands.entity(old_entity).despawn();
+            old = None;
+        }
+        // the old chunk stays drawn until its replacement exists, so a
+        // coord is never empty; without a fade both swap in one command flush
+        let fade = old.is_some() && params.lod_fade > 0.0;
+        if !fade && let Some(old_entity) = old.take() {
+            commands.entity(old_entity).despawn();
+        }
+        let alpha = if fade { 0.0 } else { 1.0 };
+        // one child per block type so every block can use its own material
+        let entity = commands
+            .spawn((
+                Transform::from_translation(c.as_vec3() * CHUNK_SIZE_F32),
+                Visibility::default(),
+                Chunk {
+                    coord: c,
+                    lod,
+                    bytes,
+                },
+                blocks,
+            ))
+            .with_children(|parent| {
+                for (block, mesh) in parts {
+                    let material = block_material(block, alpha, wind.strength, brightness.0);
+                    parent.spawn((
+                        Mesh3d(meshes.add(mesh)),
+                        MeshMaterial3d(materials.add(material)),
+                        ChunkPart {
+                            translucent: is_translucent(block),
+                        },
+                    ));
+                }
+                // children despawn with the chunk, taking their lights along
+                for (block, position) in lights.into_iter().take(light_count) {
+                    parent.spawn((
+                        PointLight {
+                            color: block_surface(block).emissive.into(),
+                            intensity: BLOCK_LIGHT_INTENSITY,
+                            range: BLOCK_LIGHT_RANGE,
+                            ..default()
+                        },
+                        Transform::from_translation(position),
+                        BlockLight,
+                    ));
+                }
+            })
+            .id();
+        map.entities.insert(c, entity);
+
+        if let Some(old_entity) = old {
+            commands.entity(entity).insert(Fade {
+                timer: Timer::from_seconds(params.lod_fade, TimerMode::Once),
+                dir: FadeDir::In,
+            });
+            commands.entity(old_entity).insert(Fade {
+                timer: Timer::from_seconds(params.lod_fade, TimerMode::Once),
+                dir: FadeDir::Out,
             });
-            let entity = commands
-                .spawn((
-                    Mesh3d(handle),
-                    MeshMaterial3d(mat_handle.clone()),
-                    Transform::from_xyz(
-                        c.x as f32 * CHUNK_SIZE as f32,
-                        c.y as f32 * CHUNK_SIZE as f32,
-                        c.z as f32 * CHUNK_SIZE as f32,
-                    ),
-                    Visibility::default(),
-                    Chunk { coord: c, lod },
-                ))
-                .id();
-            map.entities.insert(c, entity);
-
-            if let Some(old_entity) = old {
-                commands.entity(entity).insert(Fade {
-                    timer: Timer::from_seconds(0.5, TimerMode::Once),
-                    dir: FadeDir::In,
-                });
-                commands.entity(old_entity).insert(Fade {
-                    timer: Timer::from_seconds(0.5, TimerMode::Once),
-                    dir: FadeDir::Out,
-                });
-            }
-            cache.colors.insert(c, surface);
-            finished.push(*coord);
         }
-    }
-    for coord in finished {
-        pending.tasks.remove(&coord);
+        cache.colors.insert(c, surface);
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn cleanup_chunks(
     mut commands: Commands,
     chunks: Query<Entity, With<Chunk>>,
     mut map: ResMut<ChunkMap>,
     mut pending: ResMut<PendingTasks>,
     mut cache: ResMut<SurfaceCache>,
+    mut occluders: ResMut<OccluderHeights>,
+    mut occlusion: ResMut<OcclusionState>,
+    mut freeze: ResMut<FreezeChunks>,
+    mut stats: ResMut<GenerationStats>,
+    mut progress: ResMut<LoadingProgress>,
+    mut stash: ResMut<ChunkStash>,
 ) {
     for e in &chunks {
         commands.entity(e).despawn();
     }
+    // empty chunks weigh nothing, so evicting down to zero bytes would keep them
+    for (_, stashed) in stash.chunks.drain() {
+        commands.entity(stashed.entity).despawn();
+    }
+    *stash = ChunkStash::default();
+    *progress = LoadingProgress::default();
+    // a frozen empty world would stay empty
+    freeze.0 = false;
+    stats.recent.clear();
+    occluders.clear();
+    *occlusion = OcclusionState::default();
     map.entities.clear();
     pending.tasks.clear();
+    pending.forced.clear();
     cache.colors.clear();
 }
 
-fn frustum_cull_chunks(
-    cam: Query<(&Frustum, &GlobalTransform), With<Camera3d>>,
-    mut q: Query<(&Transform, &mut Visibility), With<Chunk>>,
+/// Counts the generated chunks in the columns within [`LOADING_RADIUS`] of
+/// the generation centre until all of them exist.
+fn track_loading(
+    params: Res<WorldParams>,
+    map: Res<ChunkMap>,
+    mut progress: ResMut<LoadingProgress>,
+    player: Query<&Transform, With<PlayerCam>>,
 ) {
-    let Ok((frustum, _cam_transform)) = cam.single() else {
+    if progress.finished {
         return;
-    };
-    let aabb = Aabb::from_min_max(Vec3::ZERO, Vec3::splat(CHUNK_SIZE as f32));
-    for (transform, mut vis) in &mut q {
-        let world_from_local = Affine3A::from_mat4(transform.compute_matrix());
-        let visible = frustum.intersects_obb(&aabb, &world_from_local, true, true);
-        *vis = if visible {
-            Visibility::Visible
-        } else {
-            Visibility::Hidden
-        };
     }
-}
-
-fn fade_chunks(
-    time: Res<Time>,
-    mut commands: Commands,
-    mut q: Query<(Entity, &mut Fade, &MeshMaterial3d<StandardMaterial>)>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
-) {
-    for (e, mut fade, mat_handle) in &mut q {
-        if let Some(mat) = materials.get_mut(&mat_handle.0) {
-            fade.timer.tick(time.delta());
-            let t = fade.timer.fraction();
-            let a = match fade.dir {
-                FadeDir::In => t,
-                FadeDir::Out => 1.0 - t,
-            };
-            mat.base_color = mat.base_color.with_alpha(a);
-            if fade.timer.finished() {
-                match fade.dir {
-                    FadeDir::In => {
-                        commands.entity(e).remove::<Fade>();
-                    }
-                    FadeDir::Out => {
-                        commands.entity(e).despawn();
-                    }
+    let player_pos = player.single().map(|t| t.translation).unwrap_or(Vec3::ZERO);
+    let center = params.generation_center(player_pos);
+    let radius = LOADING_RADIUS.min(params.view_width);
+    let mut done = 0;
+    let mut needed = 0;
+    for x in -radius..=radius {
+        for z in -radius..=radius {
+            for y in 0..params.height_chunks {
+                let coord = IVec3::new(center.x + x, y, center.z + z);
+                if !params.in_bounds(coord) {
+                    continue;
+                }
+                needed += 1;
+                if map.entities.contains_key(&coord) {
+                    done += 1;
                 }
             }
         }
     }
+    *progress = LoadingProgress {
+        done,
+        needed,
+        finished: done == needed,
+    };
 }
 
-// === Meshing ===
-
-#[derive(Clone, Copy, Eq, PartialEq)]
-enum BlockType {
-    Empty,
-    Grass,
-    Dirt,
-    Stone,
+/// Chunk containing the world position `pos`, rounding toward negative
+/// infinity so `-0.5` lies in chunk `-1`.
+pub fn world_to_chunk(pos: Vec3) -> IVec3 {
+    (pos / CHUNK_SIZE_F32).floor().as_ivec3()
 }
 
-const EMPTY: BlockType = BlockType::Empty;
-const GRASS: BlockType = BlockType::Grass;
-const DIRT: BlockType = BlockType::Dirt;
-const STONE: BlockType = BlockType::Stone;
-
-impl Voxel for BlockType {
-    fn get_visibility(&self) -> VoxelVisibility {
-        match self {
-            BlockType::Empty => VoxelVisibility::Empty,
-            _ => VoxelVisibility::Opaque,
-        }
-    }
+/// Chunk whose mesh draws the voxel at `voxel`.
+///
+/// Chunk `c` meshes the voxels from `c * CHUNK_SIZE + 1` up to and including
+/// `(c + 1) * CHUNK_SIZE` along each axis.
+fn chunk_of_voxel(voxel: IVec3) -> IVec3 {
+    (voxel - IVec3::ONE).div_euclid(IVec3::splat(CHUNK_SIZE))
 }
 
-impl MergeVoxel for BlockType {
-    type MergeValue = BlockType;
-    fn merge_value(&self) -> Self::MergeValue {
-        *self
+/// Throws away the chunk under the crosshair, or the one the camera is in,
+/// and generates it again at its current level of detail when `F5` (by
+/// default) is pressed.
+///
+/// The surface cache entry goes too, so the chunk is rebuilt from scratch.
+#[allow(clippy::too_many_arguments)]
+fn regenerate_chunk(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    settings: Res<NoiseSettings>,
+    params: Res<WorldParams>,
+    noise: Res<TerrainNoise>,
+    mut map: ResMut<ChunkMap>,
+    mut pending: ResMut<PendingTasks>,
+    mut cache: ResMut<SurfaceCache>,
+    chunks: Query<&Chunk>,
+    cam: Query<&Transform, With<PlayerCam>>,
+) {
+    if !keys.just_pressed(bindings.key(Action::RegenerateChunk)) {
+        return;
     }
+    let Ok(cam) = cam.single() else {
+        return;
+    };
+    let coord = match raycast_block(&noise.0, cam.translation, *cam.forward(), REACH) {
+        Some(voxel) => chunk_of_voxel(voxel),
+        None => world_to_chunk(cam.translation),
+    };
+    let Some(entity) = map.entities.remove(&coord) else {
+        info!("chunk {coord} is not loaded, nothing to regenerate");
+        return;
+    };
+    let lod = chunks.get(entity).map_or(1, |chunk| chunk.lod);
+    commands.entity(entity).despawn();
+    cache.colors.remove(&coord);
+
+    let settings = settings.clone();
+    let max_height = params.max_height();
+    let max_vertices = params.max_chunk_vertices;
+    let usage = params.mesh_usage;
+    pending.spawn(coord, lod, move || {
+        generate_chunk_mesh(coord, lod, settings, None, max_height, max_vertices, usage)
+    });
+    pending.forced.insert(coord);
+    info!("regenerating chunk {coord} at lod {lod}");
 }
 
-fn generate_chunk_mesh(
+/// Periodically finds chunks whose sample points are all hidden from the
+/// camera behind the [`OccluderHeights`] heightfield.
+///
+/// A pass tests at most [`OCCLUSION_CHUNKS_PER_FRAME`] chunks a frame, each
+/// from the camera position of the frame it is tested in.
+fn occlusion_cull_chunks(
+    time: Res<Time>,
+    occluders: Res<OccluderHeights>,
+    mut state: ResMut<OcclusionState>,
+    cams: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    chunks: Query<(Entity, &Chunk, Option<&Children>)>,
+) {
+    // the next pass starts once the previous one is through
+    if state.timer.tick(time.delta()).just_finished() && state.queue.is_empty() {
+        let loaded: HashSet<IVec3> = chunks.iter().map(|(_, chunk, _)| chunk.coord).collect();
+        state.occluded.retain(|coord| loaded.contains(coord));
+        state.queue.extend(chunks.iter().map(|(entity, ..)| entity));
+    }
+    // the drone camera renders in place of the player's while it flies
+    let Some((_, cam)) = cams.iter().find(|(camera, _)| camera.is_active) else {
+        return;
+    };
+    let eye = cam.translation();
+    for _ in 0..OCCLUSION_CHUNKS_PER_FRAME {
+        let Some(entity) = state.queue.pop_front() else {
+            break;
+        };
+        // unloaded since the pass started
+        let Ok((_, chunk, children)) = chunks.get(entity) else {
+            continue;
+        };
+        // chunks without geometry draw nothing either way
+        let occluded = children.is_some_and(|c| !c.is_empty())
+            && chunk_occluded(&occluders.heights, eye, chunk.coord);
+        if occluded {
+            state.occluded.insert(chunk.coord);
+        } else {
+            state.occluded.remove(&chunk.coord);
+        }
+    }
+}
+
+/// Whether every sample point of the chunk at `coord` is hidden from `eye`.
+///
+/// Points are taken on a 3x3x3 lattice spanning the chunk, so the test is
+/// conservative for everything but features fitting between lattice points.
+fn chunk_occluded(heights: &HashMap<IVec2, i32>, eye: Vec3, coord: IVec3) -> bool {
+    let origin = coord.as_vec3() * CHUNK_SIZE_F32;
+    let half = CHUNK_SIZE_F32 / 2.0;
+    for x in 0..3 {
+        for y in 0..3 {
+            for z in 0..3 {
+                let point = origin + Vec3::new(x as f32, y as f32, z as f32) * half;
+                if !ray_blocked(heights, eye, point, coord.xz()) {
+                    return false;
+                }
+            }
+        }
+    }
+    true
+}
+
+/// Whether the segment from `from` to `to` dips below the occluder surface
+/// of some chunk column other than the ones it starts and ends in.
+fn ray_blocked(heights: &HashMap<IVec2, i32>, from: Vec3, to: Vec3, target: IVec2) -> bool {
+    let column = |p: Vec3| (p.xz() / CHUNK_SIZE_F32).floor().as_ivec2();
+    let start = column(from);
+    let delta = to - from;
+    let steps = (delta.length() / OCCLUSION_STEP).ceil() as i32;
+    for i in 1..steps {
+        let p = from + delta * (i as f32 / steps as f32);
+        let col = column(p);
+        if col == start || col == target {
+            continue;
+        }
+        if let Some(&height) = heights.get(&col)
+            && p.y < height as f32
+        {
+            return true;
+        }
+    }
+    false
+}
+
+fn frustum_cull_chunks(
+    cams: Query<(&Camera, &Frustum, &GlobalTransform), With<Camera3d>>,
+    params: Res<WorldParams>,
+    mut occlusion: ResMut<OcclusionState>,
+    mut stats: ResMut<CullStats>,
+    mut q: Query<(&Chunk, &mut Visibility, Option<&Children>)>,
+) {
+    let Some((_, frustum, cam_transform)) = cams.iter().find(|(camera, ..)| camera.is_active)
+    else {
+        return;
+    };
+    *stats = CullStats::default();
+    let cam_chunk = world_to_chunk(cam_transform.translation());
+    let render_radius = params.render_radius();
+    let aabb = Aabb::from_min_max(Vec3::ZERO, Vec3::splat(CHUNK_SIZE_F32));
+    let was_in_frustum = std::mem::take(&mut occlusion.in_frustum);
+    for (chunk, mut vis, children) in &mut q {
+        let dist = (chunk.coord.x - cam_chunk.x)
+            .abs()
+            .max((chunk.coord.z - cam_chunk.z).abs());
+        let distant = dist > render_radius;
+        let world_from_local = Affine3A::from_translation(chunk.coord.as_vec3() * CHUNK_SIZE_F32);
+        let in_frustum = !distant && frustum.intersects_obb(&aabb, &world_from_local, true, true);
+        if in_frustum {
+            occlusion.in_frustum.insert(chunk.coord);
+            // tested from another angle, so it may well be in sight now
+            if !was_in_frustum.contains(&chunk.coord) {
+                occlusion.occluded.remove(&chunk.coord);
+            }
+        }
+        let occluded = in_frustum && occlusion.occluded.contains(&chunk.coord);
+        let visible = in_frustum && !occluded;
+        let parts = children.map_or(0, |c| c.len());
+        stats.chunks += 1;
+        if distant {
+            stats.distant += 1;
+        }
+        if visible {
+            stats.visible += 1;
+            stats.draw_calls += parts;
+        }
+        if occluded {
+            stats.occluded += 1;
+            stats.saved_draw_calls += parts;
+        }
+        *vis = if visible {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn fade_chunks(
+    time: Res<Time>,
+    mut commands: Commands,
+    mut q: Query<(Entity, &mut Fade, Option<&Children>)>,
+    parts: Query<(&ChunkPart, &MeshMaterial3d<ChunkMaterial>)>,
+    mut materials: ResMut<Assets<ChunkMaterial>>,
+) {
+    for (e, mut fade, children) in &mut q {
+        fade.timer.tick(time.delta());
+        let t = fade.timer.fraction();
+        let a = match fade.dir {
+            FadeDir::In => t,
+            FadeDir::Out => 1.0 - t,
+        };
+        for child in children.into_iter().flatten() {
+            if let Ok((part, mat_handle)) = parts.get(*child)
+                && let Some(mat) = materials.get_mut(&mat_handle.0)
+            {
+                mat.base.base_color = mat.base.base_color.with_alpha(a);
+                // opaque parts only blend while fading so they depth sort correctly afterwards
+                mat.base.alpha_mode = if part.translucent || a < 1.0 {
+                    AlphaMode::Blend
+                } else {
+                    AlphaMode::Opaque
+                };
+            }
+        }
+        if fade.timer.finished() {
+            match fade.dir {
+                FadeDir::In => {
+                    commands.entity(e).remove::<Fade>();
+                }
+                FadeDir::Out => {
+                    commands.entity(e).despawn();
+                }
+            }
+        }
+    }
+}
+
+// === Meshing ===
+
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+enum BlockType {
+    Empty,
+    Grass,
+    Dirt,
+    Stone,
+    Sand,
+    Snow,
+    Wood,
+    Leaf,
+    Water,
+    Lava,
+}
+
+const EMPTY: BlockType = BlockType::Empty;
+const GRASS: BlockType = BlockType::Grass;
+const DIRT: BlockType = BlockType::Dirt;
+const STONE: BlockType = BlockType::Stone;
+const SAND: BlockType = BlockType::Sand;
+const SNOW: BlockType = BlockType::Snow;
+const WOOD: BlockType = BlockType::Wood;
+const LEAF: BlockType = BlockType::Leaf;
+const WATER: BlockType = BlockType::Water;
+const LAVA: BlockType = BlockType::Lava;
+
+/// Every block type, indexed by its discriminant.
+const BLOCK_TYPES: [BlockType; 10] = [
+    EMPTY, GRASS, DIRT, STONE, SAND, SNOW, WOOD, LEAF, WATER, LAVA,
+];
+
+/// Blocks of each type in a chunk, attached to its entity.
+///
+/// Tallied from the voxel grid while meshing, so no voxels need to be kept
+/// around. A coarse lod voxel counts for all the blocks it stands for.
+#[derive(Component, Clone, Default, PartialEq, Debug)]
+pub struct BlockCounts([u64; BLOCK_TYPES.len()]);
+
+/// Width in characters of the longest histogram bar.
+const HISTOGRAM_BAR: usize = 20;
+
+impl BlockCounts {
+    fn add(&mut self, block: BlockType, count: u64) {
+        self.0[block as usize] += count;
+    }
+
+    /// Adds the counts of `other` to these.
+    pub fn merge(&mut self, other: &BlockCounts) {
+        for (total, count) in self.0.iter_mut().zip(other.0) {
+            *total += count;
+        }
+    }
+
+    fn total(&self) -> u64 {
+        self.0.iter().sum()
+    }
+
+    /// One line per block type present, most common first, leaving out air,
+    /// e.g. `Stone  61.2% ############ 1234567`.
+    pub fn histogram(&self) -> Vec<String> {
+        let solid = self.total() - self.0[EMPTY as usize];
+        let mut present: Vec<(BlockType, u64)> = BLOCK_TYPES
+            .into_iter()
+            .zip(self.0)
+            .filter(|&(block, count)| block != EMPTY && count > 0)
+            .collect();
+        present.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+        present
+            .into_iter()
+            .map(|(block, count)| {
+                let share = count as f64 / solid as f64;
+                let bar = "#".repeat((share * HISTOGRAM_BAR as f64).round() as usize);
+                format!("{block:?} {:.1}% {bar} {count}", share * 100.0)
+            })
+            .collect()
+    }
+}
+
+/// World height up to which empty space above the terrain fills with water.
+pub const SEA_LEVEL: i32 = 44;
+
+impl Voxel for BlockType {
+    fn get_visibility(&self) -> VoxelVisibility {
+        match self {
+            BlockType::Empty => VoxelVisibility::Empty,
+            BlockType::Water => VoxelVisibility::Translucent,
+            _ => VoxelVisibility::Opaque,
+        }
+    }
+}
+
+impl MergeVoxel for BlockType {
+    type MergeValue = BlockType;
+    fn merge_value(&self) -> Self::MergeValue {
+        *self
+    }
+}
+
+fn block_color(block: BlockType) -> [f32; 4] {
+    match block {
+        GRASS => [0.1, 0.8, 0.1, 1.0],
+        DIRT => [0.55, 0.27, 0.07, 1.0],
+        STONE => [0.6, 0.6, 0.6, 1.0],
+        SAND => [0.86, 0.8, 0.55, 1.0],
+        SNOW => [0.95, 0.95, 0.97, 1.0],
+        WOOD => [0.4, 0.26, 0.13, 1.0],
+        LEAF => [0.2, 0.6, 0.2, 1.0],
+        WATER => [0.15, 0.35, 0.8, 0.6],
+        LAVA => [1.0, 0.4, 0.05, 1.0],
+        _ => [1.0, 1.0, 1.0, 1.0],
+    }
+}
+
+/// Physically based surface parameters of a block type.
+struct BlockSurface {
+    roughness: f32,
+    reflectance: f32,
+    /// Emitted light added on top of the lit vertex color.
+    emissive: LinearRgba,
+}
+
+fn block_surface(block: BlockType) -> BlockSurface {
+    let matte = |roughness| BlockSurface {
+        roughness,
+        reflectance: 0.3,
+        emissive: LinearRgba::BLACK,
+    };
+    match block {
+        WATER => BlockSurface {
+            roughness: 0.05,
+            reflectance: 0.9,
+            emissive: LinearRgba::BLACK,
+        },
+        LAVA => BlockSurface {
+            roughness: 0.4,
+            reflectance: 0.2,
+            emissive: LinearRgba::rgb(2.0, 0.55, 0.05),
+        },
+        SNOW => matte(0.6),
+        SAND | WOOD => matte(0.85),
+        STONE | DIRT | LEAF => matte(1.0),
+        _ => matte(0.9),
+    }
+}
+
+/// Whether the block glows, making it a candidate for a [`BlockLight`].
+fn is_emissive(block: BlockType) -> bool {
+    block_surface(block).emissive != LinearRgba::BLACK
+}
+
+/// Whether terrain behind the block shows through it.
+fn is_translucent(block: BlockType) -> bool {
+    block.get_visibility() == VoxelVisibility::Translucent
+}
+
+/// Material for the part of a chunk made of `block`, starting at `alpha`
+/// so chunks can fade in.
+///
+/// Translucent parts are alpha blended and drawn back to front by Bevy's
+/// transparent pass; they are double sided so the surface stays visible
+/// from below.
+fn block_material(block: BlockType, alpha: f32, wind: f32, brightness: f32) -> ChunkMaterial {
+    let surface = block_surface(block);
+    let translucent = is_translucent(block);
+    let base = StandardMaterial {
+        base_color: Color::linear_rgba(brightness, brightness, brightness, alpha),
+        perceptual_roughness: surface.roughness,
+        reflectance: surface.reflectance,
+        emissive: surface.emissive,
+        alpha_mode: if translucent || alpha < 1.0 {
+            AlphaMode::Blend
+        } else {
+            AlphaMode::Opaque
+        },
+        double_sided: translucent,
+        cull_mode: if translucent {
+            None
+        } else {
+            Some(bevy::render::render_resource::Face::Back)
+        },
+        ..default()
+    };
+    ChunkMaterial {
+        base,
+        extension: WindExtension { strength: wind },
+    }
+}
+
+/// Wind sway weight of every vertex of `block`'s faces.
+fn block_sway(block: BlockType) -> f32 {
+    if block == LEAF { 1.0 } else { 0.0 }
+}
+
+/// Colors used for grass tufts and the different flower kinds.
+const TUFT_COLOR: [f32; 4] = [0.3, 0.75, 0.2, 1.0];
+const FLOWER_COLORS: [[f32; 4]; 4] = [
+    [0.9, 0.15, 0.15, 1.0],
+    [0.95, 0.85, 0.2, 1.0],
+    [0.95, 0.95, 0.95, 1.0],
+    [0.6, 0.3, 0.8, 1.0],
+];
+
+/// Colors grass and leaves tint towards in dry and lush regions.
+const DRY_FOLIAGE: [f32; 3] = [0.7, 0.65, 0.2];
+const LUSH_FOLIAGE: [f32; 3] = [0.02, 0.5, 0.1];
+
+/// Most point lights a single chunk's emissive faces add.
+const MAX_CHUNK_LIGHTS: usize = 4;
+/// Luminous power and reach of a [`BlockLight`].
+const BLOCK_LIGHT_INTENSITY: f32 = 60_000.0;
+const BLOCK_LIGHT_RANGE: f32 = 12.0;
+/// Rough size of a [`BlockLight`] entity, counted into [`Chunk::bytes`] so
+/// stashed lights weigh on [`WorldParams::stash_budget`] too.
+const BLOCK_LIGHT_BYTES: usize = 512;
+
+/// Blocks per lod step that chunk skirts reach below the edge surface.
+const SKIRT_DEPTH: u32 = 2;
+
+/// Horizontal reach of tree canopies and boulders beyond their base column.
+const FEATURE_MARGIN: i32 = 3;
+/// Largest accepted feature spacing, bounding the candidate scan around a chunk.
+const MAX_FEATURE_SPACING: i32 = 16;
+/// Largest accepted tree and boulder density scale.
+pub const MAX_FEATURE_DENSITY: f32 = 4.0;
+
+/// Cave noise value above which a voxel is carved out.
+const CAVE_THRESHOLD: f32 = 0.9;
+
+/// Largest accepted [`NoiseSettings::ridge_height`]; higher cliffs only
+/// turn into spikes.
+pub const MAX_RIDGE_HEIGHT: f32 = 120.0;
+
+/// Largest accepted [`NoiseSettings::offset`] either way. Panned columns
+/// stay clear of `i32` overflow, and `f32` noise inputs still hit every
+/// whole block.
+pub const MAX_OFFSET: i32 = 1 << 24;
+
+/// Blocks the 3D density noise can move the surface up or down by. It has to
+/// outweigh the one block per block height bias for overhangs to form.
+const DENSITY_AMPLITUDE: f32 = 16.0;
+/// Vertical reach of the density field above and below the surface height.
+const DENSITY_REACH: i32 = DENSITY_AMPLITUDE as i32;
+
+/// Surface height of superflat worlds, kept above [`SEA_LEVEL`] so they stay dry.
+const FLAT_HEIGHT: i32 = 48;
+/// Dirt layers below the grass of superflat worlds.
+const FLAT_DIRT_DEPTH: i32 = 3;
+
+/// Terrain column resolved from the noise layers and the biome map.
+struct Column {
+    height: i32,
+    surface: BlockType,
+    subsurface: BlockType,
+    /// Number of subsurface blocks between the surface and the stone.
+    subsurface_depth: i32,
+    tree_density: f32,
+    boulder_density: f32,
+}
+
+/// Noise generators shared by every column of a chunk.
+pub struct NoiseResources {
+    layers: Vec<(FastNoiseLite, f32)>,
+    /// Displaces the layer sample positions, see [`NoiseSettings::warp`].
+    warp: Option<FastNoiseLite>,
+    /// See [`NoiseSettings::offset`], at most [`MAX_OFFSET`] either way,
+    /// applied by [`NoiseResources::pan`].
+    offset: IVec2,
+    /// See [`NoiseSettings::base_height`], kept inside the world.
+    base_height: i32,
+    /// See [`NoiseSettings::ridge_height`], at most [`MAX_RIDGE_HEIGHT`].
+    ridge_height: f32,
+    ridge_mode: RidgeMode,
+    /// Sum of the layer amplitudes, the most the layers add to a column.
+    layer_total: f32,
+    cave: FastNoiseLite,
+    cliff: FastNoiseLite,
+    river: FastNoiseLite,
+    river_width: f32,
+    river_depth: i32,
+    /// 3D noise perturbing the surface, see [`NoiseSettings::overhangs`].
+    density: Option<FastNoiseLite>,
+    biomes: BiomeSampler,
+    trees: FastNoiseLite,
+    boulders: FastNoiseLite,
+    decorations: FastNoiseLite,
+    decoration_density: f32,
+    /// See [`NoiseSettings::caves`].
+    carve_caves: bool,
+    /// See [`NoiseSettings::bedrock`].
+    bedrock: bool,
+    /// See [`NoiseSettings::trees`].
+    grow_trees: bool,
+    /// See [`NoiseSettings::boulders`].
+    place_boulders: bool,
+    /// See [`NoiseSettings::tree_density`], at most [`MAX_FEATURE_DENSITY`].
+    tree_density: f32,
+    /// See [`NoiseSettings::boulder_density`], at most [`MAX_FEATURE_DENSITY`].
+    boulder_density: f32,
+    /// Smallest distance between two feature bases, see [`features_in`].
+    feature_spacing: i32,
+    /// See [`NoiseSettings::smooth_normals`].
+    smooth_normals: bool,
+    /// See [`NoiseSettings::greedy_meshing`].
+    greedy_meshing: bool,
+    /// See [`NoiseSettings::weld_vertices`].
+    weld_vertices: bool,
+    /// Low-frequency climate noise tinting grass and leaves, see
+    /// [`NoiseResources::foliage_color`].
+    climate: FastNoiseLite,
+    foliage_variation: f32,
+    /// Whether caves at or below [`NoiseResources::lava_level`] fill with lava.
+    pub lava: bool,
+    pub lava_level: i32,
+    max_height: i32,
+    /// Replaces the noise terrain with a fixed grass, dirt and stone profile.
+    superflat: bool,
+}
+
+impl NoiseResources {
+    pub fn from_settings(settings: &NoiseSettings, max_height: i32) -> Self {
+        // every layer adds at most its amplitude, biome amplitudes are at most one
+        let total: f32 = settings.layers.iter().map(|l| l.amplitude).sum();
+        let base_height = settings.base_height.clamp(1, max_height - 1);
+        let ridge_height = settings.ridge_height.clamp(0.0, MAX_RIDGE_HEIGHT);
+        let headroom = (max_height - 1 - base_height) as f32 - ridge_height;
+        let scale = if settings.normalize_height && total > headroom {
+            headroom.max(0.0) / total
+        } else {
+            1.0
+        };
+
+        // 2D terrain noise layers for varied heights
+        let mut layers = Vec::new();
+        for layer in &settings.layers {
+            let mut n = FastNoiseLite::with_seed(settings.seed.wrapping_add(layer.seed));
+            n.set_noise_type(Some(match layer.noise {
+                LayerNoise::Perlin => NoiseType::Perlin,
+                LayerNoise::OpenSimplex2S => NoiseType::OpenSimplex2S,
+            }));
+            n.set_frequency(Some(layer.frequency));
+            let fractal = layer.fractal;
+            if fractal.octaves > 1 {
+                // octaves are normalised together, so the amplitude still bounds the layer
+                n.set_fractal_type(Some(match fractal.mode {
+                    FractalMode::Fbm => FractalType::FBm,
+                    FractalMode::Ridged => FractalType::Ridged,
+                    FractalMode::PingPong => FractalType::PingPong,
+                }));
+                n.set_fractal_octaves(Some(fractal.octaves.min(MAX_OCTAVES)));
+                n.set_fractal_gain(Some(fractal.gain));
+                n.set_fractal_lacunarity(Some(fractal.lacunarity));
+            }
+            layers.push((n, layer.amplitude * scale));
+        }
+        let warp = (settings.warp.strength > 0.0).then(|| {
+            let mut n = FastNoiseLite::with_seed(settings.seed.wrapping_add(settings.warp.seed));
+            n.set_domain_warp_type(Some(DomainWarpType::OpenSimplex2));
+            n.set_domain_warp_amp(Some(settings.warp.strength));
+            n.set_frequency(Some(settings.warp.frequency));
+            n
+        });
+
+        // 3D noise for sparse caves and cliffs
+        let mut cave = FastNoiseLite::with_seed(settings.seed.wrapping_add(3));
+        cave.set_noise_type(Some(NoiseType::Perlin));
+        cave.set_frequency(Some(0.05));
+
+        // ridged noise for mountain cliffs
+        let mut cliff = FastNoiseLite::with_seed(settings.seed.wrapping_add(9));
+        cliff.set_noise_type(Some(NoiseType::Perlin));
+        cliff.set_frequency(Some(0.004));
+
+        // rivers follow the zero crossings of this noise
+        let mut river = FastNoiseLite::with_seed(settings.seed.wrapping_add(settings.rivers.seed));
+        river.set_noise_type(Some(NoiseType::Perlin));
+        river.set_frequency(Some(settings.rivers.frequency));
+
+        let density = (settings.overhangs && !settings.superflat).then(|| {
+            let mut n = FastNoiseLite::with_seed(settings.seed.wrapping_add(57));
+            n.set_noise_type(Some(NoiseType::Perlin));
+            n.set_frequency(Some(0.08));
+            n
+        });
+
+        // value noise sampled on integer coordinates acts as a per-column hash
+        let mut trees = FastNoiseLite::with_seed(settings.seed.wrapping_add(1337));
+        trees.set_noise_type(Some(NoiseType::Value));
+        trees.set_frequency(Some(1.0));
+        let mut boulders = FastNoiseLite::with_seed(settings.seed.wrapping_add(4242));
+        boulders.set_noise_type(Some(NoiseType::Value));
+        boulders.set_frequency(Some(1.0));
+        let mut decorations = FastNoiseLite::with_seed(settings.seed.wrapping_add(2024));
+        decorations.set_noise_type(Some(NoiseType::Value));
+        decorations.set_frequency(Some(1.0));
+
+        // regions span a few hundred blocks so the tint reads as climate
+        let mut climate = FastNoiseLite::with_seed(settings.seed.wrapping_add(71));
+        climate.set_noise_type(Some(NoiseType::Perlin));
+        climate.set_frequency(Some(0.004));
+
+        Self {
+            layers,
+            warp,
+            offset: IVec2::from_array(settings.offset)
+                .clamp(IVec2::splat(-MAX_OFFSET), IVec2::splat(MAX_OFFSET)),
+            base_height,
+            ridge_height,
+            ridge_mode: settings.ridge_mode,
+            layer_total: total * scale,
+            cave,
+            cliff,
+            river,
+            river_width: settings.rivers.width.max(0.0),
+            river_depth: settings.rivers.depth.max(0),
+            density,
+            biomes: BiomeSampler::new(&BiomeSettings {
+                seed: settings.seed.wrapping_add(settings.biome.seed),
+                ..settings.biome.clone()
+            }),
+            trees,
+            boulders,
+            decorations,
+            decoration_density: settings.decoration_density.clamp(0.0, 1.0),
+            carve_caves: settings.caves,
+            bedrock: settings.bedrock,
+            grow_trees: settings.trees,
+            place_boulders: settings.boulders,
+            tree_density: settings.tree_density.clamp(0.0, MAX_FEATURE_DENSITY),
+            boulder_density: settings.boulder_density.clamp(0.0, MAX_FEATURE_DENSITY),
+            feature_spacing: settings.feature_spacing.clamp(1, MAX_FEATURE_SPACING),
+            smooth_normals: settings.smooth_normals,
+            greedy_meshing: settings.greedy_meshing,
+            weld_vertices: settings.weld_vertices,
+            climate,
+            foliage_variation: settings.foliage_variation.clamp(0.0, 1.0),
+            lava: settings.lava && !settings.superflat,
+            lava_level: settings.lava_level,
+            max_height,
+            superflat: settings.superflat,
+        }
+    }
+
+    /// Vertical distance from the surface height beyond which voxels are
+    /// always solid below and empty above.
+    fn surface_reach(&self) -> i32 {
+        if self.density.is_some() {
+            DENSITY_REACH
+        } else {
+            0
+        }
+    }
+
+    /// Position the noises are sampled at for the world column `wx`, `wz`.
+    ///
+    /// Every sample goes through here, so the whole world pans together and
+    /// chunk borders stay seamless.
+    fn pan(&self, wx: i32, wz: i32) -> (f32, f32) {
+        ((wx + self.offset.x) as f32, (wz + self.offset.y) as f32)
+    }
+
+    /// Whether the voxel at `wy` in a column of surface `height` is solid,
+    /// before caves are carved.
+    fn solid(&self, height: i32, wx: i32, wy: i32, wz: i32) -> bool {
+        if self.bedrock && wy == 0 {
+            return true;
+        }
+        match &self.density {
+            None => wy <= height,
+            Some(noise) => {
+                let (nx, nz) = self.pan(wx, wz);
+                let offset = noise.get_noise_3d(nx, wy as f32, nz);
+                (height - wy) as f32 + offset * DENSITY_AMPLITUDE > 0.0
+            }
+        }
+    }
+
+    /// Color of `block` at the world position `wx`, `wz`, tinting grass and
+    /// leaves by the climate noise.
+    ///
+    /// The tint only depends on the world position, so faces meeting at a
+    /// chunk border get the same color on both sides.
+    fn foliage_color(&self, block: BlockType, wx: f32, wz: f32) -> [f32; 4] {
+        let mut color = block_color(block);
+        if !matches!(block, GRASS | LEAF) || self.foliage_variation == 0.0 {
+            return color;
+        }
+        let climate = self
+            .climate
+            .get_noise_2d(wx + self.offset.x as f32, wz + self.offset.y as f32);
+        let target = if climate < 0.0 {
+            DRY_FOLIAGE
+        } else {
+            LUSH_FOLIAGE
+        };
+        let t = climate.abs().min(1.0) * self.foliage_variation;
+        for (c, target) in color.iter_mut().zip(target) {
+            *c += (target - *c) * t;
+        }
+        color
+    }
+
+    /// Biome blend of the world column at `wx`, `wz`.
+    fn biome(&self, wx: i32, wz: i32) -> BiomeSample {
+        self.biomes.sample(wx + self.offset.x, wz + self.offset.y)
+    }
+
+    /// Whether the cave noise hollows out the voxel at `wx`, `wy`, `wz`.
+    fn carved(&self, wx: i32, wy: i32, wz: i32) -> bool {
+        if self.superflat || !self.carve_caves || self.bedrock && wy == 0 {
+            return false;
+        }
+        let (nx, nz) = self.pan(wx, wz);
+        self.cave.get_noise_3d(nx, wy as f32, nz) > CAVE_THRESHOLD
+    }
+
+    /// Whether the generated terrain holds a solid block at the world voxel.
+    ///
+    /// Follows the full resolution mesher, lava included, but ignores trees
+    /// and boulders.
+    pub fn block_solid(&self, wx: i32, wy: i32, wz: i32) -> bool {
+        if !(0..self.max_height).contains(&wy) {
+            return false;
+        }
+        let height = terrain_height(self, wx, wz);
+        self.solid(height, wx, wy, wz)
+            && (!self.carved(wx, wy, wz) || self.lava && wy <= self.lava_level)
+    }
+
+    /// Tree or boulder the column at `wx`, `wz` would carry if no stronger
+    /// candidate stood within the feature spacing.
+    fn feature_candidate(&self, wx: i32, wz: i32) -> Option<Candidate> {
+        let column = self.column(wx, wz);
+        let (nx, nz) = self.pan(wx, wz);
+        let t = (self.trees.get_noise_2d(nx, nz) + 1.0) / 2.0;
+        let b = (self.boulders.get_noise_2d(nx, nz) + 1.0) / 2.0;
+        let t_density = column.tree_density;
+        let b_density = column.boulder_density;
+
+        // noise values are never negative, so a zero scale rules every column out
+        let (priority, feature) = if self.grow_trees
+            && column.surface == GRASS
+            && t < t_density * t_density * self.tree_density
+        {
+            let trunk = 4 + (t * 1000.0) as i32 % 3;
+            (t, Feature::Tree { trunk })
+        } else if self.place_boulders
+            && column.surface != SNOW
+            && b < b_density * b_density * self.boulder_density
+        {
+            let radius = 1 + (b * 1000.0) as i32 % 2;
+            (b, Feature::Boulder { radius })
+        } else {
+            return None;
+        };
+        Some(Candidate {
+            priority,
+            height: column.height,
+            feature,
+        })
+    }
+
+    fn column(&self, wx: i32, wz: i32) -> Column {
+        if self.superflat {
+            return Column {
+                height: terrain_height(self, wx, wz),
+                surface: GRASS,
+                subsurface: DIRT,
+                subsurface_depth: FLAT_DIRT_DEPTH,
+                tree_density: 0.0,
+                boulder_density: 0.0,
+            };
+        }
+
+        let biome = self.biome(wx, wz);
+        let height = biome_height(self, &biome, wx, wz);
+
+        let (surface, subsurface) = if height as f32 >= biome.snow_line {
+            (SNOW, STONE)
+        } else if height < SEA_LEVEL {
+            (SAND, SAND)
+        } else {
+            match biome.dominant.params().ground {
+                Ground::Grass => (GRASS, DIRT),
+                Ground::Sand => (SAND, SAND),
+            }
+        };
+
+        Column {
+            height,
+            surface,
+            subsurface,
+            subsurface_depth: 1,
+            tree_density: biome.tree_density,
+            boulder_density: biome.boulder_density,
+        }
+    }
+}
+
+/// Terrain surface height of the world column at `wx`, `wz`, clamped to the
+/// world height.
+///
+/// This is the single source of truth for the ground level: chunk meshing,
+/// the menu preview and anything that needs to know where the ground is
+/// should go through it.
+pub fn terrain_height(noise: &NoiseResources, wx: i32, wz: i32) -> i32 {
+    if noise.superflat {
+        return FLAT_HEIGHT.clamp(1, noise.max_height - 1);
+    }
+
+    biome_height(noise, &noise.biome(wx, wz), wx, wz)
+}
+
+/// [`terrain_height`] for a column whose biome is already sampled, so
+/// callers that need the biome too don't sample it twice.
+fn biome_height(noise: &NoiseResources, biome: &BiomeSample, wx: i32, wz: i32) -> i32 {
+    let (nx, nz) = noise.pan(wx, wz);
+    let (lx, lz) = match &noise.warp {
+        Some(warp) => warp.domain_warp_2d(nx, nz),
+        None => (nx, nz),
+    };
+
+    let mut layered = 0.0;
+    if let Some((first_noise, first_amp)) = noise.layers.first() {
+        let val = (first_noise.get_noise_2d(lx, lz) + 1.0) / 2.0;
+        layered += val * first_amp;
+
+        for (layer, amp) in &noise.layers[1..] {
+            layered += layer.get_noise_2d(lx, lz) * amp;
+        }
+    }
+    let ridge = 1.0 - noise.cliff.get_noise_2d(nx, nz).abs();
+    let mut crest = ridge * ridge * noise.ridge_height * biome.ridge;
+    if noise.ridge_mode == RidgeMode::Multiplicative {
+        crest *= if noise.layer_total > 0.0 {
+            (layered / noise.layer_total).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+    }
+
+    let height = noise.base_height + (layered * biome.amplitude) as i32 + crest as i32;
+    carve_river(noise, wx, wz, height).clamp(1, noise.max_height - 1)
+}
+
+/// Lowers `height` toward the river bed where the river noise is near zero.
+///
+/// The noise is continuous in world space, so channels run on across chunk
+/// borders and get flooded because their bed lies below [`SEA_LEVEL`].
+fn carve_river(noise: &NoiseResources, wx: i32, wz: i32, height: i32) -> i32 {
+    if noise.river_width <= 0.0 {
+        return height;
+    }
+    let (nx, nz) = noise.pan(wx, wz);
+    let distance = noise.river.get_noise_2d(nx, nz).abs() / noise.river_width;
+    let bed = SEA_LEVEL - noise.river_depth;
+    if distance >= 1.0 || height <= bed {
+        return height;
+    }
+    // smoothstep falloff gives sloped banks instead of vertical walls
+    let t = 1.0 - distance;
+    let carve = t * t * (3.0 - 2.0 * t);
+    height - ((height - bed) as f32 * carve).round() as i32
+}
+
+/// Whether `pos` lies inside the water filling terrain below [`SEA_LEVEL`].
+pub fn is_underwater(noise: &NoiseResources, pos: Vec3) -> bool {
+    let (wx, wz) = (pos.x.floor() as i32, pos.z.floor() as i32);
+    // water voxels span `surface + 1..=SEA_LEVEL`
+    pos.y < (SEA_LEVEL + 1) as f32 && pos.y >= (terrain_height(noise, wx, wz) + 1) as f32
+}
+
+/// First solid voxel along the ray from `origin` towards `dir`, if one lies
+/// within `reach` blocks. Trees and boulders count as solid.
+///
+/// Steps through the voxel grid cell by cell, so no voxel the ray touches is
+/// skipped however thin the corner it clips.
+pub fn raycast_block(noise: &NoiseResources, origin: Vec3, dir: Vec3, reach: f32) -> Option<IVec3> {
+    let dir = dir.normalize_or_zero();
+    if dir == Vec3::ZERO {
+        return None;
+    }
+    let mut voxel = origin.floor().as_ivec3();
+    let step = IVec3::from_array(dir.to_array().map(|d| if d < 0.0 { -1 } else { 1 }));
+    // ray length per voxel crossed along each axis, infinite when parallel
+    let delta = dir.recip().abs();
+    let mut next = Vec3::from_array(std::array::from_fn(|axis| {
+        let boundary = if dir[axis] > 0.0 {
+            voxel[axis] as f32 + 1.0 - origin[axis]
+        } else {
+            origin[axis] - voxel[axis] as f32
+        };
+        boundary * delta[axis]
+    }));
+    let features = feature_voxels(noise, origin, origin + dir * reach);
+    loop {
+        if noise.block_solid(voxel.x, voxel.y, voxel.z) || features.contains(&voxel) {
+            return Some(voxel);
+        }
+        let axis = if next.x < next.y && next.x < next.z {
+            0
+        } else if next.y < next.z {
+            1
+        } else {
+            2
+        };
+        if next[axis] > reach {
+            return None;
+        }
+        voxel[axis] += step[axis];
+        next[axis] += delta[axis];
+    }
+}
+
+/// Renders a top-down heightmap of the terrain around the world origin.
+///
+/// Columns are sampled exactly like chunk generation does so the preview
+/// matches the world: surface block colors shaded darker in valleys.
+pub fn heightmap_preview(settings: &NoiseSettings, max_height: i32) -> Image {
+    let noise = NoiseResources::from_settings(settings, max_height);
+    let half = PREVIEW_SIZE as i32 / 2;
+    let mut data = Vec::with_capacity((PREVIEW_SIZE * PREVIEW_SIZE * 4) as usize);
+    for pz in 0..PREVIEW_SIZE as i32 {
+        for px in 0..PREVIEW_SIZE as i32 {
+            let (wx, wz) = ((px - half) * PREVIEW_STRIDE, (pz - half) * PREVIEW_STRIDE);
+            data.extend(map_color(&noise, wx, wz));
+        }
+    }
+    map_image(PREVIEW_SIZE, data)
+}
+
+/// Top-down map color of the world column at `wx`, `wz`: the surface block
+/// color shaded darker in valleys, or water below sea level.
+pub fn map_color(noise: &NoiseResources, wx: i32, wz: i32) -> [u8; 4] {
+    let column = noise.column(wx, wz);
+    let shade = 0.4 + 0.6 * column.height as f32 / noise.max_height as f32;
+    let color = if column.height < SEA_LEVEL {
+        block_color(WATER)
+    } else {
+        noise.foliage_color(column.surface, wx as f32, wz as f32)
+    };
+    let channel = |c: f32| (c * shade * 255.0).clamp(0.0, 255.0) as u8;
+    [channel(color[0]), channel(color[1]), channel(color[2]), 255]
+}
+
+/// Square RGBA image of edge `size` from row-major pixel `data`.
+pub fn map_image(size: u32, data: Vec<u8>) -> Image {
+    use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+    Image::new(
+        Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+fn generate_chunk_mesh(
+    coord: IVec3,
+    lod: u32,
+    settings: NoiseSettings,
+    surface: Option<Vec<[f32; 4]>>,
+    max_height: i32,
+    max_vertices: usize,
+    usage: RenderAssetUsages,
+) -> ChunkTaskResult {
+    let start = Instant::now();
+    let noise = NoiseResources::from_settings(&settings, max_height);
+    let (geometry, surface) = build_geometry(coord, lod, &noise, surface);
+    if let Some((vertices, indices)) = geometry.over_budget(max_vertices) {
+        warn!(
+            "chunk {coord} at lod {lod} has {vertices} vertices and {indices} indices, \
+             above the ceiling of {max_vertices} vertices"
+        );
+    }
+    let meshes = geometry
+        .parts
+        .into_iter()
+        .map(|(block, part)| (block, part.into_mesh(usage)))
+        .collect();
+    ChunkTaskResult {
+        coord,
+        lod,
+        meshes,
+        surface,
+        min_surface: geometry.min_surface,
+        duration: start.elapsed(),
+        lights: geometry.lights,
+        blocks: geometry.blocks,
+    }
+}
+
+/// Working memory of [`build_mesh`], kept per thread so the pool threads
+/// stop allocating it anew for every chunk.
+#[derive(Default)]
+struct MeshScratch {
+    voxels: Vec<BlockType>,
+    quads: Option<GreedyQuadsBuffer>,
+    /// Single-face quads of the naive mesher, copied into `quads`.
+    unit_quads: UnitQuadBuffer,
+    /// Feature candidates around the chunk, see [`features_in`].
+    candidates: Vec<Option<Candidate>>,
+}
+
+thread_local! {
+    static SCRATCH: RefCell<MeshScratch> = RefCell::default();
+}
+
+/// Builds the chunk geometry at the mesher resolution matching `lod`.
+fn build_geometry(
+    coord: IVec3,
+    lod: u32,
+    noise: &NoiseResources,
+    surface: Option<Vec<[f32; 4]>>,
+) -> (ChunkMeshes, Vec<[f32; 4]>) {
+    SCRATCH.with_borrow_mut(|scratch| match lod {
+        1 => build_mesh::<LOD1_PADDED>(coord, lod, noise, surface, scratch),
+        2 => build_mesh::<LOD2_PADDED>(coord, lod, noise, surface, scratch),
+        _ => build_mesh::<LOD1_PADDED>(coord, 1, noise, surface, scratch),
+    })
+}
+
+/// World block sampled by the padded voxel `voxel` of the chunk at `coord`
+/// meshed at `lod`. Voxel 0 along an axis is the padding layer just below
+/// the chunk's own blocks.
+fn voxel_to_world(coord: IVec3, lod: u32, voxel: IVec3) -> IVec3 {
+    coord * CHUNK_SIZE + (voxel - IVec3::ONE) * lod as i32
+}
+
+/// Padded voxel of the chunk at `coord` meshed at `lod` whose sample covers
+/// the world block `block`, if it lies inside the `N`-wide voxel grid.
+fn world_to_voxel<const N: u32>(coord: IVec3, lod: u32, block: IVec3) -> Option<IVec3> {
+    let voxel = (block - coord * CHUNK_SIZE).div_euclid(IVec3::splat(lod as i32)) + IVec3::ONE;
+    (voxel.min_element() >= 0 && voxel.max_element() < N as i32).then_some(voxel)
+}
+
+/// Writes `block` at the world block `pos` into the full-detail voxels of
+/// the chunk at `coord`, if it falls inside the padded voxel grid.
+fn set_block<const N: u32>(
+    voxels: &mut [BlockType],
+    coord: IVec3,
+    pos: IVec3,
+    block: BlockType,
+    replace: bool,
+) {
+    let Some(voxel) = world_to_voxel::<N>(coord, 1, pos) else {
+        return;
+    };
+    let shape = ConstShape3u32::<{ N }, { N }, { N }> {};
+    let idx = shape.linearize(voxel.as_uvec3().to_array()) as usize;
+    if replace || voxels[idx] == EMPTY {
+        voxels[idx] = block;
+    }
+}
+
+/// Calls `put` with every world block of `feature` rooted on the world
+/// block `base`, its block type and whether it replaces terrain rather than
+/// only filling empty space.
+///
+/// Trees are a trunk with a leaf canopy on top, boulders a small stone ball
+/// resting on `base`.
+fn feature_blocks(feature: Feature, base: IVec3, mut put: impl FnMut(IVec3, BlockType, bool)) {
+    match feature {
+        Feature::Tree { trunk } => {
+            let top = base.y + trunk;
+            for dy in -2..=1 {
+                let radius: i32 = if dy >= 0 { 1 } else { 2 };
+                for dx in -radius..=radius {
+                    for dz in -radius..=radius {
+                        if dx.abs() == radius && dz.abs() == radius && dy != -1 {
+                            continue;
+                        }
+                        put(IVec3::new(base.x + dx, top + dy, base.z + dz), LEAF, false);
+                    }
+                }
+            }
+            for y in 1..=trunk {
+                put(base + IVec3::Y * y, WOOD, true);
+            }
+        }
+        Feature::Boulder { radius } => {
+            let center = base + IVec3::Y;
+            for dx in -radius..=radius {
+                for dy in -radius..=radius {
+                    for dz in -radius..=radius {
+                        if dx * dx + dy * dy + dz * dz <= radius * radius {
+                            put(center + IVec3::new(dx, dy, dz), STONE, false);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Tree or boulder rooted on a terrain column.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Feature {
+    Tree { trunk: i32 },
+    Boulder { radius: i32 },
+}
+
+/// Feature a column would carry before spacing is enforced.
+#[derive(Clone, Copy)]
+struct Candidate {
+    /// Lower values win against nearby candidates.
+    priority: f32,
+    height: i32,
+    feature: Feature,
+}
+
+/// Scatters trees and boulders on full-detail chunks.
+///
+/// Feature bases are scanned in a margin around the chunk so canopies
+/// crossing the border are generated identically by both neighbours.
+fn place_features<const N: u32>(
+    voxels: &mut [BlockType],
+    candidates: &mut Vec<Option<Candidate>>,
     coord: IVec3,
-    lod: u32,
-    settings: NoiseSettings,
-    surface: Option<Vec<[f32; 4]>>,
-) -> (Mesh, Vec<[f32; 4]>) {
-    match lod {
-        1 => build_mesh::<{ CHUNK_SIZE_U32 + 3 }>(coord, lod, &settings, surface),
-        2 => build_mesh::<{ LOD2_SIZE_U32 + 3 }>(coord, lod, &settings, surface),
-        _ => build_mesh::<{ CHUNK_SIZE_U32 + 3 }>(coord, 1, &settings, surface),
+    noise: &NoiseResources,
+) {
+    let origin = coord * CHUNK_SIZE;
+    let min = origin.xz() + IVec2::splat(-1 - FEATURE_MARGIN);
+    let max = origin.xz() + IVec2::splat(N as i32 - 2 + FEATURE_MARGIN);
+    for (pos, height, feature) in features_in(noise, min, max, candidates) {
+        feature_blocks(
+            feature,
+            IVec3::new(pos.x, height, pos.y),
+            |pos, block, replace| {
+                set_block::<N>(voxels, coord, pos, block, replace);
+            },
+        );
+    }
+}
+
+/// World blocks taken by the trees and boulders that reach into the columns
+/// between `a` and `b`, as [`place_features`] would leave them.
+fn feature_voxels(noise: &NoiseResources, a: Vec3, b: Vec3) -> HashSet<IVec3> {
+    let mut voxels = HashSet::new();
+    if noise.superflat {
+        return voxels;
+    }
+    let min = a.min(b).floor().as_ivec3().xz() - IVec2::splat(FEATURE_MARGIN);
+    let max = a.max(b).floor().as_ivec3().xz() + IVec2::splat(FEATURE_MARGIN);
+    for (pos, height, feature) in features_in(noise, min, max, &mut Vec::new()) {
+        feature_blocks(
+            feature,
+            IVec3::new(pos.x, height, pos.y),
+            |pos, _, replace| {
+                // leaves and boulders do not displace water, see `build_mesh`
+                let flooded = pos.y > terrain_height(noise, pos.x, pos.z) && pos.y <= SEA_LEVEL;
+                if replace || !flooded {
+                    voxels.insert(pos);
+                }
+            },
+        );
+    }
+    voxels
+}
+
+/// Features rooted in the world columns `min..=max`, thinned so that no two
+/// lie closer than the feature spacing.
+///
+/// A candidate survives only if it beats every candidate within the
+/// spacing. That decision depends on world-space noise alone, so every
+/// chunk whose margin covers a column agrees on its feature, whichever of
+/// them is generated first.
+fn features_in(
+    noise: &NoiseResources,
+    min: IVec2,
+    max: IVec2,
+    candidates: &mut Vec<Option<Candidate>>,
+) -> Vec<(IVec2, i32, Feature)> {
+    let reach = noise.feature_spacing - 1;
+    let (lo, hi) = (min - IVec2::splat(reach), max + IVec2::splat(reach));
+    let side = (hi.x - lo.x + 1) as usize;
+    candidates.clear();
+    for wz in lo.y..=hi.y {
+        for wx in lo.x..=hi.x {
+            candidates.push(noise.feature_candidate(wx, wz));
+        }
+    }
+    let at = |wx: i32, wz: i32| candidates[(wz - lo.y) as usize * side + (wx - lo.x) as usize];
+    // ties fall back to the world position so exactly one candidate wins
+    let rank = |c: &Candidate, wx: i32, wz: i32| (c.priority, wx, wz);
+
+    let mut features = Vec::new();
+    for wz in min.y..=max.y {
+        for wx in min.x..=max.x {
+            let Some(own) = at(wx, wz) else {
+                continue;
+            };
+            let beaten = (wz - reach..=wz + reach).any(|oz| {
+                (wx - reach..=wx + reach).any(|ox| {
+                    (ox, oz) != (wx, wz)
+                        && at(ox, oz).is_some_and(|other| {
+                            rank(&other, ox, oz).partial_cmp(&rank(&own, wx, wz))
+                                == Some(std::cmp::Ordering::Less)
+                        })
+                })
+            });
+            if !beaten {
+                features.push((IVec2::new(wx, wz), own.height, own.feature));
+            }
+        }
+    }
+    features
+}
+
+/// Chunk geometry split by block type, in order of first appearance.
+#[derive(Default, Clone, PartialEq, Debug)]
+struct ChunkMeshes {
+    parts: Vec<(BlockType, ChunkGeometry)>,
+    /// Lower bound of the terrain surface height across the chunk's columns.
+    min_surface: i32,
+    /// Chunk-local spots in front of exposed emissive faces to place point
+    /// lights at, at most [`MAX_CHUNK_LIGHTS`].
+    lights: Vec<(BlockType, Vec3)>,
+    blocks: BlockCounts,
+}
+
+impl ChunkMeshes {
+    /// Total vertex and index counts of all parts, if the vertices exceed
+    /// `max_vertices`.
+    fn over_budget(&self, max_vertices: usize) -> Option<(usize, usize)> {
+        let (vertices, indices) = self.parts.iter().fold((0, 0), |(v, i), (_, part)| {
+            (v + part.positions.len(), i + part.indices.len())
+        });
+        (vertices > max_vertices).then_some((vertices, indices))
+    }
+
+    /// Gives every opaque vertex the average normal of the exposed block
+    /// faces meeting at its corner.
+    ///
+    /// The faces are read from the padded `voxels` around the corner rather
+    /// than from this chunk's quads, so corners on a chunk border also see
+    /// the faces of the neighbour and both chunks agree on their normal.
+    /// Grass tops blend into dirt sides; water keeps its flat normals.
+    fn smooth_normals<const N: u32>(&mut self, voxels: &[BlockType], lod: u32) {
+        let shape = ConstShape3u32::<{ N }, { N }, { N }> {};
+        let opaque = |voxel: UVec3| {
+            voxels[shape.linearize(voxel.to_array()) as usize].get_visibility()
+                == VoxelVisibility::Opaque
+        };
+        for (_, part) in self.parts.iter_mut().filter(|(b, _)| !is_translucent(*b)) {
+            for (p, n) in part.positions.iter().zip(&mut part.normals) {
+                // positions sit one padding voxel short of their corner
+                let corner = (Vec3::from(*p) / lod as f32).round().as_uvec3() + UVec3::ONE;
+                let mut sum = IVec3::ZERO;
+                for side in 0..8 {
+                    let side = UVec3::new(side & 1, (side >> 1) & 1, side >> 2);
+                    let voxel = corner + side - UVec3::ONE;
+                    if !opaque(voxel) {
+                        continue;
+                    }
+                    // a face touching the corner is open if the voxel across it is
+                    for axis in 0..3 {
+                        let mut across = voxel;
+                        across[axis] = corner[axis] - side[axis];
+                        if !opaque(across) {
+                            sum[axis] += 1 - 2 * side[axis] as i32;
+                        }
+                    }
+                }
+                *n = sum.as_vec3().normalize_or(Vec3::from(*n)).to_array();
+            }
+        }
+    }
+
+    /// Welds the vertices of every part, returning the vertex counts before
+    /// and after.
+    fn weld(&mut self) -> (usize, usize) {
+        let count = |parts: &[(BlockType, ChunkGeometry)]| -> usize {
+            parts.iter().map(|(_, part)| part.positions.len()).sum()
+        };
+        let before = count(&self.parts);
+        for (_, part) in &mut self.parts {
+            part.weld();
+        }
+        (before, count(&self.parts))
+    }
+
+    fn part(&mut self, block: BlockType) -> &mut ChunkGeometry {
+        let index = match self.parts.iter().position(|(b, _)| *b == block) {
+            Some(index) => index,
+            None => {
+                self.parts.push((block, ChunkGeometry::default()));
+                self.parts.len() - 1
+            }
+        };
+        &mut self.parts[index].1
+    }
+}
+
+/// CPU-side vertex and index buffers produced by chunk meshing.
+#[derive(Default, Clone, PartialEq, Debug)]
+struct ChunkGeometry {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    colors: Vec<[f32; 4]>,
+    /// Wind sway weight per vertex, see [`ATTRIBUTE_SWAY`].
+    sway: Vec<f32>,
+    indices: Vec<u32>,
+}
+
+impl ChunkGeometry {
+    /// Merges vertices whose position, normal, color and sway all match,
+    /// pointing the indices at the vertex kept.
+    ///
+    /// Only exact duplicates merge, so the surface looks the same; faceted
+    /// normals keep the faces of a block edge apart.
+    fn weld(&mut self) {
+        let mut kept: HashMap<[u32; 11], u32> = HashMap::with_capacity(self.positions.len());
+        let mut remap = Vec::with_capacity(self.positions.len());
+        let mut welded = ChunkGeometry::default();
+        for i in 0..self.positions.len() {
+            let (p, n, c) = (self.positions[i], self.normals[i], self.colors[i]);
+            let attributes = [p[0], p[1], p[2], n[0], n[1], n[2], c[0], c[1], c[2], c[3]];
+            let key: [u32; 11] = std::array::from_fn(|k| {
+                attributes.get(k).copied().unwrap_or(self.sway[i]).to_bits()
+            });
+            let index = *kept.entry(key).or_insert_with(|| {
+                welded.positions.push(p);
+                welded.normals.push(n);
+                welded.colors.push(c);
+                welded.sway.push(self.sway[i]);
+                welded.positions.len() as u32 - 1
+            });
+            remap.push(index);
+        }
+        welded.indices = self.indices.iter().map(|&i| remap[i as usize]).collect();
+        *self = welded;
+    }
+
+    /// Appends a decoration made of two crossed, double-sided quads standing on `base`.
+    fn push_cross_quads(&mut self, base: Vec3, height: f32, color: [f32; 4]) {
+        let (lo, hi) = (0.15, 0.85);
+        let diagonals = [((lo, lo), (hi, hi)), ((hi, lo), (lo, hi))];
+        for ((x0, z0), (x1, z1)) in diagonals {
+            let start = self.positions.len() as u32;
+            self.positions.extend_from_slice(&[
+                [base.x + x0, base.y, base.z + z0],
+                [base.x + x1, base.y, base.z + z1],
+                [base.x + x1, base.y + height, base.z + z1],
+                [base.x + x0, base.y + height, base.z + z0],
+            ]);
+            // Upward normals keep both sides lit like the ground they stand on.
+            self.normals.extend_from_slice(&[[0.0, 1.0, 0.0]; 4]);
+            self.colors.extend_from_slice(&[color; 4]);
+            // rooted at the ground, only the tips move
+            self.sway.extend_from_slice(&[0.0, 0.0, 1.0, 1.0]);
+            self.indices.extend_from_slice(&[
+                start,
+                start + 1,
+                start + 2,
+                start,
+                start + 2,
+                start + 3,
+                start,
+                start + 2,
+                start + 1,
+                start,
+                start + 3,
+                start + 2,
+            ]);
+        }
+    }
+
+    /// Appends a single-sided quad facing `normal`, whatever the corner order,
+    /// colored per corner.
+    fn push_quad(&mut self, corners: [Vec3; 4], normal: Vec3, colors: [[f32; 4]; 4]) {
+        let start = self.positions.len() as u32;
+        self.positions.extend(corners.map(|c| c.to_array()));
+        self.normals.extend_from_slice(&[normal.to_array(); 4]);
+        self.colors.extend_from_slice(&colors);
+        self.sway.extend_from_slice(&[0.0; 4]);
+        let facing = (corners[1] - corners[0])
+            .cross(corners[2] - corners[0])
+            .dot(normal);
+        if facing > 0.0 {
+            self.indices.extend_from_slice(&[
+                start,
+                start + 1,
+                start + 2,
+                start,
+                start + 2,
+                start + 3,
+            ]);
+        } else {
+            self.indices.extend_from_slice(&[
+                start,
+                start + 2,
+                start + 1,
+                start,
+                start + 3,
+                start + 2,
+            ]);
+        }
+    }
+
+    fn into_mesh(self, usage: RenderAssetUsages) -> Mesh {
+        use bevy::render::mesh::PrimitiveTopology;
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList, usage);
+        let vertex_count = self.positions.len();
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, self.positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, self.normals);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, self.colors);
+        mesh.insert_attribute(ATTRIBUTE_SWAY, self.sway);
+        mesh.insert_indices(compact_indices(vertex_count, self.indices));
+        mesh
+    }
+}
+
+/// Bytes of vertex and index data in `mesh`.
+fn mesh_bytes(mesh: &Mesh) -> usize {
+    let indices = match mesh.indices() {
+        Some(Indices::U16(indices)) => indices.len() * 2,
+        Some(Indices::U32(indices)) => indices.len() * 4,
+        None => 0,
+    };
+    mesh.count_vertices() * mesh.get_vertex_size() as usize + indices
+}
+
+/// Uses 16-bit indices when every vertex is addressable with them, halving
+/// the index buffer of the many small chunk parts.
+fn compact_indices(vertex_count: usize, indices: Vec<u32>) -> Indices {
+    if vertex_count <= u16::MAX as usize + 1 {
+        Indices::U16(indices.into_iter().map(|i| i as u16).collect())
+    } else {
+        Indices::U32(indices)
     }
 }
 
 fn build_mesh<const N: u32>(
     coord: IVec3,
     lod: u32,
-    settings: &NoiseSettings,
+    noise: &NoiseResources,
     surface_in: Option<Vec<[f32; 4]>>,
-) -> (Mesh, Vec<[f32; 4]>) {
+    scratch: &mut MeshScratch,
+) -> (ChunkMeshes, Vec<[f32; 4]>) {
     let size = N - 2;
 
-    let shape = ConstShape3u32::<{ N }, { N }, { N }> {};
-    let mut voxels = vec![EMPTY; (N * N * N) as usize];
+    let shape = ConstShape3u32::<{ N }, { N }, { N }> {};
+    // the previous chunk's voxels must not leak into this one
+    let MeshScratch {
+        voxels,
+        quads,
+        unit_quads,
+        candidates,
+    } = scratch;
+    voxels.clear();
+    voxels.resize((N * N * N) as usize, EMPTY);
+
+    let cached_surface = surface_in.is_some();
+    let mut surface_colors = surface_in
+        .unwrap_or_else(|| vec![[0.0, 0.0, 0.0, 1.0]; (CHUNK_SIZE_U32 * CHUNK_SIZE_U32) as usize]);
+
+    // surface height and block of every padded column, indexed by z * (size + 2) + x
+    let mut surfaces = Vec::with_capacity(((size + 2) * (size + 2)) as usize);
+
+    for z in 0..=size + 1 {
+        for x in 0..=size + 1 {
+            let column_pos = voxel_to_world(coord, lod, UVec3::new(x, 0, z).as_ivec3());
+            let (wx, wz) = (column_pos.x, column_pos.z);
+
+            let column = noise.column(wx, wz);
+            let height = column.height;
+            surfaces.push((height, column.surface));
+
+            for y in 1..=size + 1 {
+                let wy = voxel_to_world(coord, lod, IVec3::new(0, y as i32, 0)).y;
+                let idx = shape.linearize([x, y, z]) as usize;
+                let flood = wy > height && wy <= SEA_LEVEL;
+                if wy > height + noise.surface_reach() {
+                    if flood {
+                        voxels[idx] = WATER;
+                    }
+                    continue;
+                }
+
+                let mut block = EMPTY;
+
+                for offset in (0..lod).rev() {
+                    let sample_y = wy + offset as i32;
+                    if !noise.solid(height, wx, sample_y, wz) {
+                        continue;
+                    }
+
+                    if noise.carved(wx, sample_y, wz) {
+                        if noise.lava && sample_y <= noise.lava_level {
+                            block = LAVA;
+                            break;
+                        }
+                        continue; // carve cave
+                    }
+
+                    block = if !noise.solid(height, wx, sample_y + 1, wz) {
+                        column.surface
+                    } else if sample_y >= height - column.subsurface_depth {
+                        column.subsurface
+                    } else {
+                        STONE
+                    };
+                    break;
+                }
+
+                if block == EMPTY {
+                    if flood {
+                        voxels[idx] = WATER;
+                    }
+                } else {
+                    voxels[idx] = block;
+                    if lod == 1
+                        && x > 0
+                        && x <= CHUNK_SIZE_U32
+                        && z > 0
+                        && z <= CHUNK_SIZE_U32
+                        && wy == height
+                    {
+                        let lx = x - 1;
+                        let lz = z - 1;
+                        surface_colors[(lx * CHUNK_SIZE_U32 + lz) as usize] =
+                            noise.foliage_color(block, wx as f32, wz as f32);
+                    }
+                }
+            }
+        }
+    }
+
+    if lod == 1 && !noise.superflat {
+        place_features::<N>(voxels, candidates, coord, noise);
+    }
+
+    let mut meshes = ChunkMeshes {
+        // coarse lods skip columns, so allow the terrain to dip between samples
+        min_surface: surfaces.iter().map(|(h, _)| *h).min().unwrap_or(0)
+            - (lod as i32 - 1)
+            - noise.surface_reach(),
+        ..Default::default()
+    };
+    // the chunk owns its grid without the padding ring, see `chunk_of_voxel`
+    let weight = u64::from(lod).pow(3);
+    for z in 2..=size {
+        for y in 2..=size {
+            for x in 2..=size {
+                meshes
+                    .blocks
+                    .add(voxels[shape.linearize([x, y, z]) as usize], weight);
+            }
+        }
+    }
+
+    let buffer = quads.get_or_insert_with(|| GreedyQuadsBuffer::new(voxels.len()));
+    buffer.reset(voxels.len());
+    if noise.greedy_meshing {
+        greedy_quads(
+            voxels,
+            &shape,
+            [1; 3],
+            [size + 1; 3],
+            &RIGHT_HANDED_Y_UP_CONFIG.faces,
+            buffer,
+        );
+    } else {
+        unit_quads.reset();
+        visible_block_faces(
+            voxels,
+            &shape,
+            [1; 3],
+            [size + 1; 3],
+            &RIGHT_HANDED_Y_UP_CONFIG.faces,
+            unit_quads,
+        );
+        // unit quads are quads of size one, so the assembly below serves both
+        for (group, units) in buffer.quads.groups.iter_mut().zip(&unit_quads.groups) {
+            group.extend(units.iter().map(|&unit| UnorientedQuad::from(unit)));
+        }
+    }
+
+    let origin = coord * CHUNK_SIZE;
+    for (face, group) in RIGHT_HANDED_Y_UP_CONFIG
+        .faces
+        .iter()
+        .zip(buffer.quads.groups.iter())
+    {
+        for quad in group.iter() {
+            let voxel = voxels[shape.linearize(quad.minimum) as usize];
+            let mut face_positions = face.quad_mesh_positions(quad, lod as f32);
+            for p in &mut face_positions {
+                p[0] -= lod as f32;
+                p[1] -= lod as f32;
+                p[2] -= lod as f32;
+            }
+            if is_emissive(voxel) && meshes.lights.len() < MAX_CHUNK_LIGHTS {
+                let centre = face_positions.iter().map(|&p| Vec3::from(p)).sum::<Vec3>() / 4.0;
+                let normal = Vec3::from(face.quad_mesh_normals()[0]);
+                meshes.lights.push((voxel, centre + normal * 0.5));
+            }
+            let geometry = meshes.part(voxel);
+            let start = geometry.positions.len() as u32;
+            geometry.positions.extend_from_slice(&face_positions);
+            geometry
+                .normals
+                .extend_from_slice(&face.quad_mesh_normals());
+            geometry
+                .indices
+                .extend_from_slice(&face.quad_mesh_indices(start));
+
+            if lod == 1 || !cached_surface || voxel == WATER {
+                // greedy quads span many columns, so tint per corner
+                for p in &face_positions {
+                    geometry.colors.push(noise.foliage_color(
+                        voxel,
+                        origin.x as f32 + p[0],
+                        origin.z as f32 + p[2],
+                    ));
+                }
+            } else {
+                let lx = ((quad.minimum[0] - 1) * lod).min(CHUNK_SIZE_U32 - 1);
+                let lz = ((quad.minimum[2] - 1) * lod).min(CHUNK_SIZE_U32 - 1);
+                let color = surface_colors[(lx * CHUNK_SIZE_U32 + lz) as usize];
+                geometry.colors.extend_from_slice(&[color; 4]);
+            }
+            geometry.sway.extend_from_slice(&[block_sway(voxel); 4]);
+        }
+    }
+
+    // before skirts and decorations, which would drag the terrain normals along
+    if noise.smooth_normals {
+        meshes.smooth_normals::<N>(voxels, lod);
+    }
+
+    push_skirts(&mut meshes, &surfaces, coord, lod, size, noise);
+
+    if lod == 1 && !noise.superflat && noise.decoration_density > 0.0 {
+        for lz in 0..CHUNK_SIZE {
+            for lx in 0..CHUNK_SIZE {
+                let (height, surface) = surfaces[((lz + 1) * (size as i32 + 2) + lx + 1) as usize];
+                let ly = height + 1 - origin.y;
+                if surface != GRASS || !(0..CHUNK_SIZE).contains(&ly) {
+                    continue;
+                }
+                let above = IVec3::new(lx, ly, lz) + IVec3::ONE;
+                if voxels[shape.linearize(above.as_uvec3().to_array()) as usize] != EMPTY {
+                    continue;
+                }
+                // the ground of the lowest layer lives in the chunk below
+                let ground = above - IVec3::Y;
+                if ly > 0 && voxels[shape.linearize(ground.as_uvec3().to_array()) as usize] != GRASS
+                {
+                    continue;
+                }
+
+                let wx = origin.x + lx;
+                let wz = origin.z + lz;
+                let (nx, nz) = noise.pan(wx, wz);
+                let r = (noise.decorations.get_noise_2d(nx, nz) + 1.0) / 2.0;
+                if r >= noise.decoration_density {
+                    continue;
+                }
+                let kind = r / noise.decoration_density;
+                let (height, color) = if kind < 0.8 {
+                    (0.6, TUFT_COLOR)
+                } else {
+                    let flower = ((kind - 0.8) / 0.2 * FLOWER_COLORS.len() as f32) as usize;
+                    (0.5, FLOWER_COLORS[flower.min(FLOWER_COLORS.len() - 1)])
+                };
+                meshes.part(GRASS).push_cross_quads(
+                    IVec3::new(lx, ly, lz).as_vec3(),
+                    height,
+                    color,
+                );
+            }
+        }
+    }
+
+    if noise.weld_vertices {
+        let (before, after) = meshes.weld();
+        debug!("chunk {coord} at lod {lod} welded from {before} to {after} vertices");
+    }
+
+    (meshes, surface_colors)
+}
+
+/// Hangs outward-facing skirt quads below the surface along the chunk border.
+///
+/// Neighbours at another lod sample different columns, so their border
+/// heights disagree and leave see-through cracks. Each skirt starts at the
+/// lower of the edge column and the padding column beyond it: above that the
+/// mesher already emitted the exposed side faces, and below it the skirt
+/// stays hidden inside a neighbour of the same lod.
+fn push_skirts(
+    meshes: &mut ChunkMeshes,
+    surfaces: &[(i32, BlockType)],
+    coord: IVec3,
+    lod: u32,
+    size: u32,
+    noise: &NoiseResources,
+) {
+    let lod_i = lod as i32;
+    let (row, last) = (size as usize + 2, size as usize);
+    let base_y = coord.y * CHUNK_SIZE;
+    // top of the voxel holding world height `h`, in mesh units
+    let top = |h: i32| ((h - base_y).div_euclid(lod_i) + 1) * lod_i;
+    let meshed = 2 * lod_i..=last as i32 * lod_i;
+    let depth = (SKIRT_DEPTH * lod) as f32;
+    // the mesher skips the padding ring, so voxel `i` spans `(i - 1) * lod..i * lod`
+    let (low, high) = (lod as f32, (last as u32 * lod) as f32);
+    let origin = (coord * CHUNK_SIZE).as_vec3();
+
+    for i in 2..=last {
+        let (a, b) = ((i as u32 - 1) * lod, i as u32 * lod);
+        let (a, b) = (a as f32, b as f32);
+        // (edge column, padding column, normal, corners at the plane)
+        let sides = [
+            (i * row + 2, i * row + 1, Vec3::NEG_X, [(low, a), (low, b)]),
+            (
+                i * row + last,
+                i * row + last + 1,
+                Vec3::X,
+                [(high, a), (high, b)],
+            ),
+            (2 * row + i, row + i, Vec3::NEG_Z, [(a, low), (b, low)]),
+            (
+                last * row + i,
+                (last + 1) * row + i,
+                Vec3::Z,
+                [(a, high), (b, high)],
+            ),
+        ];
+        for (own, pad, normal, [(x0, z0), (x1, z1)]) in sides {
+            let (height, surface) = surfaces[own];
+            if !meshed.contains(&top(height)) {
+                continue;
+            }
+            let upper = top(height.min(surfaces[pad].0));
+            if upper <= lod_i {
+                continue;
+            }
+            let upper = upper as f32;
+            let lower = upper - depth;
+            let corners = [
+                Vec3::new(x0, lower, z0),
+                Vec3::new(x1, lower, z1),
+                Vec3::new(x1, upper, z1),
+                Vec3::new(x0, upper, z0),
+            ];
+            let colors =
+                corners.map(|c| noise.foliage_color(surface, origin.x + c.x, origin.z + c.z));
+            meshes.part(surface).push_quad(corners, normal, colors);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::{LayerFractal, NoiseLayer, RiverSettings, WarpSettings};
+    use bevy::asset::AssetPlugin;
+    use bevy::state::app::StatesPlugin;
+    use bevy::time::TimeUpdateStrategy;
+
+    /// Fixed settings so generation tests never depend on `settings.json`.
+    fn test_settings() -> NoiseSettings {
+        let layer = |seed, frequency, amplitude| NoiseLayer {
+            seed,
+            frequency,
+            amplitude,
+            noise: LayerNoise::Perlin,
+            fractal: LayerFractal::default(),
+        };
+        NoiseSettings {
+            seed: 0,
+            layers: [
+                layer(0, 0.003, 120.0),
+                layer(1, 0.01, 20.0),
+                layer(2, 0.03, 6.0),
+                layer(4, 0.08, 2.0),
+                layer(5, 0.16, 1.0),
+            ],
+            biome: BiomeSettings::default(),
+            decoration_density: 0.15,
+            superflat: false,
+            normalize_height: false,
+            // golden outputs predate rivers
+            rivers: RiverSettings {
+                width: 0.0,
+                ..RiverSettings::default()
+            },
+            warp: WarpSettings::default(),
+            overhangs: false,
+            foliage_variation: 0.5,
+            lava: false,
+            lava_level: 16,
+            caves: true,
+            bedrock: true,
+            trees: true,
+            boulders: true,
+            tree_density: 0.5,
+            boulder_density: 0.3,
+            feature_spacing: 5,
+            smooth_normals: false,
+            greedy_meshing: true,
+            weld_vertices: false,
+            base_height: 40,
+            ridge_height: 20.0,
+            ridge_mode: RidgeMode::Additive,
+            offset: [0, 0],
+        }
+    }
+
+    fn test_noise() -> NoiseResources {
+        NoiseResources::from_settings(&test_settings(), 256)
+    }
+
+    fn vertex_count(meshes: &ChunkMeshes) -> usize {
+        meshes
+            .parts
+            .iter()
+            .map(|(_, part)| part.positions.len())
+            .sum()
+    }
+
+    /// FNV-1a over the raw bits of every vertex position.
+    #[cfg(not(any(feature = "chunk-16", feature = "chunk-64")))]
+    fn position_hash(meshes: &ChunkMeshes) -> u64 {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let positions = meshes.parts.iter().flat_map(|(_, part)| &part.positions);
+        for value in positions.flatten() {
+            for byte in value.to_bits().to_le_bytes() {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+            }
+        }
+        hash
+    }
+
+    /// Chunk holding the terrain surface above the middle of chunk column
+    /// `column`, so mesh tests find geometry at every chunk size.
+    fn surface_chunk(noise: &NoiseResources, column: IVec2) -> IVec3 {
+        let middle = column * CHUNK_SIZE + CHUNK_SIZE / 2;
+        let surface = terrain_height(noise, middle.x, middle.y);
+        IVec3::new(column.x, surface.div_euclid(CHUNK_SIZE), column.y)
+    }
+
+    /// World columns sampled by the terrain height tests.
+    fn sample_columns() -> impl Iterator<Item = (i32, i32)> {
+        (-4..4).flat_map(|x| (-4..4).map(move |z| (x * 37, z * 53)))
+    }
+
+    #[test]
+    fn column_height_matches_terrain_height() {
+        let noise = NoiseResources::from_settings(&test_settings(), 256);
+        for (wx, wz) in sample_columns() {
+            assert_eq!(noise.column(wx, wz).height, terrain_height(&noise, wx, wz));
+        }
+    }
+
+    #[test]
+    fn terrain_height_grows_with_amplitude() {
+        let mut settings = test_settings();
+        let low = NoiseResources::from_settings(&settings, 256);
+        settings.layers[0].amplitude *= 2.0;
+        let high = NoiseResources::from_settings(&settings, 256);
+        let mut raised = false;
+        for (wx, wz) in sample_columns() {
+            let (a, b) = (terrain_height(&low, wx, wz), terrain_height(&high, wx, wz));
+            assert!(b >= a, "height dropped at ({wx}, {wz}): {a} -> {b}");
+            raised |= b > a;
+        }
+        assert!(raised);
+    }
+
+    #[test]
+    fn zero_frequency_terrain_is_flat() {
+        let mut settings = test_settings();
+        for layer in &mut settings.layers {
+            layer.frequency = 0.0;
+        }
+        settings.biome.frequency = 0.0;
+        let mut noise = NoiseResources::from_settings(&settings, 256);
+        noise.cliff.set_frequency(Some(0.0));
+        let expected = terrain_height(&noise, 0, 0);
+        for (wx, wz) in sample_columns() {
+            assert_eq!(terrain_height(&noise, wx, wz), expected);
+        }
+    }
+
+    #[test]
+    fn terrain_below_sea_level_is_flooded() {
+        let mut settings = test_settings();
+        for layer in &mut settings.layers {
+            layer.amplitude = 0.0;
+        }
+        let noise = NoiseResources::from_settings(&settings, 256);
+        assert!(terrain_height(&noise, 0, 0) < SEA_LEVEL);
+        let coord = IVec3::new(0, SEA_LEVEL / CHUNK_SIZE, 0);
+        let (meshes, _) = build_geometry(coord, 1, &noise, None);
+        assert!(meshes.parts.iter().any(|(block, _)| *block == WATER));
+        // the sea floor still has faces below the translucent water
+        assert!(meshes.parts.iter().any(|(block, _)| *block == SAND));
+        assert!(is_translucent(WATER) && !is_translucent(SAND));
+        assert!(block_surface(WATER).roughness < block_surface(STONE).roughness);
+    }
+
+    #[test]
+    fn terrain_noise_follows_the_settings() {
+        let mut world = World::new();
+        let mut settings = test_settings();
+        world.insert_resource(settings.clone());
+        world.insert_resource(WorldParams::default());
+        world.init_resource::<TerrainNoise>();
+        let expected = |settings: &NoiseSettings| {
+            let noise =
+                NoiseResources::from_settings(settings, WorldParams::default().max_height());
+            terrain_height(&noise, 40, -25)
+        };
+        world.run_system_cached(refresh_terrain_noise).unwrap();
+        let height = terrain_height(&world.resource::<TerrainNoise>().0, 40, -25);
+        assert_eq!(height, expected(&settings));
+
+        settings.offset = [500, 0];
+        world.insert_resource(settings.clone());
+        world.run_system_cached(refresh_terrain_noise).unwrap();
+        let moved = terrain_height(&world.resource::<TerrainNoise>().0, 40, -25);
+        assert_eq!(moved, expected(&settings));
+        assert_ne!(moved, height);
+    }
+
+    #[test]
+    fn only_water_columns_are_underwater() {
+        let mut settings = test_settings();
+        for layer in &mut settings.layers {
+            layer.amplitude = 0.0;
+        }
+        let max_height = DEFAULT_HEIGHT_CHUNKS * CHUNK_SIZE;
+        let noise = NoiseResources::from_settings(&settings, max_height);
+        let ground = terrain_height(&noise, 0, 0);
+        assert!(ground < SEA_LEVEL);
+        let at = |y: f32| is_underwater(&noise, Vec3::new(0.5, y, 0.5));
+        assert!(at(SEA_LEVEL as f32));
+        assert!(at(ground as f32 + 1.5));
+        assert!(!at(SEA_LEVEL as f32 + 1.5));
+        assert!(!at(ground as f32));
+
+        // superflat worlds sit above the sea
+        settings.superflat = true;
+        let flat = NoiseResources::from_settings(&settings, max_height);
+        assert!(!is_underwater(&flat, Vec3::new(0.5, SEA_LEVEL as f32, 0.5)));
+    }
+
+    #[test]
+    fn occluder_columns_keep_the_lowest_surface_of_their_chunks() {
+        let mut occluders = OccluderHeights::default();
+        let column = IVec2::new(3, -2);
+        occluders.insert(column.extend(0).xzy(), 40);
+        occluders.insert(column.extend(1).xzy(), 30);
+        occluders.insert(column.extend(2).xzy(), 50);
+        assert_eq!(occluders.heights[&column], 30);
+
+        // the column stays an occluder until its last chunk unloads
+        assert_eq!(occluders.remove(column.extend(1).xzy()), Some(30));
+        assert_eq!(occluders.heights[&column], 40);
+        assert_eq!(occluders.remove(column.extend(1).xzy()), None);
+        occluders.remove(column.extend(0).xzy());
+        occluders.remove(column.extend(2).xzy());
+        assert!(!occluders.heights.contains_key(&column));
+        assert!(occluders.chunks.is_empty());
+    }
+
+    #[test]
+    fn chunks_behind_a_ridge_are_occluded() {
+        let ground = CHUNK_SIZE;
+        let mut heights: HashMap<IVec2, i32> = (-1..=6)
+            .flat_map(|x| (-1..=1).map(move |z| (IVec2::new(x, z), ground)))
+            .collect();
+        let eye = Vec3::new(0.5, ground as f32 + 2.0, 0.5)
+            * Vec3::new(CHUNK_SIZE_F32, 1.0, CHUNK_SIZE_F32);
+        let behind = IVec3::new(5, 1, 0);
+
+        // flat ground leaves the chunk above it in plain sight
+        assert!(!chunk_occluded(&heights, eye, behind));
+        // a ridge three chunks tall in between hides it completely
+        for z in -1..=1 {
+            heights.insert(IVec2::new(2, z), ground + 3 * CHUNK_SIZE);
+        }
+        assert!(chunk_occluded(&heights, eye, behind));
+        // chunks high enough to peek over the ridge stay visible
+        assert!(!chunk_occluded(&heights, eye, IVec3::new(5, 12, 0)));
+    }
+
+    #[test]
+    fn offset_pans_the_terrain_seamlessly() {
+        let mut settings = test_settings();
+        settings.overhangs = true;
+        let plain = NoiseResources::from_settings(&settings, 256);
+        let shift = IVec2::new(3 * CHUNK_SIZE, -2 * CHUNK_SIZE);
+        settings.offset = shift.to_array();
+        let panned = NoiseResources::from_settings(&settings, 256);
+        for (wx, wz) in [(0, 0), (37, -12), (-80, 95)] {
+            assert_eq!(
+                terrain_height(&panned, wx, wz),
+                terrain_height(&plain, wx + shift.x, wz + shift.y)
+            );
+        }
+        // a whole number of chunks makes the panned chunk the same mesh
+        let coord = IVec3::new(0, 1, 0);
+        let moved = coord + IVec3::new(3, 0, -2);
+        let mesh = |noise: &NoiseResources, coord| {
+            build_mesh::<LOD1_PADDED>(coord, 1, noise, None, &mut MeshScratch::default()).0
+        };
+        assert_eq!(mesh(&panned, coord), mesh(&plain, moved));
+
+        // saved offsets past the bound are clamped instead of overflowing
+        settings.offset = [i32::MAX, i32::MIN];
+        let far = NoiseResources::from_settings(&settings, 256);
+        settings.offset = [MAX_OFFSET, -MAX_OFFSET];
+        let bound = NoiseResources::from_settings(&settings, 256);
+        assert_eq!(
+            terrain_height(&far, 1000, -1000),
+            terrain_height(&bound, 1000, -1000)
+        );
+    }
+
+    #[test]
+    fn warp_and_simplex_layers_reshape_the_terrain() {
+        let heights = |settings: &NoiseSettings| {
+            let noise = NoiseResources::from_settings(settings, 256);
+            (0..64)
+                .map(|wx| terrain_height(&noise, wx * 3, -wx))
+                .collect::<Vec<_>>()
+        };
+        let mut settings = test_settings();
+        let plain = heights(&settings);
+
+        settings.warp.strength = 40.0;
+        let warped = heights(&settings);
+        assert_ne!(warped, plain);
+        // warping moves the samples smoothly, so the terrain stays continuous
+        let noise = NoiseResources::from_settings(&settings, 256);
+        for wx in 0..64 {
+            let step = terrain_height(&noise, wx + 1, 5) - terrain_height(&noise, wx, 5);
+            assert!(step.abs() <= 8, "cliff of {step} blocks at x {wx}");
+        }
+
+        settings.warp.strength = 0.0;
+        assert_eq!(heights(&settings), plain);
+        for layer in &mut settings.layers {
+            layer.noise = LayerNoise::OpenSimplex2S;
+        }
+        assert_ne!(heights(&settings), plain);
+    }
+
+    #[test]
+    fn fractal_octaves_add_detail_to_a_layer() {
+        // roughness: summed height change between neighbouring columns
+        let roughness = |settings: &NoiseSettings| {
+            let noise = NoiseResources::from_settings(settings, 256);
+            let heights: Vec<i32> = (0..400).map(|wx| terrain_height(&noise, wx, 77)).collect();
+            heights.windows(2).map(|w| (w[1] - w[0]).abs()).sum::<i32>()
+        };
+        let mut settings = test_settings();
+        settings.ridge_height = 0.0;
+        settings.rivers.width = 0.0;
+        for layer in &mut settings.layers[1..] {
+            layer.amplitude = 0.0;
+        }
+        let smooth = roughness(&settings);
+
+        // a single octave has no fractal to stack, whatever the mode
+        settings.layers[0].fractal.mode = FractalMode::Ridged;
+        assert_eq!(roughness(&settings), smooth);
+
+        settings.layers[0].fractal = LayerFractal {
+            octaves: 6,
+            ..LayerFractal::default()
+        };
+        let detailed = roughness(&settings);
+        assert!(detailed > smooth, "{detailed} vs {smooth}");
+        settings.layers[0].fractal.mode = FractalMode::Ridged;
+        assert_ne!(roughness(&settings), detailed);
+    }
+
+    #[test]
+    fn ridge_height_sets_how_dramatic_the_cliffs_are() {
+        let mut settings = test_settings();
+        settings.rivers.width = 0.0;
+        // steepest drop between neighbouring columns along a line
+        let steepest = |settings: &NoiseSettings| {
+            let noise = NoiseResources::from_settings(settings, 256);
+            let heights: Vec<i32> = (0..400).map(|wx| terrain_height(&noise, wx, 123)).collect();
+            let steps = heights.windows(2).map(|w| (w[1] - w[0]).abs());
+            (steps.max().unwrap(), heights)
+        };
+        settings.ridge_height = 0.0;
+        let (smooth, flat) = steepest(&settings);
+        settings.ridge_height = MAX_RIDGE_HEIGHT;
+        let (cliffs, tall) = steepest(&settings);
+        assert!(cliffs > smooth, "{cliffs} <= {smooth}");
+        assert!(tall.iter().zip(&flat).all(|(t, f)| t >= f));
+
+        // multiplied ridges never rise above added ones
+        settings.ridge_mode = RidgeMode::Multiplicative;
+        let (_, scaled) = steepest(&settings);
+        assert_ne!(scaled, tall);
+        assert!(scaled.iter().zip(&tall).all(|(s, t)| s <= t));
+        assert!(scaled.iter().zip(&flat).all(|(s, f)| s >= f));
+
+        settings.ridge_height = 1e6;
+        assert_eq!(steepest(&settings).1, scaled);
+    }
+
+    #[test]
+    fn base_height_shifts_the_whole_terrain() {
+        let mut settings = test_settings();
+        settings.rivers.width = 0.0;
+        let max_height = WorldParams::default().max_height();
+        let low = NoiseResources::from_settings(&settings, max_height);
+        settings.base_height += 12;
+        let high = NoiseResources::from_settings(&settings, max_height);
+        for (wx, wz) in [(0, 0), (37, -12), (-80, 95)] {
+            assert_eq!(
+                terrain_height(&high, wx, wz),
+                terrain_height(&low, wx, wz) + 12
+            );
+        }
+        // the ground never leaves the world, however far it is pushed
+        settings.base_height = 10_000;
+        let clamped = NoiseResources::from_settings(&settings, max_height);
+        assert_eq!(terrain_height(&clamped, 0, 0), max_height - 1);
+    }
+
+    #[test]
+    fn normalized_terrain_fits_inside_the_world() {
+        let mut settings = test_settings();
+        settings.layers[0].amplitude = 2000.0;
+        let max_height = 256;
+        let peak = |settings: &NoiseSettings| {
+            let noise = NoiseResources::from_settings(settings, max_height);
+            (-64..64)
+                .flat_map(|x| (-64..64).map(move |z| (x * 16, z * 16)))
+                .map(|(x, z)| terrain_height(&noise, x, z))
+                .max()
+                .unwrap()
+        };
+        assert_eq!(peak(&settings), max_height - 1);
+        settings.normalize_height = true;
+        let normalized = peak(&settings);
+        assert!(normalized < max_height - 1 && normalized > settings.base_height);
+    }
+
+    #[test]
+    fn rivers_carve_flooded_channels() {
+        let mut settings = test_settings();
+        settings.rivers = RiverSettings::default();
+        let rivers = NoiseResources::from_settings(&settings, 256);
+        let dry = test_noise();
+
+        let mut channels = 0;
+        for wx in -2000..2000 {
+            let (carved, plain) = (terrain_height(&rivers, wx, 0), terrain_height(&dry, wx, 0));
+            assert!(carved <= plain);
+            let centre = rivers.river.get_noise_2d(wx as f32, 0.0).abs() < rivers.river_width * 0.1;
+            if centre {
+                assert!(carved < SEA_LEVEL, "river centre at x {wx} is dry");
+                channels += 1;
+            }
+        }
+        assert!(channels > 0);
+    }
+
+    #[test]
+    fn density_terrain_produces_overhangs() {
+        let mut settings = test_settings();
+        settings.overhangs = true;
+        let noise = NoiseResources::from_settings(&settings, 256);
+
+        // a solid voxel above an empty one above solid ground is an overhang,
+        // which one surface height per column can never describe
+        let overhang = (-200..200).any(|wx| {
+            let height = terrain_height(&noise, wx, 0);
+            let mut column = (height - DENSITY_REACH..=height + DENSITY_REACH)
+                .map(|wy| noise.solid(height, wx, wy, 0))
+                .skip_while(|solid| *solid);
+            column.any(|solid| solid)
+        });
+        assert!(overhang);
+
+        // the fast heightmap path is unchanged when the mode is off
+        let plain = test_noise();
+        assert!(plain.density.is_none());
+        assert!(plain.solid(50, 0, 50, 0) && !plain.solid(50, 0, 51, 0));
+    }
+
+    #[test]
+    fn superflat_world_has_fixed_strata() {
+        let mut settings = test_settings();
+        settings.superflat = true;
+        let noise = NoiseResources::from_settings(&settings, 256);
+        for (wx, wz) in sample_columns() {
+            assert_eq!(terrain_height(&noise, wx, wz), FLAT_HEIGHT);
+        }
+        // the chunk holding the top grass layer, at every chunk size
+        let coord = IVec3::new(0, (FLAT_HEIGHT - 1) / CHUNK_SIZE, 0);
+        let (meshes, _) = build_geometry(coord, 1, &noise, None);
+        let blocks: Vec<_> = meshes.parts.iter().map(|(block, _)| *block).collect();
+        assert!(blocks.contains(&GRASS));
+        assert!(blocks.iter().all(|b| [GRASS, DIRT, STONE].contains(b)));
+    }
 
-    let mut surface_colors = surface_in
-        .unwrap_or_else(|| vec![[0.0, 0.0, 0.0, 1.0]; (CHUNK_SIZE_U32 * CHUNK_SIZE_U32) as usize]);
+    #[test]
+    fn terrain_height_stays_inside_world() {
+        let mut settings = test_settings();
+        settings.layers[0].amplitude = 10_000.0;
+        let noise = NoiseResources::from_settings(&settings, 64);
+        for (wx, wz) in sample_columns() {
+            assert!((1..64).contains(&terrain_height(&noise, wx, wz)));
+        }
+    }
 
-    // 2D terrain noise layers for varied heights
-    let mut noises = Vec::new();
-    for layer in &settings.layers {
-        let mut n = FastNoiseLite::with_seed(layer.seed);
-        n.set_noise_type(Some(NoiseType::Perlin));
-        n.set_frequency(Some(layer.frequency));
-        noises.push((n, layer.amplitude));
+    #[test]
+    fn generation_is_deterministic() {
+        let noise = test_noise();
+        // the chunk holding the surface so both lods produce geometry
+        let surface = IVec3::new(-96, terrain_height(&noise, -96, 160), 160);
+        let coord = surface.div_euclid(IVec3::splat(CHUNK_SIZE));
+        for lod in [1, 2] {
+            let (first, _) = build_geometry(coord, lod, &noise, None);
+            let (second, _) = build_geometry(coord, lod, &test_noise(), None);
+            assert!(vertex_count(&first) > 0);
+            assert_eq!(first, second);
+        }
     }
 
-    // 3D noise for sparse caves and cliffs
-    let mut cave = FastNoiseLite::with_seed(3);
-    cave.set_noise_type(Some(NoiseType::Perlin));
-    cave.set_frequency(Some(0.05));
+    #[test]
+    #[cfg(not(any(feature = "chunk-16", feature = "chunk-64")))]
+    fn lod1_chunk_matches_golden_output() {
+        let (geometry, _) = build_geometry(IVec3::new(0, 2, 0), 1, &test_noise(), None);
+        assert_eq!(vertex_count(&geometry), 4864);
+        assert_eq!(position_hash(&geometry), 7734773107469635797);
+    }
 
-    for z in 0..=size + 1 {
-        for x in 0..=size + 1 {
-            let wx = coord.x * CHUNK_SIZE + ((x as i32 - 1) * lod as i32);
-            let wz = coord.z * CHUNK_SIZE + ((z as i32 - 1) * lod as i32);
+    #[test]
+    #[cfg(not(any(feature = "chunk-16", feature = "chunk-64")))]
+    fn lod2_chunk_matches_golden_output() {
+        let (geometry, _) = build_geometry(IVec3::new(2, 2, -1), 2, &test_noise(), None);
+        assert_eq!(vertex_count(&geometry), 804);
+        assert_eq!(position_hash(&geometry), 15290468563940412757);
+    }
 
-            let mut height = 40;
-            if let Some((first_noise, first_amp)) = noises.first() {
-                let val = (first_noise.get_noise_2d(wx as f32, wz as f32) + 1.0) / 2.0;
-                height += (val * first_amp) as i32;
+    #[test]
+    fn chunk_borders_hang_skirts_below_the_lower_surface() {
+        let noise = test_noise();
+        let (coord, lod) = (surface_chunk(&noise, IVec2::new(2, -1)), 2);
+        let (geometry, _) = build_geometry(coord, lod as u32, &noise, None);
+        let base = coord * CHUNK_SIZE;
+        let west: Vec<[f32; 3]> = geometry
+            .parts
+            .iter()
+            .flat_map(|(_, part)| part.positions.iter().zip(&part.normals))
+            .filter(|(p, n)| p[0] == lod as f32 && **n == [-1.0, 0.0, 0.0])
+            .map(|(p, _)| *p)
+            .collect();
 
-                for (noise, amp) in &noises[1..] {
-                    let val = noise.get_noise_2d(wx as f32, wz as f32);
-                    height += (val * amp) as i32;
-                }
+        let mut skirts = 0;
+        for i in 1..=CHUNK_SIZE / lod {
+            let wz = base.z + i * lod;
+            let own = terrain_height(&noise, base.x + lod, wz);
+            let pad = terrain_height(&noise, base.x, wz);
+            let top = |h: i32| ((h - base.y).div_euclid(lod) + 1) * lod;
+            if !(2 * lod..=CHUNK_SIZE + lod).contains(&top(own)) || top(own.min(pad)) <= lod {
+                continue;
             }
-            let height = height.clamp(1, MAX_HEIGHT - 1) as i32;
+            let lowest = west
+                .iter()
+                .filter(|p| p[2] == (i * lod) as f32)
+                .map(|p| p[1])
+                .fold(f32::INFINITY, f32::min);
+            let expected = (top(own.min(pad)) - SKIRT_DEPTH as i32 * lod) as f32;
+            assert!(lowest <= expected, "column {i}: {lowest} > {expected}");
+            skirts += 1;
+        }
+        assert!(skirts > 0);
+    }
 
-            for y in 1..=size + 1 {
-                let wy = coord.y * CHUNK_SIZE + ((y as i32 - 1) * lod as i32);
-                if wy > height {
+    #[test]
+    fn foliage_tint_is_continuous_across_chunk_borders() {
+        let noise = test_noise();
+        let grass_at_border = |coord: IVec3, x: f32| {
+            let (meshes, _) = build_geometry(coord, 1, &noise, None);
+            let mut found = HashMap::new();
+            for (block, part) in &meshes.parts {
+                if *block != GRASS {
                     continue;
                 }
+                for (p, color) in part.positions.iter().zip(&part.colors) {
+                    if p[0] == x {
+                        found.insert((p[1] as i32, p[2] as i32), *color);
+                    }
+                }
+            }
+            found
+        };
 
-                let idx = shape.linearize([x, y, z]) as usize;
-                let mut block = EMPTY;
+        let mut shared = 0;
+        let mut tints = HashSet::new();
+        for y in 0..8 {
+            // meshes span `lod..=CHUNK_SIZE + lod` in local units
+            let west = grass_at_border(IVec3::new(0, y, 0), CHUNK_SIZE_F32 + 1.0);
+            let east = grass_at_border(IVec3::new(1, y, 0), 1.0);
+            for (key, color) in &west {
+                if let Some(other) = east.get(key) {
+                    assert_eq!(color, other, "tint jumps at {key:?}");
+                    shared += 1;
+                }
+                tints.insert(color.map(f32::to_bits));
+            }
+        }
+        assert!(shared > 0, "no grass faces meet at the border");
+        assert!(tints.len() > 1, "grass is uniformly tinted");
 
-                for offset in (0..lod).rev() {
-                    let sample_y = wy + offset as i32;
-                    if sample_y > height {
-                        continue;
-                    }
+        let mut settings = test_settings();
+        settings.foliage_variation = 0.0;
+        let flat = NoiseResources::from_settings(&settings, 256);
+        assert_eq!(flat.foliage_color(GRASS, 10.0, 20.0), block_color(GRASS));
+        assert_eq!(noise.foliage_color(STONE, 10.0, 20.0), block_color(STONE));
+    }
 
-                    let noise = cave.get_noise_3d(wx as f32, sample_y as f32, wz as f32);
-                    if noise > 0.9 {
-                        continue; // carve cave
+    #[test]
+    fn deep_caves_fill_with_lava() {
+        let lava_faces = |settings: &NoiseSettings| {
+            let mut noise = NoiseResources::from_settings(settings, 256);
+            // perlin noise hardly ever passes the cave threshold, value noise
+            // on integer coordinates carves a few percent of all voxels
+            noise.cave.set_noise_type(Some(NoiseType::Value));
+            noise.cave.set_frequency(Some(1.0));
+            let mut tops = Vec::new();
+            for x in 0..4 {
+                let coord = IVec3::new(x, 0, 0);
+                let (meshes, _) = build_geometry(coord, 1, &noise, None);
+                for (block, part) in &meshes.parts {
+                    if *block == LAVA {
+                        tops.extend(part.positions.iter().map(|p| p[1] as i32));
                     }
-
-                    block = if sample_y == height {
-                        GRASS
-                    } else if sample_y == height - 1 {
-                        DIRT
-                    } else {
-                        STONE
-                    };
-                    break;
                 }
+            }
+            tops
+        };
 
-                if block != EMPTY {
-                    voxels[idx] = block;
-                    if lod == 1 {
-                        if x > 0 && x <= size && z > 0 && z <= size && wy == height {
-                            let lx = x - 1;
-                            let lz = z - 1;
-                            let color = match block {
-                                GRASS => [0.1, 0.8, 0.1, 1.0],
-                                DIRT => [0.55, 0.27, 0.07, 1.0],
-                                STONE => [0.6, 0.6, 0.6, 1.0],
-                                _ => [1.0, 1.0, 1.0, 1.0],
-                            };
-                            surface_colors[(lx * CHUNK_SIZE_U32 + lz) as usize] = color;
-                        }
+        let mut settings = test_settings();
+        assert!(lava_faces(&settings).is_empty());
+
+        settings.lava = true;
+        let tops = lava_faces(&settings);
+        assert!(!tops.is_empty(), "no cave reaches below the lava level");
+        // the top face of a voxel at `lava_level` sits one block above it
+        assert!(tops.iter().all(|&y| y <= settings.lava_level + 1));
+    }
+
+    #[test]
+    fn bedrock_keeps_caves_out_of_the_bottom_layer() {
+        let bottom_open = |bedrock: bool| {
+            let settings = NoiseSettings {
+                bedrock,
+                ..test_settings()
+            };
+            let mut noise = NoiseResources::from_settings(&settings, 256);
+            noise.cave.set_noise_type(Some(NoiseType::Value));
+            noise.cave.set_frequency(Some(1.0));
+            (-64..64)
+                .flat_map(|x| (-64..64).map(move |z| (x, z)))
+                .filter(|&(x, z)| !noise.block_solid(x, 0, z))
+                .count()
+        };
+        assert!(bottom_open(false) > 0, "no cave reaches the bottom layer");
+        assert_eq!(bottom_open(true), 0);
+    }
+
+    #[test]
+    fn lava_faces_cast_a_bounded_number_of_lights() {
+        let mut settings = test_settings();
+        settings.lava = true;
+        // pools inside the bottom chunk layer at every chunk size
+        settings.lava_level = CHUNK_SIZE / 2;
+        let mut noise = NoiseResources::from_settings(&settings, 256);
+        noise.cave.set_noise_type(Some(NoiseType::Value));
+        noise.cave.set_frequency(Some(1.0));
+        // the same 64 block square at every chunk size, as small chunks may
+        // hold no exposed lava at all
+        let per_side = (64 / CHUNK_SIZE).max(1);
+        let columns = (0..per_side).flat_map(|x| (0..per_side).map(move |z| IVec3::new(x, 0, z)));
+        let mut lit = 0;
+        for coord in columns.clone() {
+            let (meshes, _) = build_geometry(coord, 1, &noise, None);
+            assert!(meshes.lights.len() <= MAX_CHUNK_LIGHTS);
+            assert!(meshes.lights.iter().all(|(block, _)| *block == LAVA));
+            // lights hover above the pools, never inside the rock
+            for (_, light) in &meshes.lights {
+                assert!(light.y <= (settings.lava_level + 2) as f32);
+            }
+            lit += meshes.lights.len();
+        }
+        assert!(lit > 0);
+
+        for coord in columns {
+            let (plain, _) = build_geometry(coord, 1, &test_noise(), None);
+            assert!(plain.lights.is_empty());
+        }
+    }
+
+    #[test]
+    fn results_of_replaced_tasks_are_stale() {
+        AsyncComputeTaskPool::get_or_init(bevy::tasks::TaskPool::default);
+        let result = |lod| ChunkTaskResult {
+            coord: IVec3::ZERO,
+            lod,
+            meshes: Vec::new(),
+            surface: Vec::new(),
+            min_surface: 0,
+            duration: Duration::ZERO,
+            lights: Vec::new(),
+            blocks: BlockCounts::default(),
+        };
+        let mut pending = PendingTasks::default();
+        let (started_tx, started) = mpsc::channel();
+        let (release, release_rx) = mpsc::channel::<()>();
+        pending.spawn(IVec3::ZERO, 2, move || {
+            started_tx.send(()).unwrap();
+            release_rx.recv().unwrap();
+            result(2)
+        });
+        // once running, dropping the handle no longer stops it from sending
+        started.recv().unwrap();
+        pending.spawn(IVec3::ZERO, 1, move || result(1));
+        release.send(()).unwrap();
+
+        let mut finished = Vec::new();
+        for _ in 0..1000 {
+            finished.extend(
+                pending
+                    .take_finished(RESULTS_PER_FRAME)
+                    .into_iter()
+                    .map(|(result, current)| (result.lod, current)),
+            );
+            if finished.len() == 2 {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(2));
+        }
+        finished.sort();
+        assert_eq!(finished, [(1, true), (2, false)]);
+        assert!(pending.tasks.is_empty());
+    }
+
+    #[test]
+    fn block_lights_respect_the_cap_and_leave_with_their_chunk() {
+        let mut app = test_app();
+        app.world_mut()
+            .resource_mut::<WorldParams>()
+            .max_block_lights = 6;
+        set_state(&mut app, AppState::Playing);
+        run_until_generated(&mut app);
+
+        // hand two loaded chunks a replacement carrying four lights each
+        let chunks: Vec<(IVec3, u32)> = app
+            .world_mut()
+            .query::<&Chunk>()
+            .iter(app.world())
+            .map(|chunk| (chunk.coord, chunk.lod))
+            .take(2)
+            .collect();
+        for (coord, lod) in chunks {
+            app.world_mut()
+                .resource_mut::<PendingTasks>()
+                .spawn(coord, lod, move || ChunkTaskResult {
+                    coord,
+                    lod,
+                    meshes: Vec::new(),
+                    surface: Vec::new(),
+                    min_surface: 0,
+                    duration: Duration::ZERO,
+                    lights: vec![(LAVA, Vec3::splat(8.0)); MAX_CHUNK_LIGHTS],
+                    blocks: BlockCounts::default(),
+                });
+        }
+        run_until_generated(&mut app);
+        app.update();
+        let lights = |app: &mut App| {
+            app.world_mut()
+                .query_filtered::<(), With<BlockLight>>()
+                .iter(app.world())
+                .count()
+        };
+        assert_eq!(lights(&mut app), 6);
+
+        // lights of stashed chunks no longer hold on to the cap; they are
+        // stashed under far coords so they are not shown again
+        let mut lit: Vec<Entity> = app
+            .world_mut()
+            .query_filtered::<&ChildOf, With<BlockLight>>()
+            .iter(app.world())
+            .map(ChildOf::parent)
+            .collect();
+        lit.dedup();
+        for (i, entity) in lit.into_iter().enumerate() {
+            let chunk = app.world_mut().entity_mut(entity).take::<Chunk>().unwrap();
+            let world = app.world_mut();
+            world
+                .resource_mut::<ChunkMap>()
+                .entities
+                .remove(&chunk.coord);
+            world
+                .resource_mut::<SurfaceCache>()
+                .colors
+                .remove(&chunk.coord);
+            let far = IVec3::new(1000 + i as i32, 0, 0);
+            let mut stash = world.resource_mut::<ChunkStash>();
+            stash.bytes += chunk.bytes;
+            stash.order.push_back(far);
+            stash.chunks.insert(
+                far,
+                Stashed {
+                    entity,
+                    lod: chunk.lod,
+                    bytes: chunk.bytes,
+                    min_surface: None,
+                },
+            );
+        }
+        let (coord, lod) = app
+            .world_mut()
+            .query::<&Chunk>()
+            .iter(app.world())
+            .map(|chunk| (chunk.coord, chunk.lod))
+            .next()
+            .unwrap();
+        app.world_mut()
+            .resource_mut::<PendingTasks>()
+            .spawn(coord, lod, move || ChunkTaskResult {
+                coord,
+                lod,
+                meshes: Vec::new(),
+                surface: Vec::new(),
+                min_surface: 0,
+                duration: Duration::ZERO,
+                lights: vec![(LAVA, Vec3::splat(8.0)); MAX_CHUNK_LIGHTS],
+                blocks: BlockCounts::default(),
+            });
+        run_until_generated(&mut app);
+        app.update();
+        assert_eq!(lights(&mut app), 6 + MAX_CHUNK_LIGHTS);
+
+        set_state(&mut app, AppState::Menu);
+        app.update();
+        assert_eq!(lights(&mut app), 0);
+    }
+
+    #[test]
+    fn features_agree_across_chunk_windows_and_keep_their_spacing() {
+        let noise = test_noise();
+        let mut scratch = Vec::new();
+        // two chunk windows sharing the margin around their common border
+        let west = features_in(&noise, IVec2::new(-36, 0), IVec2::new(35, 71), &mut scratch);
+        let east = features_in(&noise, IVec2::new(28, 0), IVec2::new(99, 71), &mut scratch);
+        let in_overlap = |(pos, _, _): &&(IVec2, i32, Feature)| (28..=35).contains(&pos.x);
+        let shared: Vec<_> = west.iter().filter(in_overlap).collect();
+        assert_eq!(shared, east.iter().filter(in_overlap).collect::<Vec<_>>());
+
+        let all: Vec<IVec2> = west.iter().map(|(pos, _, _)| *pos).collect();
+        assert!(all.len() > 4, "too few features to check the spacing");
+        for (i, a) in all.iter().enumerate() {
+            for b in &all[i + 1..] {
+                let dist = (*a - *b).abs().max_element();
+                assert!(
+                    dist >= noise.feature_spacing,
+                    "{a} and {b} are {dist} apart"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn features_move_with_the_world_seed() {
+        let place = |seed: i32| {
+            let settings = NoiseSettings {
+                seed,
+                ..test_settings()
+            };
+            let noise = NoiseResources::from_settings(&settings, 256);
+            features_in(&noise, IVec2::ZERO, IVec2::splat(95), &mut Vec::new())
+        };
+        let first = place(1);
+        assert!(!first.is_empty());
+        // the same seed places the same trees and boulders again
+        assert_eq!(place(1), first);
+        let other = place(2);
+        assert!(!other.is_empty());
+        let spots = |features: &[(IVec2, i32, Feature)]| -> HashSet<IVec2> {
+            features.iter().map(|(pos, _, _)| *pos).collect()
+        };
+        let shared = spots(&first).intersection(&spots(&other)).count();
+        assert!(
+            shared * 4 < first.len(),
+            "{shared} of {} spots kept",
+            first.len()
+        );
+    }
+
+    #[test]
+    fn feature_densities_scale_and_disable_features() {
+        let count = |trees: f32, boulders: f32| {
+            let mut settings = test_settings();
+            settings.tree_density = trees;
+            settings.boulder_density = boulders;
+            let noise = NoiseResources::from_settings(&settings, 256);
+            let features = features_in(&noise, IVec2::ZERO, IVec2::splat(95), &mut Vec::new());
+            let trees = features
+                .iter()
+                .filter(|(_, _, f)| matches!(f, Feature::Tree { .. }))
+                .count();
+            (trees, features.len() - trees)
+        };
+        let (trees, boulders) = count(0.5, 0.3);
+        assert!(trees > 0 && boulders > 0);
+        assert_eq!(count(0.0, 0.0), (0, 0));
+        assert_eq!(count(0.0, 0.3).0, 0);
+        assert!(count(MAX_FEATURE_DENSITY, 0.0).0 > trees);
+        // out of range scales are clamped rather than rejected
+        assert_eq!(count(-1.0, -1.0), (0, 0));
+    }
+
+    #[test]
+    fn generation_passes_switch_off_independently() {
+        let run = |caves: bool, trees: bool, boulders: bool| {
+            let settings = NoiseSettings {
+                caves,
+                trees,
+                boulders,
+                ..test_settings()
+            };
+            let mut noise = NoiseResources::from_settings(&settings, 256);
+            let features = features_in(&noise, IVec2::ZERO, IVec2::splat(95), &mut Vec::new());
+            let trees = features
+                .iter()
+                .filter(|(_, _, f)| matches!(f, Feature::Tree { .. }))
+                .count();
+            // value noise passes the cave threshold far more often than perlin
+            noise.cave.set_noise_type(Some(NoiseType::Value));
+            noise.cave.set_frequency(Some(1.0));
+            let carved = (0..64)
+                .flat_map(|wx| (0..64).map(move |wy| (wx, wy, 7)))
+                .filter(|&(wx, wy, wz)| noise.carved(wx, wy, wz))
+                .count();
+            (carved, trees, features.len() - trees)
+        };
+        let (carved, trees, boulders) = run(true, true, true);
+        assert!(carved > 0 && trees > 0 && boulders > 0);
+        assert_eq!(run(false, true, true), (0, trees, boulders));
+        // switching one feature off leaves the other to fill its spots
+        assert_eq!(run(true, false, true).1, 0);
+        assert!(run(true, false, true).2 >= boulders);
+        assert_eq!(run(true, true, false).2, 0);
+        assert_eq!(run(true, false, false), (carved, 0, 0));
+    }
+
+    #[test]
+    fn smooth_normals_round_off_rolling_hills() {
+        let coord = surface_chunk(&test_noise(), IVec2::ZERO);
+        let (hard, _) = build_geometry(coord, 1, &test_noise(), None);
+        let mut settings = test_settings();
+        settings.smooth_normals = true;
+        let smooth_noise = NoiseResources::from_settings(&settings, 256);
+        let (smooth, _) = build_geometry(coord, 1, &smooth_noise, None);
+
+        let normals = |meshes: &ChunkMeshes| -> Vec<Vec3> {
+            let grass = meshes.parts.iter().find(|(b, _)| *b == GRASS).unwrap();
+            grass.1.normals.iter().map(|n| Vec3::from(*n)).collect()
+        };
+        let axis_aligned = |n: &Vec3| n.abs().max_element() > 0.999;
+        // the default stays faceted
+        assert!(normals(&hard).iter().all(axis_aligned));
+
+        let (hard_grass, smooth_grass) = (normals(&hard), normals(&smooth));
+        assert_eq!(hard_grass.len(), smooth_grass.len());
+        assert!(smooth_grass.iter().all(|n| (n.length() - 1.0).abs() < 1e-4));
+        // corners on the hill's steps now lean between the faces they join
+        let leaning = smooth_grass.iter().filter(|n| !axis_aligned(n)).count();
+        assert!(
+            leaning * 10 > smooth_grass.len(),
+            "only {leaning} normals leaned"
+        );
+        // smoothing only ever touches normals
+        for ((block, a), (_, b)) in hard.parts.iter().zip(&smooth.parts) {
+            assert_eq!(a.positions, b.positions, "{block:?} positions moved");
+        }
+    }
+
+    #[test]
+    fn smooth_normals_agree_across_chunk_borders() {
+        let mut settings = test_settings();
+        settings.smooth_normals = true;
+        let noise = NoiseResources::from_settings(&settings, 256);
+        let west = surface_chunk(&noise, IVec2::ZERO);
+        let east = west + IVec3::X;
+        // smoothed normals of the vertices on the plane x = `x`, by block, y
+        // and z; the skirts there keep their flat outward normals
+        let border = |coord: IVec3, x: f32| {
+            let (meshes, _) = build_geometry(coord, 1, &noise, None);
+            let mut found = HashMap::new();
+            for (block, part) in &meshes.parts {
+                if is_translucent(*block) {
+                    continue;
+                }
+                for (p, n) in part.positions.iter().zip(&part.normals) {
+                    if p[0] == x && n[0].abs() < 1.0 {
+                        found.insert((*block as u8, p[1] as i32, p[2] as i32), Vec3::from(*n));
                     }
                 }
             }
+            found
+        };
+        let (west, east) = (border(west, CHUNK_SIZE_F32 + 1.0), border(east, 1.0));
+        let shared: Vec<_> = west.keys().filter(|k| east.contains_key(k)).collect();
+        assert!(!shared.is_empty());
+        for key in shared {
+            assert!(
+                west[key].abs_diff_eq(east[key], 1e-5),
+                "seam at {key:?}: {} vs {}",
+                west[key],
+                east[key]
+            );
+        }
+    }
+
+    #[test]
+    fn naive_meshing_gives_every_face_its_own_quad() {
+        let coord = surface_chunk(&test_noise(), IVec2::ZERO);
+        let (greedy, _) = build_geometry(coord, 1, &test_noise(), None);
+        let mut settings = test_settings();
+        settings.greedy_meshing = false;
+        let naive_noise = NoiseResources::from_settings(&settings, 256);
+        let (naive, _) = build_geometry(coord, 1, &naive_noise, None);
+
+        let quad_areas = |positions: &[[f32; 3]]| -> Vec<f32> {
+            positions
+                .chunks(4)
+                .map(|q| {
+                    let [a, b, c] = [q[0], q[1], q[2]].map(Vec3::from);
+                    (b - a).cross(c - a).length()
+                })
+                .collect()
+        };
+        assert_eq!(greedy.parts.len(), naive.parts.len());
+        for (block, merged) in &greedy.parts {
+            let (_, single) = naive.parts.iter().find(|(b, _)| b == block).unwrap();
+            let (merged, single) = (quad_areas(&merged.positions), quad_areas(&single.positions));
+            let total = |areas: &[f32]| areas.iter().sum::<f32>();
+            // the same surface, only cut into more pieces
+            assert!((total(&merged) - total(&single)).abs() < 1e-2, "{block:?}");
+            assert!(single.len() >= merged.len());
         }
+        let quads = |meshes: &ChunkMeshes| -> usize {
+            meshes
+                .parts
+                .iter()
+                .map(|(_, g)| g.positions.len() / 4)
+                .sum()
+        };
+        assert!(quads(&naive) > quads(&greedy));
     }
 
-    let mut buffer = GreedyQuadsBuffer::new(voxels.len());
-    greedy_quads(
-        &voxels,
-        &shape,
-        [1; 3],
-        [size + 1; 3],
-        &RIGHT_HANDED_Y_UP_CONFIG.faces,
-        &mut buffer,
-    );
+    #[test]
+    fn welding_shares_vertices_without_moving_triangles() {
+        let coord = surface_chunk(&test_noise(), IVec2::ZERO);
+        let mut settings = test_settings();
+        settings.greedy_meshing = false;
+        let plain = NoiseResources::from_settings(&settings, 256);
+        settings.weld_vertices = true;
+        let welded_noise = NoiseResources::from_settings(&settings, 256);
+        let (plain, _) = build_geometry(coord, 1, &plain, None);
+        let (welded, _) = build_geometry(coord, 1, &welded_noise, None);
 
-    let mut positions: Vec<[f32; 3]> = Vec::new();
-    let mut normals: Vec<[f32; 3]> = Vec::new();
-    let mut colors: Vec<[f32; 4]> = Vec::new();
-    let mut indices: Vec<u32> = Vec::new();
+        let corners = |g: &ChunkGeometry| -> Vec<_> {
+            g.indices
+                .iter()
+                .map(|&i| {
+                    let i = i as usize;
+                    (g.positions[i], g.normals[i], g.colors[i], g.sway[i])
+                })
+                .collect()
+        };
+        let vertices = |meshes: &ChunkMeshes| -> usize {
+            meshes.parts.iter().map(|(_, g)| g.positions.len()).sum()
+        };
+        for (block, before) in &plain.parts {
+            let (_, after) = welded.parts.iter().find(|(b, _)| b == block).unwrap();
+            // every triangle keeps its corners, in order
+            assert_eq!(corners(before), corners(after), "{block:?}");
+        }
+        assert!(vertices(&welded) < vertices(&plain));
+    }
 
-    for (face, group) in RIGHT_HANDED_Y_UP_CONFIG
-        .faces
-        .iter()
-        .zip(buffer.quads.groups.iter())
-    {
-        for quad in group.iter() {
-            let start = positions.len() as u32;
-            let mut face_positions = face.quad_mesh_positions(quad, lod as f32);
-            for p in &mut face_positions {
-                p[0] -= lod as f32;
-                p[1] -= lod as f32;
-                p[2] -= lod as f32;
+    #[test]
+    fn reused_scratch_buffers_leave_no_stale_voxels() {
+        let noise = test_noise();
+        let fresh = |coord, lod| {
+            build_mesh::<LOD1_PADDED>(coord, lod, &noise, None, &mut MeshScratch::default()).0
+        };
+        let first = fresh(IVec3::new(0, 2, 0), 1);
+
+        // a neighbour at another lod, then the same chunk again on this thread
+        build_geometry(IVec3::new(5, 1, -3), 2, &noise, None);
+        build_geometry(IVec3::new(-2, 2, 4), 1, &noise, None);
+        let (reused, _) = build_geometry(IVec3::new(0, 2, 0), 1, &noise, None);
+        assert_eq!(reused, first);
+    }
+
+    #[test]
+    fn block_counts_cover_every_block_of_the_chunk() {
+        let noise = NoiseResources::from_settings(&test_settings(), 256);
+        let coord = IVec3::new(0, 1, 0);
+        let fine = build_mesh::<LOD1_PADDED>(coord, 1, &noise, None, &mut MeshScratch::default())
+            .0
+            .blocks;
+        let coarse = build_mesh::<LOD2_PADDED>(coord, 2, &noise, None, &mut MeshScratch::default())
+            .0
+            .blocks;
+        let volume = CHUNK_SIZE.pow(3) as u64;
+        assert_eq!(fine.total(), volume);
+        assert_eq!(coarse.total(), volume);
+
+        let mut both = fine.clone();
+        both.merge(&coarse);
+        assert_eq!(both.total(), 2 * volume);
+        let lines = both.histogram();
+        assert!(!lines.is_empty() && lines.iter().all(|l| !l.starts_with("Empty")));
+        assert!(BlockCounts::default().histogram().is_empty());
+    }
+
+    #[test]
+    fn oversized_chunks_are_reported() {
+        let noise = test_noise();
+        let coord = surface_chunk(&noise, IVec2::ZERO);
+        let (meshes, _) = build_geometry(coord, 1, &noise, None);
+        let vertices = vertex_count(&meshes);
+        assert!(vertices > 0);
+        assert_eq!(meshes.over_budget(vertices), None);
+        let indices = meshes.parts.iter().map(|(_, p)| p.indices.len()).sum();
+        assert_eq!(meshes.over_budget(vertices - 1), Some((vertices, indices)));
+    }
+
+    #[test]
+    fn raycast_hits_the_first_solid_voxel() {
+        let noise = test_noise();
+        let ground = terrain_height(&noise, 3, 7);
+        let above = Vec3::new(3.5, ground as f32 + 4.5, 7.5);
+        assert_eq!(
+            raycast_block(&noise, above, Vec3::NEG_Y, 6.0),
+            Some(IVec3::new(3, ground, 7))
+        );
+        // out of reach, pointing at the sky, or without a direction
+        assert_eq!(raycast_block(&noise, above, Vec3::NEG_Y, 3.0), None);
+        assert_eq!(raycast_block(&noise, above, Vec3::Y, 6.0), None);
+        assert_eq!(raycast_block(&noise, above, Vec3::ZERO, 6.0), None);
+
+        let hit = raycast_block(&noise, above, Vec3::new(1.0, -1.0, 0.5), 12.0).unwrap();
+        assert!(
+            noise.block_solid(hit.x, hit.y, hit.z)
+                || feature_voxels(&noise, above, above).contains(&hit)
+        );
+    }
+
+    #[test]
+    fn raycast_hits_trees_and_boulders() {
+        let noise = test_noise();
+        let features = features_in(&noise, IVec2::splat(-64), IVec2::splat(64), &mut Vec::new());
+        let (pos, height, trunk) = features
+            .iter()
+            .find_map(|&(pos, height, feature)| match feature {
+                Feature::Tree { trunk } => Some((pos, height, trunk)),
+                Feature::Boulder { .. } => None,
+            })
+            .expect("no tree near the origin");
+        // straight down onto the canopy
+        let above = Vec3::new(
+            pos.x as f32 + 0.5,
+            (height + trunk) as f32 + 5.5,
+            pos.y as f32 + 0.5,
+        );
+        let canopy = IVec3::new(pos.x, height + trunk + 1, pos.y);
+        assert!(!noise.block_solid(canopy.x, canopy.y, canopy.z));
+        assert_eq!(
+            raycast_block(&noise, above, Vec3::NEG_Y, 10.0),
+            Some(canopy)
+        );
+
+        let (pos, height, radius) = features
+            .iter()
+            .find_map(|&(pos, height, feature)| match feature {
+                Feature::Boulder { radius } => Some((pos, height, radius)),
+                Feature::Tree { .. } => None,
+            })
+            .expect("no boulder near the origin");
+        let above = Vec3::new(
+            pos.x as f32 + 0.5,
+            (height + radius) as f32 + 5.5,
+            pos.y as f32 + 0.5,
+        );
+        assert_eq!(
+            raycast_block(&noise, above, Vec3::NEG_Y, 10.0),
+            Some(IVec3::new(pos.x, height + 1 + radius, pos.y))
+        );
+    }
+
+    #[test]
+    fn small_meshes_use_16_bit_indices() {
+        let noise = test_noise();
+        let coord = surface_chunk(&noise, IVec2::new(2, -1));
+        let (geometry, _) = build_geometry(coord, 2, &noise, None);
+        let (_, part) = geometry.parts[0].clone();
+        let expected = part.indices.clone();
+        let usage = WorldParams::default().mesh_usage;
+        let mesh = part.into_mesh(usage);
+        // chunk meshes stay readable on the CPU unless configured otherwise
+        assert_eq!(mesh.asset_usage, usage);
+        assert!(usage.contains(RenderAssetUsages::MAIN_WORLD));
+        assert!(matches!(mesh.indices(), Some(Indices::U16(_))));
+        let indices: Vec<u32> = mesh.indices().unwrap().iter().map(|i| i as u32).collect();
+        assert_eq!(indices, expected);
+
+        // more vertices than 16 bits can address
+        let mut dense = ChunkGeometry::default();
+        for i in 0..20_000 {
+            dense.push_quad(
+                [Vec3::X, Vec3::Z, Vec3::ONE, Vec3::Y].map(|c| c + Vec3::splat(i as f32)),
+                Vec3::Y,
+                [[1.0; 4]; 4],
+            );
+        }
+        let expected = dense.indices.clone();
+        let mesh = dense.into_mesh(usage);
+        assert!(matches!(mesh.indices(), Some(Indices::U32(_))));
+        let indices: Vec<u32> = mesh.indices().unwrap().iter().map(|i| i as u32).collect();
+        assert_eq!(indices, expected);
+    }
+
+    /// Time every [`test_app`] frame advances by, whatever the wall clock does.
+    const TEST_FRAME: Duration = Duration::from_millis(16);
+
+    fn test_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), StatesPlugin))
+            .init_asset::<Mesh>()
+            .init_asset::<ChunkMaterial>()
+            .insert_resource(WorldParams {
+                view_width: 1,
+                ..default()
+            })
+            .insert_resource(test_settings())
+            .insert_resource(TimeUpdateStrategy::ManualDuration(TEST_FRAME))
+            .init_resource::<KeyBindings>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .init_state::<AppState>()
+            .add_sub_state::<GameState>()
+            .add_plugins(WorldPlugin);
+        app
+    }
+
+    /// [`test_app`] in the `Playing` state over a superflat world laid out
+    /// by `params`, with the player camera placed at `player`.
+    fn playing_app(params: WorldParams, player: Transform) -> (App, Entity) {
+        let mut app = test_app();
+        let mut settings = test_settings();
+        settings.superflat = true;
+        app.insert_resource(settings).insert_resource(params);
+        let player = app.world_mut().spawn((player, PlayerCam::default())).id();
+        set_state(&mut app, AppState::Playing);
+        (app, player)
+    }
+
+    /// Moves the camera `player` to `x` along the x axis.
+    fn move_player(app: &mut App, player: Entity, x: f32) {
+        let mut transform = app.world_mut().get_mut::<Transform>(player).unwrap();
+        transform.translation.x = x;
+    }
+
+    fn set_state(app: &mut App, state: AppState) {
+        app.world_mut()
+            .resource_mut::<NextState<AppState>>()
+            .set(state);
+        app.update();
+    }
+
+    /// Runs frames until every queued chunk task has been turned into an entity.
+    ///
+    /// The time allowed grows with the chunk volume, so `chunk-64` builds get
+    /// enough time for their eightfold larger chunks.
+    fn run_until_generated(app: &mut App) {
+        run_until_generated_checking(app, |_| {});
+    }
+
+    /// [`run_until_generated`], calling `check` after every frame.
+    fn run_until_generated_checking(app: &mut App, mut check: impl FnMut(&mut App)) {
+        let budget = Duration::from_secs(20) * (CHUNK_SIZE / 32).max(1).pow(3) as u32;
+        let start = Instant::now();
+        while start.elapsed() < budget {
+            app.update();
+            check(app);
+            let world = app.world();
+            if !world.resource::<ChunkMap>().entities.is_empty()
+                && world.resource::<PendingTasks>().tasks.is_empty()
+            {
+                return;
             }
-            positions.extend_from_slice(&face_positions);
-            normals.extend_from_slice(&face.quad_mesh_normals());
-            indices.extend_from_slice(&face.quad_mesh_indices(start));
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        panic!("chunk generation did not finish within {budget:?}");
+    }
 
-            let voxel = voxels[shape.linearize(quad.minimum) as usize];
-            let color = if lod == 1 {
-                match voxel {
-                    GRASS => [0.1, 0.8, 0.1, 1.0],
-                    DIRT => [0.55, 0.27, 0.07, 1.0],
-                    STONE => [0.6, 0.6, 0.6, 1.0],
-                    _ => [1.0, 1.0, 1.0, 1.0],
+    /// Number of live chunk entities per coord, ignoring chunks fading out
+    /// after being replaced.
+    fn live_chunks_per_coord(app: &mut App) -> HashMap<IVec3, usize> {
+        let mut counts = HashMap::new();
+        let world = app.world_mut();
+        for (chunk, fade) in world.query::<(&Chunk, Option<&Fade>)>().iter(world) {
+            if !fade.is_some_and(|f| matches!(f.dir, FadeDir::Out)) {
+                *counts.entry(chunk.coord).or_default() += 1;
+            }
+        }
+        counts
+    }
+
+    /// Asserts every mapped entity is alive, not fading out and at its coord.
+    fn assert_map_consistent(app: &mut App) {
+        let mapped: Vec<_> = app
+            .world()
+            .resource::<ChunkMap>()
+            .entities
+            .iter()
+            .map(|(coord, entity)| (*coord, *entity))
+            .collect();
+        for (coord, entity) in mapped {
+            let entity = app.world().get_entity(entity);
+            assert!(entity.is_ok(), "chunk map points to a despawned entity");
+            let entity = entity.unwrap();
+            assert_eq!(entity.get::<Chunk>().map(|c| c.coord), Some(coord));
+            assert!(
+                !entity
+                    .get::<Fade>()
+                    .is_some_and(|f| matches!(f.dir, FadeDir::Out))
+            );
+        }
+    }
+
+    fn chunk_count(app: &mut App) -> usize {
+        app.world_mut()
+            .query_filtered::<Entity, With<Chunk>>()
+            .iter(app.world())
+            .count()
+    }
+
+    #[test]
+    fn returning_to_menu_clears_world_state() {
+        let mut app = test_app();
+        set_state(&mut app, AppState::Playing);
+        run_until_generated(&mut app);
+        assert!(chunk_count(&mut app) > 0);
+
+        set_state(&mut app, AppState::Menu);
+        app.update();
+        assert_eq!(chunk_count(&mut app), 0);
+        assert!(app.world().resource::<ChunkMap>().entities.is_empty());
+        assert!(app.world().resource::<PendingTasks>().tasks.is_empty());
+        assert!(app.world().resource::<SurfaceCache>().colors.is_empty());
+    }
+
+    #[test]
+    fn second_game_starts_without_ghost_chunks() {
+        let mut app = test_app();
+        set_state(&mut app, AppState::Playing);
+        run_until_generated(&mut app);
+        set_state(&mut app, AppState::Menu);
+
+        set_state(&mut app, AppState::Playing);
+        run_until_generated(&mut app);
+        let mapped = app.world().resource::<ChunkMap>().entities.len();
+        assert_eq!(chunk_count(&mut app), mapped);
+    }
+
+    #[test]
+    fn lod_bands_are_round() {
+        let params = WorldParams {
+            view_width: 8,
+            height_chunks: 1,
+            lod1_radius: 4,
+            ..default()
+        };
+        let lod = |x, z| required_lod(&params, IVec3::ZERO, IVec3::new(x, 0, z), 8);
+        // the band reaches as far along the axes as a square would
+        assert_eq!(lod(4, 0), Some(1));
+        assert_eq!(lod(0, -4), Some(1));
+        assert_eq!(lod(5, 0), Some(2));
+        // but not into the corners
+        assert_eq!(lod(2, 3), Some(1));
+        assert_eq!(lod(3, 3), Some(2));
+        assert_eq!(lod(-4, 4), Some(2));
+        // loading still covers the square
+        assert_eq!(lod(8, 8), Some(2));
+        assert_eq!(lod(9, 0), None);
+    }
+
+    #[test]
+    fn quality_presets_scale_the_view_at_every_chunk_size() {
+        let [low, medium, high] = QualityPreset::ALL.map(|preset| {
+            let mut params = WorldParams::default();
+            params.apply_quality(preset);
+            assert_eq!(params.render_radius(), params.view_width);
+            params
+        });
+        assert!(low.view_width < medium.view_width && medium.view_width < high.view_width);
+        assert!(low.lod1_radius < medium.lod1_radius && medium.lod1_radius < high.lod1_radius);
+        assert!(low.lod1_radius >= 1);
+
+        // the top tier is the out-of-the-box view
+        let defaults = WorldParams::default();
+        assert_eq!(high.view_width, defaults.view_width);
+        assert_eq!(high.lod1_radius, defaults.lod1_radius);
+    }
+
+    #[test]
+    fn lod_changes_never_duplicate_chunks() {
+        let (mut app, player) = playing_app(
+            WorldParams {
+                view_width: LOD1_RADIUS + 1,
+                height_chunks: 1,
+                ..default()
+            },
+            Transform::default(),
+        );
+
+        // stepping back and forth flips the lod of the ring of chunks at
+        // the full-detail radius every frame while their tasks are in flight
+        for frame in 0..30 {
+            let x = if frame % 2 == 0 { CHUNK_SIZE_F32 } else { 0.0 };
+            move_player(&mut app, player, x);
+            app.update();
+            let counts = live_chunks_per_coord(&mut app);
+            assert!(counts.values().all(|&n| n == 1), "duplicate chunk entities");
+            assert_map_consistent(&mut app);
+        }
+
+        run_until_generated(&mut app);
+        let counts = live_chunks_per_coord(&mut app);
+        assert!(counts.values().all(|&n| n == 1));
+        assert_eq!(
+            counts.len(),
+            app.world().resource::<ChunkMap>().entities.len()
+        );
+    }
+
+    #[test]
+    fn fast_movement_keeps_chunk_map_consistent() {
+        let (mut app, player) = playing_app(
+            WorldParams {
+                view_width: 2,
+                height_chunks: 1,
+                ..default()
+            },
+            Transform::default(),
+        );
+
+        // outrun chunk generation so tasks complete for coords already left behind
+        for frame in 0..40 {
+            move_player(&mut app, player, frame as f32 * 3.0 * CHUNK_SIZE_F32);
+            app.update();
+            assert_map_consistent(&mut app);
+            let counts = live_chunks_per_coord(&mut app);
+            assert!(counts.values().all(|&n| n == 1), "duplicate chunk entities");
+        }
+
+        run_until_generated(&mut app);
+        assert_map_consistent(&mut app);
+        let player_chunk = IVec3::new(39 * 3, 0, 0);
+        let params = app.world().resource::<WorldParams>();
+        let map = &app.world().resource::<ChunkMap>().entities;
+        assert!(
+            map.keys()
+                .all(|c| required_lod(params, player_chunk, *c, params.unload_radius()).is_some())
+        );
+    }
+
+    #[test]
+    fn far_plane_reaches_the_farthest_loaded_corner() {
+        let mut params = WorldParams {
+            view_width: 64,
+            near_plane: 0.0,
+            ..default()
+        };
+        let (near, far) = params.clip_planes();
+        assert_eq!(near, MIN_NEAR_PLANE);
+        let corner = Vec3::new(
+            params.view_distance(),
+            params.max_height() as f32,
+            params.view_distance(),
+        );
+        assert!(far > corner.length());
+        // small worlds keep Bevy's default reach
+        params.view_width = 1;
+        assert_eq!(params.clip_planes().1, MIN_FAR_PLANE);
+    }
+
+    #[test]
+    fn generation_timing_covers_the_recent_window_per_lod() {
+        let mut stats = GenerationStats::default();
+        let ms = Duration::from_millis;
+        stats.record(1, ms(100));
+        for _ in 0..TIMING_WINDOW - 1 {
+            stats.record(1, ms(2));
+        }
+        stats.record(2, ms(4));
+        // the slow lod 1 chunk dropped out of the window
+        assert_eq!(stats.timing(1), Some((ms(2), ms(2))));
+        assert_eq!(stats.timing(2), Some((ms(4), ms(4))));
+        assert_eq!(stats.timing(3), None);
+    }
+
+    #[test]
+    fn bounded_worlds_generate_only_inside_the_bounds() {
+        let bounds = ChunkBounds::around_origin(1);
+        // standing at the edge, most of the view lies outside
+        let (mut app, player) = playing_app(
+            WorldParams {
+                view_width: 3,
+                height_chunks: 1,
+                bounds: Some(bounds),
+                ..default()
+            },
+            Transform::from_xyz(CHUNK_SIZE_F32 * 1.5, 0.0, 0.0),
+        );
+        run_until_generated(&mut app);
+        let map = &app.world().resource::<ChunkMap>().entities;
+        assert_eq!(map.len(), 9);
+        assert!(map.keys().all(|c| bounds.contains(c.xz())));
+        assert!(app.world().resource::<LoadingProgress>().finished);
+
+        // shrinking the bounds unloads what falls outside
+        app.world_mut().resource_mut::<WorldParams>().bounds = Some(ChunkBounds::around_origin(0));
+        move_player(&mut app, player, 0.0);
+        run_until_generated(&mut app);
+        let map = &app.world().resource::<ChunkMap>().entities;
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), vec![IVec3::ZERO]);
+
+        assert_eq!(
+            bounds.clamp(Vec3::new(-1000.0, 5.0, 10.0)),
+            Vec3::new(-CHUNK_SIZE_F32, 5.0, 10.0)
+        );
+    }
+
+    #[test]
+    fn loading_finishes_once_the_area_around_the_player_exists() {
+        let (mut app, _) = playing_app(
+            WorldParams {
+                view_width: 2,
+                height_chunks: 2,
+                ..default()
+            },
+            Transform::default(),
+        );
+        let progress = app.world().resource::<LoadingProgress>();
+        assert!(!progress.finished);
+        assert_eq!(progress.needed, 3 * 3 * 2);
+
+        run_until_generated(&mut app);
+        let progress = app.world().resource::<LoadingProgress>();
+        assert!(progress.finished);
+        assert_eq!(progress.done, progress.needed);
+
+        // a rebuilt world has to load again
+        app.world_mut().send_event(RegenerateWorld);
+        app.update();
+        assert!(!app.world().resource::<LoadingProgress>().finished);
+        run_until_generated(&mut app);
+        assert!(app.world().resource::<LoadingProgress>().finished);
+    }
+
+    #[test]
+    fn frozen_chunks_stay_put_until_thawed() {
+        let (mut app, player) = playing_app(
+            WorldParams {
+                view_width: 1,
+                height_chunks: 1,
+                ..default()
+            },
+            Transform::default(),
+        );
+        run_until_generated(&mut app);
+        let loaded: HashSet<IVec3> = app
+            .world()
+            .resource::<ChunkMap>()
+            .entities
+            .keys()
+            .copied()
+            .collect();
+
+        app.world_mut().resource_mut::<FreezeChunks>().0 = true;
+        move_player(&mut app, player, 20.0 * CHUNK_SIZE_F32);
+        for _ in 0..20 {
+            app.update();
+        }
+        let map = &app.world().resource::<ChunkMap>().entities;
+        assert_eq!(map.keys().copied().collect::<HashSet<_>>(), loaded);
+        assert!(app.world().resource::<PendingTasks>().tasks.is_empty());
+
+        app.world_mut().resource_mut::<FreezeChunks>().0 = false;
+        run_until_generated(&mut app);
+        let map = &app.world().resource::<ChunkMap>().entities;
+        assert!(map.keys().all(|c| c.x >= 19 && !loaded.contains(c)));
+    }
+
+    #[test]
+    fn regenerating_replaces_the_camera_chunk_while_frozen() {
+        // looking straight up never hits the ground, so the camera chunk is taken
+        let (mut app, _) = playing_app(
+            WorldParams {
+                view_width: 1,
+                height_chunks: 1,
+                ..default()
+            },
+            Transform::from_xyz(1.0, 1.0, 1.0).looking_to(Vec3::Y, Vec3::X),
+        );
+        run_until_generated(&mut app);
+        let before = app.world().resource::<ChunkMap>().entities.clone();
+        assert!(before.contains_key(&IVec3::ZERO));
+
+        app.world_mut().resource_mut::<FreezeChunks>().0 = true;
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::F5);
+        app.update();
+        // no input plugin runs here to clear the press
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .reset_all();
+        run_until_generated(&mut app);
+        let map = &app.world().resource::<ChunkMap>().entities;
+        assert_eq!(map.len(), before.len());
+        assert_ne!(map[&IVec3::ZERO], before[&IVec3::ZERO]);
+        assert!(
+            map.iter()
+                .filter(|(c, _)| **c != IVec3::ZERO)
+                .all(|(c, e)| before[c] == *e)
+        );
+        assert_map_consistent(&mut app);
+    }
+
+    #[test]
+    fn world_positions_round_down_to_their_chunk() {
+        let size = CHUNK_SIZE_F32;
+        assert_eq!(world_to_chunk(Vec3::ZERO), IVec3::ZERO);
+        assert_eq!(world_to_chunk(Vec3::splat(size - 0.01)), IVec3::ZERO);
+        assert_eq!(world_to_chunk(Vec3::splat(size)), IVec3::ONE);
+        // negative positions belong to the chunk below, not to chunk zero
+        assert_eq!(world_to_chunk(Vec3::splat(-0.01)), IVec3::NEG_ONE);
+        assert_eq!(world_to_chunk(Vec3::splat(-size)), IVec3::NEG_ONE);
+        assert_eq!(world_to_chunk(Vec3::splat(-size - 0.01)), IVec3::splat(-2));
+        assert_eq!(
+            world_to_chunk(Vec3::new(-0.5, 3.0 * size + 1.0, 2.5 * size)),
+            IVec3::new(-1, 3, 2)
+        );
+    }
+
+    #[test]
+    fn voxel_indices_round_trip_at_negative_coords_and_lod_2() {
+        let coords = [IVec3::new(-1, 0, -2), IVec3::new(-3, -1, 2), IVec3::ZERO];
+        for coord in coords {
+            for (lod, n) in [(1, LOD1_PADDED), (2, LOD2_PADDED)] {
+                for voxel in [UVec3::ZERO, UVec3::ONE, UVec3::new(3, n - 1, 2)] {
+                    let voxel = voxel.as_ivec3();
+                    let block = voxel_to_world(coord, lod, voxel);
+                    let back = |block| match lod {
+                        1 => world_to_voxel::<LOD1_PADDED>(coord, lod, block),
+                        _ => world_to_voxel::<LOD2_PADDED>(coord, lod, block),
+                    };
+                    assert_eq!(back(block), Some(voxel), "{coord} lod {lod}");
+                    // at lod 2 a voxel samples the lower of every two blocks
+                    let upper = block + IVec3::splat(lod as i32 - 1);
+                    assert_eq!(back(upper), Some(voxel), "{coord} lod {lod}");
                 }
-            } else {
-                let lx = quad.minimum[0] - 1;
-                let lz = quad.minimum[2] - 1;
-                surface_colors[(lx * CHUNK_SIZE_U32 + lz) as usize]
-            };
-            colors.extend_from_slice(&[color; 4]);
+            }
         }
+        // voxel 1 holds the chunk's first block, voxel 0 the one below it
+        let coord = IVec3::new(-1, 0, -1);
+        let origin = coord * CHUNK_SIZE;
+        assert_eq!(voxel_to_world(coord, 1, IVec3::ONE), origin);
+        assert_eq!(voxel_to_world(coord, 2, IVec3::splat(2)), origin + 2);
+        assert_eq!(voxel_to_world(coord, 1, IVec3::ZERO), origin - 1);
+        assert_eq!(
+            world_to_voxel::<LOD1_PADDED>(coord, 1, origin - 2),
+            None,
+            "beyond the padding"
+        );
     }
 
-    use bevy::render::mesh::PrimitiveTopology;
-    use bevy::render::render_asset::RenderAssetUsages;
-    let mut mesh = Mesh::new(
-        PrimitiveTopology::TriangleList,
-        RenderAssetUsages::default(),
-    );
-    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
-    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
-    mesh.insert_attribute(Mesh::ATTRIBUTE_COLOR, colors);
-    mesh.insert_indices(Indices::U32(indices));
-    (mesh, surface_colors)
+    #[test]
+    fn blocks_land_in_their_voxel_in_negative_chunks() {
+        let coord = IVec3::new(-1, 0, -1);
+        let shape = ConstShape3u32::<LOD1_PADDED, LOD1_PADDED, LOD1_PADDED> {};
+        let at = |voxels: &[BlockType], x: u32, y: u32, z: u32| {
+            voxels[shape.linearize([x, y, z]) as usize]
+        };
+        let mut voxels = vec![EMPTY; (LOD1_PADDED * LOD1_PADDED * LOD1_PADDED) as usize];
+        let size = CHUNK_SIZE as u32;
+
+        // world block -1 is the last of chunk -1 along x
+        set_block::<LOD1_PADDED>(
+            &mut voxels,
+            coord,
+            IVec3::new(-1, 5, -CHUNK_SIZE),
+            STONE,
+            false,
+        );
+        assert_eq!(at(&voxels, size, 6, 1), STONE);
+        // blocks 0 and 1 are only padding for it, block 2 lies outside
+        set_block::<LOD1_PADDED>(&mut voxels, coord, IVec3::new(0, 5, -5), WOOD, false);
+        assert_eq!(at(&voxels, size + 1, 6, size - 4), WOOD);
+        let before = voxels.clone();
+        set_block::<LOD1_PADDED>(&mut voxels, coord, IVec3::new(2, 5, -5), WOOD, false);
+        assert_eq!(voxels, before);
+
+        // leaves fill empty voxels only, trunks replace whatever is there
+        set_block::<LOD1_PADDED>(
+            &mut voxels,
+            coord,
+            IVec3::new(-1, 5, -CHUNK_SIZE),
+            LEAF,
+            false,
+        );
+        assert_eq!(at(&voxels, size, 6, 1), STONE);
+        set_block::<LOD1_PADDED>(
+            &mut voxels,
+            coord,
+            IVec3::new(-1, 5, -CHUNK_SIZE),
+            WOOD,
+            true,
+        );
+        assert_eq!(at(&voxels, size, 6, 1), WOOD);
+    }
+
+    #[test]
+    fn voxels_map_to_the_chunk_that_draws_them() {
+        let size = CHUNK_SIZE;
+        assert_eq!(chunk_of_voxel(IVec3::splat(1)), IVec3::ZERO);
+        assert_eq!(chunk_of_voxel(IVec3::splat(size)), IVec3::ZERO);
+        assert_eq!(chunk_of_voxel(IVec3::splat(size + 1)), IVec3::ONE);
+        assert_eq!(chunk_of_voxel(IVec3::ZERO), IVec3::NEG_ONE);
+    }
+
+    #[test]
+    fn pacing_at_the_view_edge_keeps_chunks_loaded() {
+        let (mut app, player) = playing_app(
+            WorldParams {
+                view_width: 2,
+                height_chunks: 1,
+                unload_margin: 2,
+                ..default()
+            },
+            Transform::default(),
+        );
+        run_until_generated(&mut app);
+        let before: HashMap<IVec3, Entity> = app.world().resource::<ChunkMap>().entities.clone();
+
+        for x in [1.5, -0.5, 1.5, -0.5] {
+            move_player(&mut app, player, x * CHUNK_SIZE_F32);
+            run_until_generated(&mut app);
+        }
+
+        let map = &app.world().resource::<ChunkMap>().entities;
+        for (coord, entity) in before {
+            assert_eq!(
+                map.get(&coord),
+                Some(&entity),
+                "chunk {coord} was regenerated"
+            );
+        }
+    }
+
+    #[test]
+    fn unloaded_chunks_reappear_from_the_stash() {
+        let round_trip = |stash_budget: usize| {
+            let (mut app, player) = playing_app(
+                WorldParams {
+                    view_width: 1,
+                    height_chunks: 1,
+                    unload_margin: 0,
+                    stash_budget,
+                    ..default()
+                },
+                Transform::default(),
+            );
+            run_until_generated(&mut app);
+            let before = app.world().resource::<ChunkMap>().entities.clone();
+
+            move_player(&mut app, player, 10.5 * CHUNK_SIZE_F32);
+            run_until_generated(&mut app);
+            move_player(&mut app, player, 0.5 * CHUNK_SIZE_F32);
+            app.update();
+            let back = app.world().resource::<ChunkMap>().entities.clone();
+            let queued = app.world().resource::<PendingTasks>().tasks.len();
+            (before, back, queued)
+        };
+
+        let (before, back, queued) = round_trip(WorldParams::default().stash_budget);
+        assert_eq!(back, before, "stashed chunks were not reused");
+        assert_eq!(queued, 0);
+
+        // without a budget every chunk is generated anew
+        let (before, back, queued) = round_trip(0);
+        assert!(back.is_empty());
+        assert_eq!(queued, before.len());
+    }
+
+    #[test]
+    fn instant_lod_swap_never_leaves_a_coord_empty() {
+        let (mut app, player) = playing_app(
+            WorldParams {
+                view_width: LOD1_RADIUS + 1,
+                height_chunks: 1,
+                lod_fade: 0.0,
+                ..default()
+            },
+            Transform::default(),
+        );
+        run_until_generated(&mut app);
+        let loaded: Vec<IVec3> = app
+            .world()
+            .resource::<ChunkMap>()
+            .entities
+            .keys()
+            .copied()
+            .collect();
+
+        // the ring at the full-detail radius drops to lod 2 and back
+        for x in [CHUNK_SIZE_F32, 0.0] {
+            move_player(&mut app, player, x);
+            run_until_generated_checking(&mut app, |app| {
+                let world = app.world_mut();
+                let mut counts: HashMap<IVec3, usize> = HashMap::new();
+                for chunk in world.query::<&Chunk>().iter(world) {
+                    *counts.entry(chunk.coord).or_default() += 1;
+                }
+                for coord in &loaded {
+                    if coord.x > -LOD1_RADIUS {
+                        assert_eq!(
+                            counts.get(coord),
+                            Some(&1),
+                            "chunk {coord} swap was not atomic"
+                        );
+                    }
+                }
+            });
+        }
+    }
+
+    #[test]
+    fn lod_fade_hands_over_within_the_fade_time() {
+        let fade_frames = 8;
+        let (mut app, player) = playing_app(
+            WorldParams {
+                view_width: LOD1_RADIUS + 1,
+                height_chunks: 1,
+                lod_fade: fade_frames as f32 * TEST_FRAME.as_secs_f32(),
+                ..default()
+            },
+            Transform::default(),
+        );
+        run_until_generated(&mut app);
+
+        // counts the frames every replaced chunk stays around after its swap;
+        // the timer may need one tick more from rounding and the despawn
+        // lands a frame after it finishes
+        let mut fading: HashMap<Entity, u32> = HashMap::new();
+        let mut count_fades = |app: &mut App| {
+            let world = app.world_mut();
+            for (entity, fade) in world.query::<(Entity, &Fade)>().iter(world) {
+                if matches!(fade.dir, FadeDir::Out) {
+                    let frames = fading.entry(entity).or_default();
+                    *frames += 1;
+                    assert!(*frames <= fade_frames + 2, "chunk faded out too slowly");
+                }
+            }
+        };
+        move_player(&mut app, player, CHUNK_SIZE_F32);
+        run_until_generated_checking(&mut app, &mut count_fades);
+        for _ in 0..fade_frames + 2 {
+            app.update();
+            count_fades(&mut app);
+        }
+        assert!(!fading.is_empty(), "no chunk changed its lod");
+        let world = app.world_mut();
+        assert_eq!(world.query::<&Fade>().iter(world).count(), 0);
+        assert_map_consistent(&mut app);
+    }
+
+    #[test]
+    fn orbit_mode_generates_around_the_origin() {
+        let (mut app, _) = playing_app(
+            WorldParams {
+                view_width: 2,
+                height_chunks: 1,
+                orbit: true,
+                ..default()
+            },
+            Transform::from_xyz(40.0 * CHUNK_SIZE_F32, 0.0, 0.0),
+        );
+        run_until_generated(&mut app);
+
+        let map = &app.world().resource::<ChunkMap>().entities;
+        assert_eq!(map.len(), 25);
+        assert!(map.keys().all(|c| c.x.abs() <= 2 && c.z.abs() <= 2));
+    }
 }